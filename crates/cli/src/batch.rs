@@ -0,0 +1,200 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use brick_cartographer_core::Renderer;
+use clap::{Args, Parser};
+use serde_json::{Map, Value};
+
+use crate::render::{self, RenderArgs};
+
+#[derive(Args)]
+pub struct BatchArgs {
+    /// Job file (.toml, or .json) listing the saves to render
+    jobs: PathBuf,
+}
+
+// One render's options, parsed the same way as the command line
+#[derive(Parser)]
+#[command(no_binary_name = true)]
+struct JobArgs {
+    #[command(flatten)]
+    render: RenderArgs,
+}
+
+// A save and the renders made from it once it's loaded
+struct Job {
+    save: PathBuf,
+    outputs: Vec<Result<RenderArgs, String>>,
+}
+
+pub fn run(args: BatchArgs) -> Result<(), String> {
+    let jobs = read_jobs(&args.jobs)?;
+    let total: usize = jobs.iter().map(|job| job.outputs.len()).sum();
+    if total == 0 {
+        return Err(format!("{} lists no jobs", args.jobs.display()));
+    }
+
+    // The point of a batch: one device for every render
+    let mut renderer = pollster::block_on(Renderer::new(None))?;
+
+    let started = Instant::now();
+    let mut done = 0;
+    let mut failed = 0;
+    for job in &jobs {
+        let loading = Instant::now();
+        let save = job.outputs.iter().find_map(|output| output.as_ref().ok()).map(render::load);
+        if let Some(Ok(_)) = &save {
+            eprintln!("Loaded {} in {:.2}s", job.save.display(), loading.elapsed().as_secs_f32());
+        }
+
+        for output in &job.outputs {
+            done += 1;
+            let rendering = Instant::now();
+            let result = match (output, &save) {
+                (Err(e), _) => Err(e.clone()),
+                (Ok(options), Some(Ok(save))) => render::write_png(save, options, &mut renderer),
+                (Ok(_), Some(Err(e))) => Err(e.clone()),
+                (Ok(_), None) => unreachable!("a valid output loads the save"),
+            };
+            let name = match output {
+                Ok(options) => options.output_path(),
+                Err(_) => job.save.clone(),
+            };
+            match result {
+                Ok(()) => eprintln!(
+                    "[{}/{}] {} done in {:.2}s",
+                    done,
+                    total,
+                    name.display(),
+                    rendering.elapsed().as_secs_f32()
+                ),
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("[{}/{}] {} FAILED: {}", done, total, name.display(), e);
+                }
+            }
+        }
+    }
+
+    eprintln!(
+        "Finished {} renders in {:.2}s, {} failed",
+        total,
+        started.elapsed().as_secs_f32(),
+        failed
+    );
+    if failed > 0 {
+        return Err(format!("{} of {} renders failed", failed, total));
+    }
+    Ok(())
+}
+
+// Reads a job file shaped like
+//
+//   [defaults]              options shared by every job
+//   scale = 0.2
+//
+//   [[jobs]]
+//   save = "City.brdb"      options for this job, over the defaults
+//   outputs = [             optional: several renders of the same save,
+//     { output = "city.png" },                 each over the job's options
+//     { output = "city-height.png", heightmap = true },
+//   ]
+//
+// Options are the command line's long names, with `true` for flags and
+// lists for coordinates. Relative paths are taken from the job file's
+// folder.
+fn read_jobs(path: &Path) -> Result<Vec<Job>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+    let is_json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let document: Value = if is_json {
+        serde_json::from_str(&text).map_err(|e| format!("Error parsing {}: {}", path.display(), e))?
+    } else {
+        toml::from_str(&text).map_err(|e| format!("Error parsing {}: {}", path.display(), e))?
+    };
+    let base = path.parent().unwrap_or(Path::new(""));
+
+    let empty = Map::new();
+    let defaults = match document.get("defaults") {
+        Some(Value::Object(defaults)) => defaults,
+        Some(_) => return Err("'defaults' must be a table of options".to_string()),
+        None => &empty,
+    };
+    let jobs = match document.get("jobs") {
+        Some(Value::Array(jobs)) => jobs,
+        _ => return Err(format!("{} needs a list of jobs", path.display())),
+    };
+
+    jobs.iter()
+        .enumerate()
+        .map(|(i, job)| {
+            let Value::Object(job) = job else {
+                return Err(format!("job {} must be a table of options", i + 1));
+            };
+            let mut options = defaults.clone();
+            options.extend(job.iter().filter(|(key, _)| *key != "outputs").map(|(k, v)| (k.clone(), v.clone())));
+            let save = match options.get("save") {
+                Some(Value::String(save)) => base.join(save),
+                _ => return Err(format!("job {} needs a save path", i + 1)),
+            };
+
+            let outputs = match job.get("outputs") {
+                None => vec![job_args(&options, base)],
+                Some(Value::Array(outputs)) => outputs
+                    .iter()
+                    .map(|output| match output {
+                        Value::Object(output) if !output.contains_key("save") => {
+                            let mut options = options.clone();
+                            options.extend(output.iter().map(|(k, v)| (k.clone(), v.clone())));
+                            job_args(&options, base)
+                        }
+                        Value::Object(_) => Err("outputs share their job's save".to_string()),
+                        _ => Err("outputs must be tables of options".to_string()),
+                    })
+                    .collect(),
+                Some(_) => return Err(format!("job {}'s outputs must be a list", i + 1)),
+            };
+            Ok(Job { save, outputs })
+        })
+        .collect()
+}
+
+// Turns a table of options into render arguments by spelling it out as a
+// command line, so jobs accept exactly what the CLI does
+fn job_args(options: &Map<String, Value>, base: &Path) -> Result<RenderArgs, String> {
+    let mut argv = Vec::new();
+    let mut save = None;
+    for (key, value) in options {
+        let value = match value {
+            Value::Bool(true) => {
+                argv.push(format!("--{}", key.replace('_', "-")));
+                continue;
+            }
+            Value::Bool(false) => continue,
+            Value::Array(items) => items.iter().map(scalar).collect::<Option<Vec<_>>>().map(|items| items.join(",")),
+            value => scalar(value),
+        };
+        let Some(value) = value else {
+            return Err(format!("option '{}' must be a string, number, true/false or a list of those", key));
+        };
+        match key.as_str() {
+            "save" => save = Some(base.join(value)),
+            "output" => argv.push(format!("--output={}", base.join(value).display())),
+            _ => argv.push(format!("--{}={}", key.replace('_', "-"), value)),
+        }
+    }
+    argv.push("--".to_string());
+    argv.push(save.ok_or("job needs a save path")?.display().to_string());
+
+    let args = JobArgs::try_parse_from(argv)
+        .map_err(|e| e.to_string().lines().next().unwrap_or_default().trim_start_matches("error: ").to_string())?;
+    args.render.validate()?;
+    Ok(args.render)
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use brick_cartographer_core::legacy::{read_legacy, write_save};
+use brick_cartographer_core::save::SaveFormat;
+use clap::Args;
+
+use crate::read_save;
+
+#[derive(Args)]
+pub struct ConvertArgs {
+    /// Save file to convert (.brs, .brz, or .brdb)
+    input: PathBuf,
+
+    /// Path to write; its extension picks the format
+    output: PathBuf,
+}
+
+pub fn run(args: ConvertArgs) -> Result<(), String> {
+    let target = SaveFormat::from_path(&args.output)
+        .ok_or_else(|| format!("{}: unknown save extension, expected .brs, .brz or .brdb", args.output.display()))?;
+
+    let body = read_save(&args.input)?;
+    let source = SaveFormat::detect(&body);
+    let legacy = read_legacy(&body)?;
+    let bricks = legacy.save.bricks.len();
+
+    // What reading the source dropped, then what the target can't hold
+    let mut lost = legacy.lost;
+    lost.extend(write_save(legacy.save, target, &args.output)?);
+    eprintln!("Converted {} bricks from .{} to .{}", bricks, source.extension(), target.extension());
+    if !lost.is_empty() {
+        eprintln!("Not representable in .{}:", target.extension());
+        for loss in &lost {
+            eprintln!("  - {}", loss);
+        }
+    }
+    eprintln!("Wrote {}", args.output.display());
+
+    Ok(())
+}
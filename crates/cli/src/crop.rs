@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use brick_cartographer_core::crop::{crop, CropRegion};
+use brick_cartographer_core::legacy::{read_legacy, write_save};
+use brick_cartographer_core::save::SaveFormat;
+use clap::Args;
+
+use crate::{parse_ints, read_save};
+
+#[derive(Args)]
+pub struct CropArgs {
+    /// Save file to crop
+    save: PathBuf,
+
+    /// Area to keep as x1,y1,x2,y2 in save units (10 per stud); bricks
+    /// whose origin lies inside are kept
+    #[arg(long, value_parser = parse_ints::<4>, allow_hyphen_values = true)]
+    rect: [i32; 4],
+
+    /// Height range to keep as z1,z2 in save units
+    #[arg(long, value_parser = parse_ints::<2>, allow_hyphen_values = true)]
+    z: Option<[i32; 2]>,
+
+    /// Output path, whose extension picks .brs, .brz or .brdb (defaults to
+    /// the save's name with a -crop suffix, in the save's format)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: CropArgs) -> Result<(), String> {
+    let body = read_save(&args.save)?;
+    let output = args.output.clone().unwrap_or_else(|| {
+        let stem = args.save.file_stem().unwrap_or_default().to_string_lossy();
+        args.save.with_file_name(format!("{}-crop.{}", stem, SaveFormat::detect(&body).extension()))
+    });
+    let target = SaveFormat::from_path(&output)
+        .ok_or_else(|| format!("{}: unknown save extension, expected .brs, .brz or .brdb", output.display()))?;
+
+    let mut legacy = read_legacy(&body)?;
+    let total = legacy.save.bricks.len();
+    for loss in &legacy.lost {
+        eprintln!("Not carried over: {}", loss);
+    }
+
+    let [x1, y1, x2, y2] = args.rect;
+    let region = CropRegion { rect: (x1, y1, x2, y2), z: args.z.map(|[z1, z2]| (z1, z2)) };
+    let kept = crop(&mut legacy.save, &region);
+    if kept == 0 {
+        return Err("no bricks inside the crop region".to_string());
+    }
+    eprintln!("Kept {} of {} bricks", kept, total);
+
+    for loss in write_save(legacy.save, target, &output)? {
+        eprintln!("Not carried over: {}", loss);
+    }
+    eprintln!("Wrote {}", output.display());
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use brick_cartographer_core::floors::{detect_storeys, DEFAULT_HEADROOM};
+use brick_cartographer_core::Renderer;
+use clap::Args;
+use serde_json::json;
+
+use crate::render::{self, RenderArgs};
+
+#[derive(Args)]
+pub struct FloorsArgs {
+    // The output path names the images: City.png becomes City_floor_0.png,
+    // City_floor_1.png and so on, listed in City_floors.json
+    #[command(flatten)]
+    render: RenderArgs,
+
+    /// Least height between two floors, in save units (12 per brick)
+    #[arg(long, default_value_t = DEFAULT_HEADROOM)]
+    headroom: i32,
+}
+
+pub fn run(mut args: FloorsArgs) -> Result<(), String> {
+    if args.headroom <= 0 {
+        return Err("headroom must be greater than zero".to_string());
+    }
+    args.render.validate()?;
+    let save = render::load(&args.render)?;
+    let storeys = detect_storeys(save.all_bricks(), args.headroom);
+    if storeys.is_empty() {
+        return Err("no floors found".to_string());
+    }
+    eprintln!("Found {} floors", storeys.len());
+
+    let output = args.render.output_path();
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let mut renderer = pollster::block_on(Renderer::new(None))?;
+    let mut index = Vec::new();
+    for (i, storey) in storeys.iter().enumerate() {
+        // Every floor is framed like the whole build, so they line up
+        args.render.set_storey(*storey)?;
+        let path = output.with_file_name(format!("{}_floor_{}.png", stem, i));
+        let png = render::render(&save, &args.render, &mut renderer)?;
+        std::fs::write(&path, png).map_err(|e| format!("Error writing {}: {}", path.display(), e))?;
+        eprintln!("Wrote {} (floor at {})", path.display(), storey.floor);
+        index.push(json!({
+            "image": path.file_name().map(|name| name.to_string_lossy()),
+            "floor": storey.floor,
+            "next": storey.next,
+            "area": storey.area,
+        }));
+    }
+
+    let path = output.with_file_name(format!("{}_floors.json", stem));
+    let body = serde_json::to_string_pretty(&json!({ "floors": index })).map_err(|e| format!("Error encoding floors: {}", e))?;
+    std::fs::write(&path, body).map_err(|e| format!("Error writing {}: {}", path.display(), e))?;
+    eprintln!("Wrote {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use brick_cartographer_core::geojson::{export_geojson, GeoJsonOptions};
+use brick_cartographer_core::markers::MarkerLayers;
+use clap::Args;
+
+use crate::read_save;
+
+#[derive(Args)]
+pub struct GeoJsonArgs {
+    /// Save file to export
+    save: PathBuf,
+
+    /// Output path (defaults to the save's name with a .geojson extension)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Merge abutting rectangular bricks of the same color, height, owner
+    /// and material into larger rectangles
+    #[arg(long)]
+    merge: bool,
+
+    /// Markers to include as points: a comma-separated list of spawn, light,
+    /// audio and interact, "all", or "" for none
+    #[arg(long, default_value = "all")]
+    markers: MarkerLayers,
+}
+
+pub fn run(args: GeoJsonArgs) -> Result<(), String> {
+    let output = args.output.unwrap_or_else(|| args.save.with_extension("geojson"));
+    let body = read_save(&args.save)?;
+
+    let file = File::create(&output).map_err(|e| format!("Error creating {}: {}", output.display(), e))?;
+    let options = GeoJsonOptions { merge: args.merge, markers: args.markers };
+    let features = export_geojson(&body, BufWriter::new(file), &options)?;
+    eprintln!("Wrote {} features to {}", features, output.display());
+
+    Ok(())
+}
@@ -1,140 +1,80 @@
-use std::path::PathBuf;
+use std::path::Path;
 
-use brick_cartographer_core::save::GeometryMode;
-use brick_cartographer_core::{Renderer, SaveData, TileStitcher};
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+mod batch;
+mod convert;
+mod crop;
+mod floors;
+mod geojson;
+mod render;
+mod section;
+mod serve;
+mod structures;
+mod watch;
 
 /// Render a PNG map of a Brickadia save (.brs, .brz, or .brdb).
 #[derive(Parser)]
-#[command(version, about)]
-struct Args {
-    /// Save file to render
-    save: PathBuf,
-
-    /// Output PNG path (defaults to the save's name with a .png extension)
-    #[arg(short, long)]
-    output: Option<PathBuf>,
-
-    /// Pixels per save unit. The website's default view is 0.1
-    #[arg(short, long, default_value_t = 0.1)]
-    scale: f32,
-
-    /// Draw brick outlines
-    #[arg(long)]
-    outlines: bool,
-
-    /// Draw bricks without their fill (outlines only)
-    #[arg(long)]
-    no_fill: bool,
-
-    /// Color bricks by height instead of their own color
-    #[arg(long, conflicts_with_all = ["outlines", "no_fill"])]
-    heightmap: bool,
-
-    /// Rotation in degrees
-    #[arg(short, long, default_value_t = 0.0)]
-    rotation: f32,
+#[command(version, about, args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    // Rendering is the default, so `brick-cartographer City.brs` needs no
+    // subcommand
+    #[command(flatten)]
+    render: render::RenderArgs,
+}
 
-    /// Margin around the build, in pixels
-    #[arg(short, long, default_value_t = 32)]
-    margin: u32,
+#[derive(Subcommand)]
+enum Command {
+    /// Export brick footprints and markers as GeoJSON
+    Geojson(geojson::GeoJsonArgs),
+    /// Cut a rectangle out of a save into a new save
+    Crop(crop::CropArgs),
+    /// Convert a save to another format (.brs, .brz or .brdb)
+    Convert(convert::ConvertArgs),
+    /// Render many saves from a TOML or JSON job file with one renderer
+    Batch(batch::BatchArgs),
+    /// Re-render a save whenever it changes on disk
+    Watch(watch::WatchArgs),
+    /// Serve map tiles of a save over HTTP on localhost
+    Serve(serve::ServeArgs),
+    /// Render a vertical cross-section along a line: distance along it
+    /// across, height up
+    Section(section::SectionArgs),
+    /// Find a build's floors and render each with the walls standing on
+    /// it, plus a JSON index of their heights
+    Floors(floors::FloorsArgs),
+    /// Find the separate builds in a save, listed in JSON and numbered on an
+    /// index map, with optional thumbnails of each
+    Structures(structures::StructuresArgs),
 }
 
 fn main() -> Result<(), String> {
-    let args = Args::parse();
-
-    if args.scale <= 0.0 {
-        return Err("scale must be greater than zero".to_string());
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Geojson(args)) => geojson::run(args),
+        Some(Command::Crop(args)) => crop::run(args),
+        Some(Command::Convert(args)) => convert::run(args),
+        Some(Command::Batch(args)) => batch::run(args),
+        Some(Command::Watch(args)) => watch::run(args),
+        Some(Command::Serve(args)) => serve::run(args),
+        Some(Command::Section(args)) => section::run(args),
+        Some(Command::Floors(args)) => floors::run(args),
+        Some(Command::Structures(args)) => structures::run(args),
+        None => render::run(cli.render),
     }
-    if args.no_fill && !args.outlines {
-        return Err("--no-fill needs --outlines, or the map would be empty".to_string());
-    }
-
-    let output = args.output.clone().unwrap_or_else(|| args.save.with_extension("png"));
-
-    let body = std::fs::read(&args.save)
-        .map_err(|e| format!("Error reading {}: {}", args.save.display(), e))?;
-    let save = SaveData::load(&body)?;
-    eprintln!("Loaded {} bricks ({} discarded)", save.brick_count, save.discarded);
-
-    let png = pollster::block_on(render(&save, &args))?;
-    std::fs::write(&output, png).map_err(|e| format!("Error writing {}: {}", output.display(), e))?;
-    eprintln!("Wrote {}", output.display());
-
-    Ok(())
 }
 
-async fn render(save: &SaveData, args: &Args) -> Result<Vec<u8>, String> {
-    let mut renderer = Renderer::new(None).await?;
-
-    let mode = if args.heightmap {
-        GeometryMode::Heightmap
-    } else {
-        GeometryMode::Map { outlines: args.outlines, fills: !args.no_fill }
-    };
-    let culled = save.build_geometry(mode, &mut renderer)?;
-    eprintln!("Culled {} occluded bricks", culled);
-
-    // Rotation happens about the centroid, so the axis-aligned bounds grow.
-    // Bound the rotated build by its corners rather than clipping it.
-    let rotation = args.rotation.to_radians();
-    let (x1, y1, x2, y2) = save.bounds;
-    let (half_w, half_h) = rotated_half_extent(
-        (x2 - x1) as f32 / 2.0,
-        (y2 - y1) as f32 / 2.0,
-        rotation,
-    );
-
-    let width = (half_w * 2.0 * args.scale).ceil() as u32 + args.margin * 2;
-    let height = (half_h * 2.0 * args.scale).ceil() as u32 + args.margin * 2;
-
-    // Bounds are centroid-relative and the unpanned view centers on the
-    // centroid, so panning to the middle of the bounds is exactly their
-    // midpoint (no further centroid term — that double-counts it)
-    let pan_x = -((x1 + x2) as f32 / 2.0);
-    let pan_y = -((y1 + y2) as f32 / 2.0);
-
-    // Anything past the device's max texture size is rendered as a grid of
-    // tiles and stitched, so huge builds still produce one image
-    let tile = renderer.max_tile_size();
-    let cols = width.div_ceil(tile);
-    let rows = height.div_ceil(tile);
-    let tile_w = width.div_ceil(cols);
-    let tile_h = height.div_ceil(rows);
-    eprintln!("Rendering {}x{} px ({}x{} tiles of {}x{})", width, height, cols, rows, tile_w, tile_h);
-
-    let mut stitcher = TileStitcher::default();
-    stitcher.set_layout(tile_w, tile_h, rows, cols);
-
-    // World-space size of one tile, used to walk the grid from its top-left
-    let world_tile_w = tile_w as f32 / args.scale;
-    let world_tile_h = tile_h as f32 / args.scale;
-    let start_x = pan_x + (cols as f32 - 1.0) * world_tile_w / 2.0;
-    let start_y = pan_y + (rows as f32 - 1.0) * world_tile_h / 2.0;
-
-    for row in 0..rows {
-        for col in 0..cols {
-            let matrix = save.view_matrix(
-                tile_w as f32,
-                tile_h as f32,
-                start_x - col as f32 * world_tile_w,
-                start_y - row as f32 * world_tile_h,
-                args.scale,
-                rotation,
-            );
-            let pixels = renderer.render_to_pixels(tile_w, tile_h, &matrix)?.finish_blocking()?;
-            stitcher.push_pixels(&pixels, row, col)?;
-        }
-    }
-
-    stitcher.encode_png()
+fn read_save(path: &Path) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))
 }
 
-// Half-extent of an axis-aligned box rotated about its center
-fn rotated_half_extent(half_w: f32, half_h: f32, rotation: f32) -> (f32, f32) {
-    let (sin, cos) = rotation.sin_cos();
-    (
-        half_w * cos.abs() + half_h * sin.abs(),
-        half_w * sin.abs() + half_h * cos.abs(),
-    )
+// Parses N comma-separated integers, e.g. "-100,0,250,400"
+fn parse_ints<const N: usize>(s: &str) -> Result<[i32; N], String> {
+    let values: Vec<i32> = s.split(',')
+        .map(|v| v.trim().parse().map_err(|_| format!("'{}' is not an integer", v.trim())))
+        .collect::<Result<_, _>>()?;
+    values.try_into().map_err(|_| format!("expected {} comma-separated integers", N))
 }
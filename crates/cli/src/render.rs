@@ -0,0 +1,610 @@
+use std::path::{Path, PathBuf};
+
+use brick_cartographer_core::fit::{fit_scale, rotated_half_extent, Fit};
+use brick_cartographer_core::floors::{detect_storeys, Storey, DEFAULT_HEADROOM};
+use brick_cartographer_core::labels::Label;
+use brick_cartographer_core::markers::MarkerLayers;
+use brick_cartographer_core::occlusion;
+use brick_cartographer_core::overlay::{self, GridSpacing, ImageView, Overlays};
+use brick_cartographer_core::render::{Background, OutlineWidth};
+use brick_cartographer_core::brick::Brick;
+use brick_cartographer_core::merge::Rect;
+use brick_cartographer_core::color::parse_hex;
+use brick_cartographer_core::projection::{Oblique, Side};
+use brick_cartographer_core::raster::{Font, Raster};
+use brick_cartographer_core::save::{BuildOptions, GeometryMode, OutlineColor, OutlineStyle};
+use brick_cartographer_core::shadow::{self, Sun};
+use brick_cartographer_core::tiles::{TileGrid, TILE_SIZE};
+use brick_cartographer_core::{encode_png, Renderer, SaveData, TileStitcher};
+use clap::Args;
+
+use crate::{parse_ints, read_save};
+
+#[derive(Args)]
+pub struct RenderArgs {
+    /// Save file to render
+    #[arg(required = true)]
+    save: Option<PathBuf>,
+
+    /// Output PNG path (defaults to the save's name with a .png extension)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Pixels per save unit. The website's default view is 0.1
+    #[arg(short, long, default_value_t = 0.1)]
+    scale: f32,
+
+    /// Image width in pixels; the scale is chosen to fit the build
+    #[arg(long, conflicts_with = "scale")]
+    width: Option<u32>,
+
+    /// Image height in pixels; the scale is chosen to fit the build
+    #[arg(long, conflicts_with = "scale")]
+    height: Option<u32>,
+
+    /// With both --width and --height: "contain" shows the whole build,
+    /// "cover" fills the image and crops the build
+    #[arg(long, default_value = "contain")]
+    fit: Fit,
+
+    /// Draw brick outlines
+    #[arg(long)]
+    outlines: bool,
+
+    /// Outline width in screen pixels (default: 0.8 save units, scaling
+    /// with --scale), for --outlines or --plan walls
+    #[arg(long)]
+    outline_width: Option<f32>,
+
+    /// Outline color as #rrggbb, or "darken" for a darker shade of each
+    /// brick's own color
+    #[arg(long, default_value = "#000000", requires = "outlines")]
+    outline_color: OutlineColor,
+
+    /// Outline only boundaries between differently colored or elevated
+    /// regions instead of every brick
+    #[arg(long, requires = "outlines")]
+    region_outlines: bool,
+
+    /// Draw bricks without their fill (outlines only)
+    #[arg(long)]
+    no_fill: bool,
+
+    /// Merge abutting same-colored rectangular bricks into larger quads
+    #[arg(long)]
+    merge: bool,
+
+    /// Draw studs and bevels on plates and bricks so close-up renders look
+    /// like bricks (try --scale 1.0); tiles and shaped bricks stay flat
+    #[arg(long, conflicts_with_all = ["no_fill", "heightmap", "underside", "isometric", "facade"])]
+    studs: bool,
+
+    /// Draw an architectural floor plan: walls as black outlines, floors
+    /// as light fills. Only one storey is drawn, the lowest found or the
+    /// floors subcommand's.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "outlines", "no_fill", "merge", "studs", "heightmap", "isometric", "facade", "underside", "shadows",
+            "occlusion",
+        ]
+    )]
+    plan: bool,
+
+    /// Color bricks by height instead of their own color
+    #[arg(long, conflicts_with_all = ["outlines", "no_fill", "merge"])]
+    heightmap: bool,
+
+    /// Map the build from below, as a reflected ceiling plan: wherever
+    /// bricks overlap the lowest shows, revealing caves and overhangs
+    #[arg(long, conflicts_with_all = ["isometric", "facade"])]
+    underside: bool,
+
+    /// Cast sun shadows from brick heights across the ground
+    #[arg(long, conflicts_with_all = ["isometric", "facade", "underside"])]
+    shadows: bool,
+
+    /// Compass bearing the --shadows sun shines from, in degrees clockwise
+    /// from north
+    #[arg(long, default_value_t = 315.0, requires = "shadows", allow_hyphen_values = true)]
+    sun_azimuth: f32,
+
+    /// Height of the --shadows sun above the horizon, in degrees
+    #[arg(long, default_value_t = 35.0, requires = "shadows")]
+    sun_elevation: f32,
+
+    /// Blur shadow edges over this many studs; 0 keeps them hard
+    #[arg(long, default_value_t = 0.0, requires = "shadows")]
+    shadow_softness: f32,
+
+    /// Darken alleys, courtyards and the base of walls by how much of the
+    /// sky bricks within this many studs hide (default: 3)
+    #[arg(long, num_args = 0..=1, default_missing_value = "3", conflicts_with_all = ["isometric", "facade", "underside"])]
+    occlusion: Option<f32>,
+
+    /// Background behind the bricks: "transparent", a #rrggbb color, or
+    /// "checker" / "grid" with an optional ":<studs>" size, e.g. grid:32
+    #[arg(long, default_value = "transparent")]
+    background: Background,
+
+    /// Overlay a world-aligned grid every this many studs, or "chunk" for
+    /// Brickadia's world chunks
+    #[arg(long)]
+    grid: Option<GridSpacing>,
+
+    /// Label grid lines with their coordinates in studs along the top and
+    /// left edges
+    #[arg(long)]
+    ticks: bool,
+
+    /// Overlay a scale bar in studs
+    #[arg(long)]
+    scale_bar: bool,
+
+    /// Overlay a north arrow that follows --rotation
+    #[arg(long)]
+    compass: bool,
+
+    /// Label the map with text from sign and interact components
+    #[arg(long)]
+    labels: bool,
+
+    /// Mark points of interest: a comma-separated list of spawn, light,
+    /// audio and interact, or "all"
+    #[arg(long, default_value = "")]
+    markers: MarkerLayers,
+
+    /// Render only this area, as x1,y1,x2,y2 in save units (10 per stud)
+    #[arg(long, value_parser = parse_ints::<4>, allow_hyphen_values = true, conflicts_with_all = ["center", "size"])]
+    region: Option<[i32; 4]>,
+
+    /// Render only an area of --size centered here, as x,y in save units
+    #[arg(long, value_parser = parse_ints::<2>, allow_hyphen_values = true, requires = "size")]
+    center: Option<[i32; 2]>,
+
+    /// Width and height of the area around --center, as w,h in save units
+    #[arg(long, value_parser = parse_ints::<2>, requires = "center")]
+    size: Option<[i32; 2]>,
+
+    /// Rotation in degrees
+    #[arg(short, long, default_value_t = 0.0)]
+    rotation: f32,
+
+    /// Draw bricks as boxes seen from an angle, so building heights show
+    #[arg(
+        long,
+        conflicts_with_all = ["rotation", "outlines", "no_fill", "merge", "grid", "ticks", "compass", "labels", "markers"]
+    )]
+    isometric: bool,
+
+    /// Direction the --isometric view turns the build, in degrees like
+    /// --rotation
+    #[arg(long, default_value_t = 45.0, requires = "isometric", allow_hyphen_values = true)]
+    azimuth: f32,
+
+    /// Angle the --isometric view looks down from, in degrees above the
+    /// horizon; 90 is straight down
+    #[arg(long, default_value_t = 30.0, requires = "isometric")]
+    elevation: f32,
+
+    /// Draw the build level from one side, as an elevation drawing: north,
+    /// east, south or west
+    #[arg(
+        long,
+        conflicts_with_all = ["isometric", "rotation", "outlines", "no_fill", "merge", "grid", "ticks", "compass", "labels", "markers"]
+    )]
+    facade: Option<Side>,
+
+    /// Fade farther faces of an --isometric or --facade view toward this
+    /// #rrggbb color (default: a pale haze)
+    #[arg(long, num_args = 0..=1, default_missing_value = "#dce3ea", value_parser = parse_hex)]
+    fog: Option<[u8; 4]>,
+
+    // Set by the section subcommand rather than a flag
+    #[arg(skip)]
+    section: Option<Oblique>,
+
+    // The storey drawn, set by the floors subcommand
+    #[arg(skip)]
+    storey: Option<Storey>,
+
+    // Drawn instead of the save's labels, set by the structures subcommand
+    #[arg(skip)]
+    index_labels: Vec<Label>,
+
+    /// Margin around the build, in pixels
+    #[arg(short, long, default_value_t = 32)]
+    margin: u32,
+}
+
+pub fn run(args: RenderArgs) -> Result<(), String> {
+    args.validate()?;
+    let save = load(&args)?;
+    let mut renderer = pollster::block_on(Renderer::new(None))?;
+    write_png(&save, &args, &mut renderer)
+}
+
+/// Reads and parses the save named by `args`, reporting what it holds.
+pub fn load(args: &RenderArgs) -> Result<SaveData, String> {
+    parse(&read_save(args.save_path())?, args)
+}
+
+/// Like [`load`], for a save already read into memory.
+pub fn parse(body: &[u8], args: &RenderArgs) -> Result<SaveData, String> {
+    let save = SaveData::load(body)?;
+    eprintln!("Loaded {} bricks ({} discarded)", save.brick_count, save.discarded);
+    if args.labels {
+        eprintln!("Found {} labels", save.labels.len());
+    }
+    if args.markers.any() {
+        eprintln!("Found {} markers", save.markers.len());
+    }
+    Ok(save)
+}
+
+/// Renders `save` as `args` describe with an existing renderer, and writes
+/// the PNG to the output path.
+pub fn write_png(save: &SaveData, args: &RenderArgs, renderer: &mut Renderer) -> Result<(), String> {
+    let output = args.output_path();
+    let png = render(save, args, renderer)?;
+    std::fs::write(&output, png).map_err(|e| format!("Error writing {}: {}", output.display(), e))?;
+    eprintln!("Wrote {}", output.display());
+    Ok(())
+}
+
+impl RenderArgs {
+    pub fn save_path(&self) -> &Path {
+        self.save.as_deref().expect("clap requires a save path")
+    }
+
+    pub fn output_path(&self) -> PathBuf {
+        self.output.clone().unwrap_or_else(|| self.save_path().with_extension("png"))
+    }
+
+    /// Checks the option combinations clap can't express.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.scale <= 0.0 {
+            return Err("scale must be greater than zero".to_string());
+        }
+        if self.no_fill && !self.outlines {
+            return Err("--no-fill needs --outlines, or the map would be empty".to_string());
+        }
+        if self.outline_width.is_some() && !self.outlines && !self.plan {
+            return Err("--outline-width needs --outlines or --plan".to_string());
+        }
+        if self.outline_width.is_some_and(|px| px <= 0.0) {
+            return Err("outline width must be greater than zero".to_string());
+        }
+        if self.occlusion.is_some_and(|studs| studs <= 0.0) {
+            return Err("occlusion radius must be greater than zero".to_string());
+        }
+        if self.fog.is_some() && !self.isometric && self.facade.is_none() && self.section.is_none() {
+            return Err("--fog needs --isometric, --facade or a section".to_string());
+        }
+        self.region()?;
+        self.oblique()?;
+        self.sun()?;
+        Ok(())
+    }
+
+    /// Renders a cross-section instead of the map. Section profiles aren't
+    /// in map coordinates, so options placed on the map don't apply.
+    pub fn set_section(&mut self, section: Oblique) -> Result<(), String> {
+        let map_only = [
+            (self.isometric, "--isometric"),
+            (self.facade.is_some(), "--facade"),
+            (self.underside, "--underside"),
+            (self.shadows, "--shadows"),
+            (self.occlusion.is_some(), "--occlusion"),
+            (self.rotation != 0.0, "--rotation"),
+            (self.outlines, "--outlines"),
+            (self.merge, "--merge"),
+            (self.studs, "--studs"),
+            (self.plan, "--plan"),
+            (self.grid.is_some(), "--grid"),
+            (self.ticks, "--ticks"),
+            (self.compass, "--compass"),
+            (self.labels, "--labels"),
+            (self.markers.any(), "--markers"),
+        ];
+        if let Some((_, flag)) = map_only.iter().find(|(set, _)| *set) {
+            return Err(format!("{} can't be used with a section", flag));
+        }
+        self.section = Some(section);
+        Ok(())
+    }
+
+    /// Draws only the bricks of `storey`, per [`Storey::heights`]. Shading
+    /// is cast by every brick, so it's left off.
+    pub fn set_storey(&mut self, storey: Storey) -> Result<(), String> {
+        if self.shadows || self.occlusion.is_some() {
+            return Err("--shadows and --occlusion can't be used with floors".to_string());
+        }
+        self.storey = Some(storey);
+        Ok(())
+    }
+
+    /// Labels the map with `labels` in place of the save's own, such as
+    /// numbers for an index map. Empty goes back to the save's.
+    pub fn set_index_labels(&mut self, labels: Vec<Label>) {
+        self.index_labels = labels;
+    }
+
+    /// Renders only `region` (absolute save units), fitted into a square
+    /// image `size` pixels across.
+    pub fn set_frame(&mut self, (x1, y1, x2, y2): Rect, size: u32) {
+        self.region = Some([x1, y1, x2, y2]);
+        self.center = None;
+        self.size = None;
+        self.width = Some(size);
+        self.height = Some(size);
+    }
+
+    fn oblique(&self) -> Result<Option<Oblique>, String> {
+        let oblique = match (self.section, self.isometric, self.facade) {
+            (Some(section), _, _) => section,
+            (None, true, _) => Oblique::new(self.azimuth, self.elevation)?,
+            (None, false, Some(side)) => Oblique::facade(side),
+            (None, false, None) => return Ok(None),
+        };
+        Ok(Some(oblique.with_fog(self.fog)))
+    }
+
+    fn sun(&self) -> Result<Option<Sun>, String> {
+        match self.shadows {
+            true => Sun::new(self.sun_azimuth, self.sun_elevation, self.shadow_softness).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    /// The area --region or --center and --size ask for, in absolute save
+    /// units.
+    pub fn region(&self) -> Result<Option<Rect>, String> {
+        let region = match (self.region, self.center, self.size) {
+            (Some([x1, y1, x2, y2]), _, _) => (x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)),
+            (None, Some([x, y]), Some([w, h])) => (x - w / 2, y - h / 2, x + (w + 1) / 2, y + (h + 1) / 2),
+            _ => return Ok(None),
+        };
+        if region.2 <= region.0 || region.3 <= region.1 {
+            return Err("region must have a positive width and height".to_string());
+        }
+        Ok(Some(region))
+    }
+}
+
+/// Builds the geometry `args` ask for on `renderer` and renders the PNG.
+pub fn render(save: &SaveData, args: &RenderArgs, renderer: &mut Renderer) -> Result<Vec<u8>, String> {
+    // Rotation happens about the centroid, so the axis-aligned bounds grow.
+    // Bound the rotated build by its corners rather than clipping it.
+    let rotation = args.rotation.to_radians();
+    let region = args.region()?;
+    let oblique = args.oblique()?;
+    let (x1, y1, x2, y2) = map_bounds(save, args)?;
+    let (half_w, half_h) = rotated_half_extent(
+        (x2 - x1) as f32 / 2.0,
+        (y2 - y1) as f32 / 2.0,
+        rotation,
+    );
+
+    let scale = if args.width.is_some() || args.height.is_some() {
+        fit_scale((x1, y1, x2, y2), rotation, args.width, args.height, args.margin, args.fit)
+            .ok_or("--width and --height must leave room inside the margins")?
+    } else {
+        args.scale
+    };
+    let width = args.width.unwrap_or_else(|| (half_w * 2.0 * scale).ceil() as u32 + args.margin * 2);
+    let height = args.height.unwrap_or_else(|| (half_h * 2.0 * scale).ceil() as u32 + args.margin * 2);
+
+    // Bounds are centroid-relative and the unpanned view centers on the
+    // centroid, so panning to the middle of the bounds is exactly their
+    // midpoint (no further centroid term — that double-counts it)
+    let pan_x = -((x1 + x2) as f32 / 2.0);
+    let pan_y = -((y1 + y2) as f32 / 2.0);
+
+    // Anything past the device's max texture size is rendered as a grid of
+    // tiles and stitched, so huge builds still produce one image
+    let tile = renderer.max_tile_size();
+    let cols = width.div_ceil(tile);
+    let rows = height.div_ceil(tile);
+    let tile_w = width.div_ceil(cols);
+    let tile_h = height.div_ceil(rows);
+    eprintln!("Rendering {}x{} px ({}x{} tiles of {}x{})", width, height, cols, rows, tile_w, tile_h);
+
+    let view = ImageView {
+        width,
+        height,
+        matrix: save.view_matrix(width as f32, height as f32, pan_x, pan_y, scale, rotation),
+    };
+
+    // A region only needs geometry for what lands in the image, margin and
+    // rotation included. Projected maps aren't in save coordinates, so they
+    // keep to the region itself.
+    let visible = match oblique {
+        Some(_) => region,
+        None => region.map(|_| {
+            let (x1, y1, x2, y2) = view.world_bounds();
+            (x1.floor() as i32, y1.floor() as i32, x2.ceil() as i32, y2.ceil() as i32)
+        }),
+    };
+    build_geometry(save, args, visible, renderer)?;
+
+    let mut stitcher = TileStitcher::default();
+    stitcher.set_layout(tile_w, tile_h, rows, cols);
+    // The grid may overshoot the requested size by a few pixels; tiles
+    // start at the image's top-left and the stitcher trims the rest
+    stitcher.set_size(width, height);
+
+    // World-space size of one tile, used to walk the grid from its top-left
+    let world_tile_w = tile_w as f32 / scale;
+    let world_tile_h = tile_h as f32 / scale;
+    let start_x = pan_x + (width as f32 - tile_w as f32) / 2.0 / scale;
+    let start_y = pan_y + (height as f32 - tile_h as f32) / 2.0 / scale;
+
+    for row in 0..rows {
+        for col in 0..cols {
+            let matrix = save.view_matrix(
+                tile_w as f32,
+                tile_h as f32,
+                start_x - col as f32 * world_tile_w,
+                start_y - row as f32 * world_tile_h,
+                scale,
+                rotation,
+            );
+            let pixels = renderer.render_to_pixels(tile_w, tile_h, &matrix, &args.background)?.finish_blocking()?;
+            stitcher.push_pixels(&pixels, row, col)?;
+        }
+    }
+
+    // Shading falls on the ground under the overlays
+    shade(save, args, region, &mut stitcher.raster()?, &view)?;
+
+    let overlays = Overlays {
+        grid: args.grid,
+        ticks: args.ticks,
+        scale_bar: args.scale_bar,
+        compass: args.compass,
+        labels: args.labels || !args.index_labels.is_empty(),
+        markers: args.markers,
+    };
+    let labels = match args.index_labels.is_empty() {
+        true => &save.labels,
+        false => &args.index_labels,
+    };
+    if overlays.any() {
+        overlay::draw_overlays(&mut stitcher.raster()?, &view, &overlays, Some(&Font::default()), labels, &save.markers);
+    }
+
+    stitcher.encode_png()
+}
+
+// Darkens the rendered `raster` with the ambient occlusion and shadows
+// `args` ask for
+fn shade(save: &SaveData, args: &RenderArgs, region: Option<Rect>, raster: &mut Raster, view: &ImageView) -> Result<(), String> {
+    if let Some(radius) = args.occlusion {
+        occlusion::occlude(save, radius, region, raster, view);
+    }
+    if let Some(sun) = args.sun()? {
+        shadow::cast_shadows(save, &sun, region, raster, view);
+    }
+    Ok(())
+}
+
+/// Builds the geometry for the map style `args` ask for, limited to `visible`
+/// (absolute save units) when given.
+pub fn build_geometry(save: &SaveData, args: &RenderArgs, visible: Option<Rect>, renderer: &mut Renderer) -> Result<(), String> {
+    // The floors subcommand's storey, or for a plan the lowest found
+    let storey = match args.storey {
+        Some(storey) => Some(storey),
+        None if args.plan => detect_storeys(save.all_bricks(), DEFAULT_HEADROOM).first().copied(),
+        None => None,
+    };
+    let mode = if args.heightmap {
+        GeometryMode::Heightmap
+    } else if args.plan {
+        // Without any floor, walls stand on the lowest brick
+        let floor = storey.map_or_else(|| save.height_extent().0, |storey| storey.floor);
+        GeometryMode::Plan { floor }
+    } else {
+        let outlines = args.outlines.then_some(OutlineStyle {
+            color: args.outline_color,
+            regions: args.region_outlines,
+        });
+        GeometryMode::Map { fills: !args.no_fill, merge: args.merge, outlines, studs: args.studs }
+    };
+    // Set every time, since a batch shares the renderer between jobs
+    renderer.set_outline_width(args.outline_width.map_or(OutlineWidth::default(), OutlineWidth::Pixels));
+
+    let options = BuildOptions {
+        region: visible,
+        heights: storey.map(|storey| storey.heights()),
+        oblique: args.oblique()?,
+        underside: args.underside,
+    };
+    let culled = save.build_geometry_within(mode, &options, renderer)?;
+    eprintln!("Culled {} occluded bricks", culled);
+    Ok(())
+}
+
+// What the map shows, relative to the centroid: the build or the region
+// `args` ask for, as projected when the view is oblique
+fn map_bounds(save: &SaveData, args: &RenderArgs) -> Result<Rect, String> {
+    let region = args.region()?;
+    if let Some(oblique) = args.oblique()? {
+        let bricks = save.all_bricks().filter(|brick| region.is_none_or(|region| reaches(brick, region)));
+        return oblique
+            .bounds(save.centroid, bricks)
+            .ok_or_else(|| "no bricks reach into the region".to_string());
+    }
+    Ok(match region {
+        Some((x1, y1, x2, y2)) => {
+            let (cx, cy) = save.centroid;
+            (x1 - cx, y1 - cy, x2 - cx, y2 - cy)
+        }
+        None => save.bounds,
+    })
+}
+
+// Whether a brick's footprint reaches into `region` (absolute save units)
+fn reaches(brick: &Brick, (x1, y1, x2, y2): Rect) -> bool {
+    let (x, y, _) = brick.position;
+    let (w, h) = (brick.size.0 as i32, brick.size.1 as i32);
+    x + w > x1 && x - w < x2 && y + h > y1 && y - h < y2
+}
+
+/// Renders one tile of `grid` from the geometry [`render`] last built, shaded
+/// as `args` ask, as a PNG.
+pub fn render_tile(
+    save: &SaveData,
+    args: &RenderArgs,
+    renderer: &Renderer,
+    grid: &TileGrid,
+    (zoom, x, y): (u32, u32, u32),
+) -> Result<Vec<u8>, String> {
+    let mut pixels = grid.render_tile(save, renderer, zoom, x, y, &args.background)?;
+    let view = grid.tile_view(save, zoom, x, y);
+    shade(save, args, args.region()?, &mut Raster::new(&mut pixels, TILE_SIZE, TILE_SIZE)?, &view)?;
+    encode_png(&pixels, TILE_SIZE, TILE_SIZE)
+}
+
+/// The tiling for map viewers over the build, or over the region `args`
+/// ask for, and the centroid-relative bounds it covers.
+pub fn tile_grid(save: &SaveData, args: &RenderArgs) -> Result<(TileGrid, Rect), String> {
+    let bounds = map_bounds(save, args)?;
+    Ok((TileGrid::new(bounds), bounds))
+}
+
+/// Writes a tile pyramid of the geometry [`render`] last built into `dir` as
+/// `{z}/{x}/{y}.png`, from zoom 0 to `max_zoom` (by default the first zoom as
+/// detailed as --scale). Tiles are shaded like the PNG; overlays are left
+/// off. Tiles whose file already holds the same image aren't rewritten.
+/// Returns the number written and the number in the pyramid.
+pub fn write_tiles(
+    save: &SaveData,
+    args: &RenderArgs,
+    renderer: &Renderer,
+    dir: &Path,
+    max_zoom: Option<u32>,
+) -> Result<(usize, usize), String> {
+    let (grid, bounds) = tile_grid(save, args)?;
+    let max_zoom = max_zoom.unwrap_or_else(|| grid.zoom_for(args.scale));
+
+    let (mut written, mut total) = (0, 0);
+    for zoom in 0..=max_zoom {
+        let (x1, y1, x2, y2) = grid.tiles_over(zoom, bounds);
+        for x in x1..=x2 {
+            let column = dir.join(zoom.to_string()).join(x.to_string());
+            std::fs::create_dir_all(&column).map_err(|e| format!("Error creating {}: {}", column.display(), e))?;
+            for y in y1..=y2 {
+                let png = render_tile(save, args, renderer, &grid, (zoom, x, y))?;
+                let path = column.join(format!("{}.png", y));
+                total += 1;
+                if std::fs::read(&path).is_ok_and(|old| old == png) {
+                    continue;
+                }
+                std::fs::write(&path, png).map_err(|e| format!("Error writing {}: {}", path.display(), e))?;
+                written += 1;
+            }
+        }
+    }
+    Ok((written, total))
+}
@@ -0,0 +1,32 @@
+use brick_cartographer_core::projection::Oblique;
+use clap::Args;
+
+use crate::parse_ints;
+use crate::render::{self, RenderArgs};
+
+#[derive(Args)]
+pub struct SectionArgs {
+    #[command(flatten)]
+    render: RenderArgs,
+
+    /// Start of the cut line, as x,y in save units; it's seen from its
+    /// right-hand side, running left to right
+    #[arg(long, value_parser = parse_ints::<2>, allow_hyphen_values = true)]
+    from: [i32; 2],
+
+    /// End of the cut line, as x,y in save units
+    #[arg(long, value_parser = parse_ints::<2>, allow_hyphen_values = true)]
+    to: [i32; 2],
+
+    /// Width of the slice across the line, in save units (10 per stud)
+    #[arg(long, default_value_t = 40)]
+    thickness: i32,
+}
+
+pub fn run(mut args: SectionArgs) -> Result<(), String> {
+    let [x1, y1] = args.from;
+    let [x2, y2] = args.to;
+    let section = Oblique::section((x1, y1), (x2, y2), args.thickness)?;
+    args.render.set_section(section)?;
+    render::run(args.render)
+}
@@ -0,0 +1,108 @@
+use std::num::NonZeroUsize;
+
+use brick_cartographer_core::tiles::{TileGrid, TILE_SIZE};
+use brick_cartographer_core::{Renderer, SaveData};
+use clap::Args;
+use lru::LruCache;
+use serde_json::json;
+use tiny_http::{Header, Response, Server};
+
+use crate::render::{self, RenderArgs};
+
+#[derive(Args)]
+pub struct ServeArgs {
+    // Only the map style, shading and region options apply; tiles have
+    // their own size and no overlays
+    #[command(flatten)]
+    render: RenderArgs,
+
+    /// Port to listen on, on localhost only
+    #[arg(long, default_value_t = 8000)]
+    port: u16,
+
+    /// How many rendered tiles to keep in memory
+    #[arg(long, default_value_t = 4096)]
+    cache: usize,
+}
+
+pub fn run(args: ServeArgs) -> Result<(), String> {
+    args.render.validate()?;
+    let save = render::load(&args.render)?;
+    let mut renderer = pollster::block_on(Renderer::new(None))?;
+    // Tiles draw from this geometry for as long as the server runs
+    render::build_geometry(&save, &args.render, args.render.region()?, &mut renderer)?;
+    let (grid, bounds) = render::tile_grid(&save, &args.render)?;
+    let info = info(&save, &grid, bounds).to_string();
+
+    let server = Server::http(("127.0.0.1", args.port)).map_err(|e| format!("Error listening on port {}: {}", args.port, e))?;
+    eprintln!("Serving http://127.0.0.1:{}/tiles/{{z}}/{{x}}/{{y}}.png and /info.json (Ctrl+C to stop)", args.port);
+
+    let capacity = NonZeroUsize::new(args.cache).unwrap_or(NonZeroUsize::MIN);
+    let mut cache: LruCache<(u32, u32, u32), Vec<u8>> = LruCache::new(capacity);
+    for request in server.incoming_requests() {
+        let url = request.url().split('?').next().unwrap_or_default().to_string();
+        let response = if url == "/info.json" {
+            Ok(respond(info.clone().into_bytes(), "application/json"))
+        } else if let Some((z, x, y)) = tile_path(&url).filter(|&(z, x, y)| has_tile(&grid, bounds, z, x, y)) {
+            match cache.get(&(z, x, y)) {
+                Some(png) => Ok(respond(png.clone(), "image/png")),
+                None => render::render_tile(&save, &args.render, &renderer, &grid, (z, x, y))
+                    .map(|png| {
+                        cache.put((z, x, y), png.clone());
+                        respond(png, "image/png")
+                    }),
+            }
+        } else {
+            Ok(respond(b"Not found".to_vec(), "text/plain").with_status_code(404))
+        };
+        let response = response.unwrap_or_else(|e| {
+            eprintln!("Error rendering {}: {}", url, e);
+            respond(e.into_bytes(), "text/plain").with_status_code(500)
+        });
+        if let Err(e) = request.respond(response) {
+            eprintln!("Error answering {}: {}", url, e);
+        }
+    }
+    Ok(())
+}
+
+// What a viewer needs to place the tiles: everything in save units, with
+// the tile grid's origin at its top-left corner
+fn info(save: &SaveData, grid: &TileGrid, bounds: (i32, i32, i32, i32)) -> serde_json::Value {
+    let (cx, cy) = save.centroid;
+    json!({
+        "description": save.description,
+        "bricks": save.brick_count,
+        "centroid": [cx, cy],
+        "bounds": [bounds.0 + cx, bounds.1 + cy, bounds.2 + cx, bounds.3 + cy],
+        "tileSize": TILE_SIZE,
+        "origin": [grid.origin.0 + cx as f32, grid.origin.1 + cy as f32],
+        "extent": grid.extent,
+        "minZoom": 0,
+        "maxZoom": grid.zoom_for(1.0),
+    })
+}
+
+// Parses "/tiles/{z}/{x}/{y}.png"
+fn tile_path(url: &str) -> Option<(u32, u32, u32)> {
+    let rest = url.strip_prefix("/tiles/")?.strip_suffix(".png")?;
+    let mut parts = rest.split('/').map(|part| part.parse().ok());
+    let tile = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(tile)
+}
+
+// Tiles past the build would only ever be empty
+fn has_tile(grid: &TileGrid, bounds: (i32, i32, i32, i32), z: u32, x: u32, y: u32) -> bool {
+    if !grid.contains(z, x, y) {
+        return false;
+    }
+    let (x1, y1, x2, y2) = grid.tiles_over(z, bounds);
+    (x1..=x2).contains(&x) && (y1..=y2).contains(&y)
+}
+
+fn respond(body: Vec<u8>, content_type: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_data(body)
+        .with_header(Header::from_bytes("Content-Type", content_type).expect("valid header"))
+        // Lets a viewer page opened from disk or another port fetch tiles
+        .with_header(Header::from_bytes("Access-Control-Allow-Origin", "*").expect("valid header"))
+}
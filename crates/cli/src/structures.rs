@@ -0,0 +1,96 @@
+use brick_cartographer_core::labels::Label;
+use brick_cartographer_core::structures::{find_structures, Structure};
+use brick_cartographer_core::Renderer;
+use clap::Args;
+use serde_json::json;
+
+use crate::render::{self, RenderArgs};
+
+#[derive(Args)]
+pub struct StructuresArgs {
+    // The output path names what's written: City.png becomes the index map
+    // City_structures.png, its list City_structures.json and, with
+    // --thumbnails, City_structure_1.png and so on
+    #[command(flatten)]
+    render: RenderArgs,
+
+    /// Leave out structures of fewer bricks, like stray debris
+    #[arg(long, default_value_t = 20)]
+    min_bricks: usize,
+
+    /// Also render each structure on its own, fitted into a square this
+    /// many pixels across (default: 256)
+    #[arg(long, num_args = 0..=1, default_missing_value = "256")]
+    thumbnails: Option<u32>,
+}
+
+pub fn run(mut args: StructuresArgs) -> Result<(), String> {
+    if args.thumbnails == Some(0) {
+        return Err("thumbnail size must be greater than zero".to_string());
+    }
+    args.render.validate()?;
+    let save = render::load(&args.render)?;
+    let structures: Vec<Structure> = find_structures(&save)
+        .into_iter()
+        .filter(|structure| structure.bricks >= args.min_bricks)
+        .collect();
+    if structures.is_empty() {
+        return Err(format!("no structures of {} or more bricks", args.min_bricks));
+    }
+    eprintln!("Found {} structures", structures.len());
+
+    let output = args.render.output_path();
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let mut renderer = pollster::block_on(Renderer::new(None))?;
+
+    // The index map numbers each structure in its middle, as listed
+    let labels = structures
+        .iter()
+        .enumerate()
+        .map(|(i, structure)| {
+            let (x1, y1, x2, y2) = structure.bounds;
+            Label { position: ((x1 + x2) / 2, (y1 + y2) / 2, structure.heights.1), text: (i + 1).to_string() }
+        })
+        .collect();
+    args.render.set_index_labels(labels);
+    let path = output.with_file_name(format!("{}_structures.png", stem));
+    let png = render::render(&save, &args.render, &mut renderer)?;
+    std::fs::write(&path, png).map_err(|e| format!("Error writing {}: {}", path.display(), e))?;
+    eprintln!("Wrote {}", path.display());
+    args.render.set_index_labels(Vec::new());
+
+    let mut index = Vec::new();
+    for (i, structure) in structures.iter().enumerate() {
+        let thumbnail = match args.thumbnails {
+            Some(size) => {
+                args.render.set_frame(structure.bounds, size);
+                let path = output.with_file_name(format!("{}_structure_{}.png", stem, i + 1));
+                let png = render::render(&save, &args.render, &mut renderer)?;
+                std::fs::write(&path, png).map_err(|e| format!("Error writing {}: {}", path.display(), e))?;
+                path.file_name().map(|name| name.to_string_lossy().into_owned())
+            }
+            None => None,
+        };
+        let (x1, y1, x2, y2) = structure.bounds;
+        index.push(json!({
+            "number": i + 1,
+            "bounds": [x1, y1, x2, y2],
+            "bottom": structure.heights.0,
+            "top": structure.heights.1,
+            "height": structure.height(),
+            "bricks": structure.bricks,
+            "owner": structure.owner,
+            "thumbnail": thumbnail,
+        }));
+    }
+    if args.thumbnails.is_some() {
+        eprintln!("Wrote {} thumbnails", structures.len());
+    }
+
+    let path = output.with_file_name(format!("{}_structures.json", stem));
+    let body = serde_json::to_string_pretty(&json!({ "structures": index }))
+        .map_err(|e| format!("Error encoding structures: {}", e))?;
+    std::fs::write(&path, body).map_err(|e| format!("Error writing {}: {}", path.display(), e))?;
+    eprintln!("Wrote {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use brick_cartographer_core::Renderer;
+use clap::Args;
+
+use crate::read_save;
+use crate::render::{self, RenderArgs};
+
+#[derive(Args)]
+pub struct WatchArgs {
+    #[command(flatten)]
+    render: RenderArgs,
+
+    /// Also write a tile pyramid for map viewers into this folder, as
+    /// {z}/{x}/{y}.png
+    #[arg(long)]
+    tiles: Option<PathBuf>,
+
+    /// Deepest tile zoom (default: the first as detailed as --scale)
+    #[arg(long, requires = "tiles")]
+    max_zoom: Option<u32>,
+
+    /// How often to check the save for changes, in milliseconds
+    #[arg(long, default_value_t = 500)]
+    interval: u64,
+}
+
+// What a poll sees of the file without reading it
+type Stamp = Option<(SystemTime, u64)>;
+
+pub fn run(args: WatchArgs) -> Result<(), String> {
+    args.render.validate()?;
+    let path = args.render.save_path();
+    let interval = Duration::from_millis(args.interval.max(50));
+    let mut renderer = pollster::block_on(Renderer::new(None))?;
+    eprintln!("Watching {} (Ctrl+C to stop)", path.display());
+
+    let mut seen: Stamp = None;
+    let mut settling: Stamp = None;
+    let mut content = None;
+    loop {
+        // Brickadia writes saves over a moment, so a change is only read
+        // once the file has looked the same for a whole interval
+        let stamp = stamp(path);
+        if stamp != seen {
+            if stamp.is_some() && stamp == settling {
+                seen = stamp;
+                if let Err(e) = refresh(&args, &mut renderer, &mut content) {
+                    eprintln!("Error: {}", e);
+                }
+            } else {
+                settling = stamp;
+            }
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+// Re-renders if the save's bytes differ from the last good render, and
+// rewrites outputs whose images differ from what's on disk
+fn refresh(args: &WatchArgs, renderer: &mut Renderer, content: &mut Option<u64>) -> Result<(), String> {
+    let body = read_save(args.render.save_path())?;
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    let hash = hasher.finish();
+    if *content == Some(hash) {
+        return Ok(());
+    }
+
+    let started = Instant::now();
+    let save = render::parse(&body, &args.render)?;
+    let png = render::render(&save, &args.render, renderer)?;
+    let output = args.render.output_path();
+    if std::fs::read(&output).map_or(true, |old| old != png) {
+        std::fs::write(&output, png).map_err(|e| format!("Error writing {}: {}", output.display(), e))?;
+        eprintln!("Wrote {}", output.display());
+    } else {
+        eprintln!("{} is unchanged", output.display());
+    }
+    if let Some(dir) = &args.tiles {
+        let (written, total) = render::write_tiles(&save, &args.render, renderer, dir, args.max_zoom)?;
+        eprintln!("Wrote {} of {} tiles to {}", written, total, dir.display());
+    }
+    eprintln!("Refreshed in {:.2}s", started.elapsed().as_secs_f32());
+
+    *content = Some(hash);
+    Ok(())
+}
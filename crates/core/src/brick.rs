@@ -4,7 +4,7 @@ use brickadia::save::{Direction, Rotation};
 /// filtered to visible bricks, size transformed for rotation/direction, and
 /// color resolved to display sRGB.
 ///
-/// Kept deliberately small (28 bytes vs brickadia's 112-byte `Brick`): saves
+/// Kept deliberately small (36 bytes vs brickadia's 112-byte `Brick`): saves
 /// run to tens of millions of bricks and the whole list must fit alongside
 /// everything else under wasm32's 4GB memory ceiling.
 pub struct Brick {
@@ -15,6 +15,12 @@ pub struct Brick {
     pub color: [u8; 4],
     pub rotation: Rotation,
     pub direction: Direction,
+    /// Who placed it: 0 when public, otherwise an index into
+    /// [`SaveData::owners`](crate::SaveData::owners) from 1, as .brs saves
+    /// count them
+    pub owner: u32,
+    /// Index into [`SaveData::materials`](crate::SaveData::materials)
+    pub material: u16,
 }
 
 impl Brick {
@@ -22,3 +28,20 @@ impl Brick {
         (self.size.0 as u32, self.size.1 as u32, self.size.2 as u32)
     }
 }
+
+#[cfg(test)]
+impl Brick {
+    /// A gray, public, unrotated brick of the first asset, for tests.
+    pub fn sized(position: (i32, i32, i32), size: (u16, u16, u16)) -> Brick {
+        Brick {
+            position,
+            size,
+            asset_name_index: 0,
+            color: [200, 200, 200, 255],
+            rotation: Rotation::Deg0,
+            direction: Direction::ZPositive,
+            owner: 0,
+            material: 0,
+        }
+    }
+}
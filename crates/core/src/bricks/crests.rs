@@ -90,98 +90,98 @@ fn crest_left(shape: &Shape) -> Vec<f32> {
      tri(&Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2}, Tri::TopRight)].concat()
 }
 
-pub fn ramp_crest_ol(brick: &Brick, shape: &Shape) -> Vec<f32> {
+pub fn ramp_crest_ol(brick: &Brick, shape: &Shape, t: f32) -> Vec<f32> {
     match brick.direction {
         Direction::YPositive => {
             match brick.rotation {
-                Rotation::Deg90 | Rotation::Deg270 => crest_ol_down(shape),
-                _ => rec_ol(shape)
+                Rotation::Deg90 | Rotation::Deg270 => crest_ol_down(shape, t),
+                _ => rec_ol(shape, t)
             }
         },
         Direction::YNegative => {
             match brick.rotation {
-                Rotation::Deg90 | Rotation::Deg270 => crest_ol_up(shape),
-                _ => rec_ol(shape)
+                Rotation::Deg90 | Rotation::Deg270 => crest_ol_up(shape, t),
+                _ => rec_ol(shape, t)
             }
         },
         Direction::XPositive => {
             match brick.rotation {
-                Rotation::Deg90 | Rotation::Deg270 => crest_ol_right(shape),
-                _ => rec_ol(shape)
+                Rotation::Deg90 | Rotation::Deg270 => crest_ol_right(shape, t),
+                _ => rec_ol(shape, t)
             }
         },
         Direction::XNegative => {
             match brick.rotation {
-                Rotation::Deg90 | Rotation::Deg270 => crest_ol_left(shape),
-                _ => rec_ol(shape)
+                Rotation::Deg90 | Rotation::Deg270 => crest_ol_left(shape, t),
+                _ => rec_ol(shape, t)
             }
         },
-        _ => rec_ol(shape)
+        _ => rec_ol(shape, t)
     }
 }
 
-pub fn ramp_crest_end_ol(brick: &Brick, shape: &Shape) -> Vec<f32> {
+pub fn ramp_crest_end_ol(brick: &Brick, shape: &Shape, t: f32) -> Vec<f32> {
     match brick.direction {
         Direction::YPositive => {
             match brick.rotation {
-                Rotation::Deg0 => tri_ol(shape, Tri::TopRight),
-                Rotation::Deg180 => tri_ol(shape, Tri::TopLeft),
-                Rotation::Deg90 | Rotation::Deg270 => crest_ol_down(shape),
+                Rotation::Deg0 => tri_ol(shape, Tri::TopRight, t),
+                Rotation::Deg180 => tri_ol(shape, Tri::TopLeft, t),
+                Rotation::Deg90 | Rotation::Deg270 => crest_ol_down(shape, t),
             }
         },
         Direction::YNegative => {
             match brick.rotation {
-                Rotation::Deg0 => tri_ol(shape, Tri::BotLeft),
-                Rotation::Deg180 => tri_ol(shape, Tri::BotRight),
-                Rotation::Deg90 | Rotation::Deg270 => crest_ol_up(shape),
+                Rotation::Deg0 => tri_ol(shape, Tri::BotLeft, t),
+                Rotation::Deg180 => tri_ol(shape, Tri::BotRight, t),
+                Rotation::Deg90 | Rotation::Deg270 => crest_ol_up(shape, t),
             }
         },
         Direction::XPositive => {
             match brick.rotation {
-                Rotation::Deg0 => tri_ol(shape, Tri::TopLeft),
-                Rotation::Deg180 => tri_ol(shape, Tri::BotLeft),
-                Rotation::Deg90 | Rotation::Deg270 => crest_ol_right(shape),
+                Rotation::Deg0 => tri_ol(shape, Tri::TopLeft, t),
+                Rotation::Deg180 => tri_ol(shape, Tri::BotLeft, t),
+                Rotation::Deg90 | Rotation::Deg270 => crest_ol_right(shape, t),
             }
         },
         Direction::XNegative => {
             match brick.rotation {
-                Rotation::Deg0 => tri_ol(shape, Tri::BotRight),
-                Rotation::Deg180 => tri_ol(shape, Tri::TopRight),
-                Rotation::Deg90 | Rotation::Deg270 => crest_ol_left(shape),
+                Rotation::Deg0 => tri_ol(shape, Tri::BotRight, t),
+                Rotation::Deg180 => tri_ol(shape, Tri::TopRight, t),
+                Rotation::Deg90 | Rotation::Deg270 => crest_ol_left(shape, t),
             }
         },
-        _ => rec_ol(shape)
+        _ => rec_ol(shape, t)
     }
 }
 
-fn crest_ol_down(shape: &Shape) -> Vec<f32> {
+fn crest_ol_down(shape: &Shape, t: f32) -> Vec<f32> {
     let (sx, _sy) = shape.size();
     let left = &Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2 - sx, y2: shape.y2};
     let right = &Shape {x1: shape.x1 + sx, y1: shape.y1, x2: shape.x2, y2: shape.y2};
-    [tri_ol_tr_top(left), tri_ol_tr_diag(left),
-     tri_ol_tl_top(right), tri_ol_tl_diag(right)].concat()
+    [tri_ol_tr_top(left, t), tri_ol_tr_diag(left, t),
+     tri_ol_tl_top(right, t), tri_ol_tl_diag(right, t)].concat()
 }
 
-fn crest_ol_right(shape: &Shape) -> Vec<f32> {
+fn crest_ol_right(shape: &Shape, t: f32) -> Vec<f32> {
     let (_sx, sy) = shape.size();
     let top = &Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2, y2: shape.y2 - sy};
     let bot = &Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2};
-    [tri_ol_bl_left(top), tri_ol_bl_diag(top),
-     tri_ol_tl_left(bot), tri_ol_tl_diag(bot)].concat()
+    [tri_ol_bl_left(top, t), tri_ol_bl_diag(top, t),
+     tri_ol_tl_left(bot, t), tri_ol_tl_diag(bot, t)].concat()
 }
 
-fn crest_ol_up(shape: &Shape) -> Vec<f32> {
+fn crest_ol_up(shape: &Shape, t: f32) -> Vec<f32> {
     let (sx, _sy) = shape.size();
     let left = &Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2 - sx, y2: shape.y2};
     let right = &Shape {x1: shape.x1 + sx, y1: shape.y1, x2: shape.x2, y2: shape.y2};
-    [tri_ol_br_bot(left), tri_ol_br_diag(left),
-     tri_ol_bl_bot(right), tri_ol_bl_diag(right)].concat()
+    [tri_ol_br_bot(left, t), tri_ol_br_diag(left, t),
+     tri_ol_bl_bot(right, t), tri_ol_bl_diag(right, t)].concat()
 }
 
-fn crest_ol_left(shape: &Shape) -> Vec<f32> {
+fn crest_ol_left(shape: &Shape, t: f32) -> Vec<f32> {
     let (_sx, sy) = shape.size();
     let top = &Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2, y2: shape.y2 - sy};
     let bot = &Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2};
-    [tri_ol_br_right(top), tri_ol_br_diag(top),
-     tri_ol_tr_right(bot), tri_ol_tr_diag(bot)].concat()
+    [tri_ol_br_right(top, t), tri_ol_br_diag(top, t),
+     tri_ol_tr_right(bot, t), tri_ol_tr_diag(bot, t)].concat()
 }
@@ -46,62 +46,62 @@ fn corner_bl(shape: &Shape) -> Vec<f32> {
     [long, short].concat()
 }
 
-pub fn corner_ol(brick: &Brick, shape: &Shape) -> Vec<f32> {
+pub fn corner_ol(brick: &Brick, shape: &Shape, t: f32) -> Vec<f32> {
     match brick.direction {
         Direction::ZPositive =>
             match brick.rotation {
-                Rotation::Deg0 => corner_ol_tl(shape),
-                Rotation::Deg90 => corner_ol_tr(shape),
-                Rotation::Deg180 => corner_ol_br(shape),
-                Rotation::Deg270 => corner_ol_bl(shape),
+                Rotation::Deg0 => corner_ol_tl(shape, t),
+                Rotation::Deg90 => corner_ol_tr(shape, t),
+                Rotation::Deg180 => corner_ol_br(shape, t),
+                Rotation::Deg270 => corner_ol_bl(shape, t),
             }
         Direction::ZNegative =>
             match brick.rotation {
-                Rotation::Deg0 => corner_ol_tr(shape),
-                Rotation::Deg90 => corner_ol_tl(shape),
-                Rotation::Deg180 => corner_ol_bl(shape),
-                Rotation::Deg270 => corner_ol_br(shape),
+                Rotation::Deg0 => corner_ol_tr(shape, t),
+                Rotation::Deg90 => corner_ol_tl(shape, t),
+                Rotation::Deg180 => corner_ol_bl(shape, t),
+                Rotation::Deg270 => corner_ol_br(shape, t),
             }
         _ => rec(shape)
     }
 }
 
-fn corner_ol_tl(shape: &Shape) -> Vec<f32> {
+fn corner_ol_tl(shape: &Shape, t: f32) -> Vec<f32> {
     let (sx, sy) = shape.size();
-    [rec_ol_top(shape), rec_ol_left(shape),
-    rec_ol_bot(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2 - sx, y2: shape.y2}),
-    rec_ol_right(&Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2 - sx, y2: shape.y2}),
-    rec_ol_bot(&Shape {x1: shape.x1 + sx, y1: shape.y1, x2: shape.x2, y2: shape.y2 - sy}),
-    rec_ol_right(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2, y2: shape.y2 - sy}),
-    rec(&Shape {x1: shape.x1 + sx - OUTLINE_THICKNESS, y1: shape.y1 + sy - OUTLINE_THICKNESS, x2: shape.x1 + sx, y2: shape.y1 + sy})].concat()
+    [rec_ol_top(shape, t), rec_ol_left(shape, t),
+    rec_ol_bot(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2 - sx, y2: shape.y2}, t),
+    rec_ol_right(&Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2 - sx, y2: shape.y2}, t),
+    rec_ol_bot(&Shape {x1: shape.x1 + sx, y1: shape.y1, x2: shape.x2, y2: shape.y2 - sy}, t),
+    rec_ol_right(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2, y2: shape.y2 - sy}, t),
+    rec(&Shape {x1: shape.x1 + sx - t, y1: shape.y1 + sy - t, x2: shape.x1 + sx, y2: shape.y1 + sy})].concat()
 }
 
-fn corner_ol_tr(shape: &Shape) -> Vec<f32> {
+fn corner_ol_tr(shape: &Shape, t: f32) -> Vec<f32> {
     let (sx, sy) = shape.size();
-    [rec_ol_top(shape), rec_ol_right(shape),
-    rec_ol_left(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2, y2: shape.y2 - sy}),
-    rec_ol_bot(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2 - sx, y2: shape.y2 - sy}),
-    rec_ol_left(&Shape {x1: shape.x1 + sx, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2}),
-    rec_ol_bot(&Shape {x1: shape.x1 + sx, y1: shape.y1, x2: shape.x2, y2: shape.y2}),
-    rec(&Shape {x1: shape.x1 + sx, y1: shape.y1 + sy - OUTLINE_THICKNESS, x2: shape.x1 + sx + OUTLINE_THICKNESS, y2: shape.y1 + sy})].concat()
+    [rec_ol_top(shape, t), rec_ol_right(shape, t),
+    rec_ol_left(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2, y2: shape.y2 - sy}, t),
+    rec_ol_bot(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2 - sx, y2: shape.y2 - sy}, t),
+    rec_ol_left(&Shape {x1: shape.x1 + sx, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2}, t),
+    rec_ol_bot(&Shape {x1: shape.x1 + sx, y1: shape.y1, x2: shape.x2, y2: shape.y2}, t),
+    rec(&Shape {x1: shape.x1 + sx, y1: shape.y1 + sy - t, x2: shape.x1 + sx + t, y2: shape.y1 + sy})].concat()
 }
 
-fn corner_ol_br(shape: &Shape) -> Vec<f32> {
+fn corner_ol_br(shape: &Shape, t: f32) -> Vec<f32> {
     let (sx, sy) = shape.size();
-    [rec_ol_bot(shape), rec_ol_right(shape),
-    rec_ol_top(&Shape {x1: shape.x1 + sx, y1: shape.y1, x2: shape.x2, y2: shape.y2}),
-    rec_ol_left(&Shape {x1: shape.x1 + sx, y1: shape.y1, x2: shape.x2, y2: shape.y2 - sy}),
-    rec_ol_top(&Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2 - sx, y2: shape.y2}),
-    rec_ol_left(&Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2}),
-    rec(&Shape {x1: shape.x1 + sx, y1: shape.y1 + sy, x2: shape.x1 + sx + OUTLINE_THICKNESS, y2: shape.y1 + sy + OUTLINE_THICKNESS})].concat()
+    [rec_ol_bot(shape, t), rec_ol_right(shape, t),
+    rec_ol_top(&Shape {x1: shape.x1 + sx, y1: shape.y1, x2: shape.x2, y2: shape.y2}, t),
+    rec_ol_left(&Shape {x1: shape.x1 + sx, y1: shape.y1, x2: shape.x2, y2: shape.y2 - sy}, t),
+    rec_ol_top(&Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2 - sx, y2: shape.y2}, t),
+    rec_ol_left(&Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2}, t),
+    rec(&Shape {x1: shape.x1 + sx, y1: shape.y1 + sy, x2: shape.x1 + sx + t, y2: shape.y1 + sy + t})].concat()
 }
 
-fn corner_ol_bl(shape: &Shape) -> Vec<f32> {
+fn corner_ol_bl(shape: &Shape, t: f32) -> Vec<f32> {
     let (sx, sy) = shape.size();
-    [rec_ol_bot(shape), rec_ol_left(shape),
-    rec_ol_top(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2 - sx, y2: shape.y2}),
-    rec_ol_right(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2 - sx, y2: shape.y2 - sy}),
-    rec_ol_top(&Shape {x1: shape.x1 + sx, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2}),
-    rec_ol_right(&Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2}),
-    rec(&Shape {x1: shape.x1 + sx - OUTLINE_THICKNESS, y1: shape.y1 + sy, x2: shape.x1 + sx, y2: shape.y1 + sy + OUTLINE_THICKNESS})].concat()
+    [rec_ol_bot(shape, t), rec_ol_left(shape, t),
+    rec_ol_top(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2 - sx, y2: shape.y2}, t),
+    rec_ol_right(&Shape {x1: shape.x1, y1: shape.y1, x2: shape.x2 - sx, y2: shape.y2 - sy}, t),
+    rec_ol_top(&Shape {x1: shape.x1 + sx, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2}, t),
+    rec_ol_right(&Shape {x1: shape.x1, y1: shape.y1 + sy, x2: shape.x2, y2: shape.y2}, t),
+    rec(&Shape {x1: shape.x1 + sx - t, y1: shape.y1 + sy, x2: shape.x1 + sx, y2: shape.y1 + sy + t})].concat()
 }
@@ -4,6 +4,7 @@ mod rounds;
 mod crests;
 mod ramps;
 mod misc;
+mod studs;
 
 pub use self::primitives::*;
 pub use self::wedges::*;
@@ -11,6 +12,7 @@ pub use self::rounds::*;
 pub use self::crests::*;
 pub use self::ramps::*;
 pub use self::misc::*;
+pub use self::studs::*;
 
 use crate::brick::Brick;
 
@@ -63,19 +65,21 @@ pub fn calculate_brick_vertices(name: &str, brick: &Brick) -> Vec<f32> {
     }
 }
 
-pub fn calculate_brick_outline_vertices(name: &str, brick: &Brick) -> Vec<f32> {
+// Outline strips run along the inside of the footprint, `t` save units wide.
+// Positions are linear in `t`, which is what lets the renderer rescale them.
+pub fn calculate_brick_outline_vertices(name: &str, brick: &Brick, t: f32) -> Vec<f32> {
     let shape = Shape::from(brick);
     match BrickKind::from_name(name) {
-        BrickKind::Corner => corner_ol(brick, &shape),
-        BrickKind::SideWedge => side_wedge_ol(brick, &shape),
-        BrickKind::Wedge => wedge_ol(brick, &shape),
-        BrickKind::Ramp => ramp_ol(brick, &shape),
-        BrickKind::RampCorner => ramp_corner_ol(brick, &shape),
-        BrickKind::RampCornerInverted => ramp_corner_inverted_ol(brick, &shape),
-        BrickKind::RampCrest => ramp_crest_ol(brick, &shape),
-        BrickKind::RampCrestEnd => ramp_crest_end_ol(brick, &shape),
-        BrickKind::Round => round_ol(brick, &shape),
-        BrickKind::Rect => rec_ol(&shape),
+        BrickKind::Corner => corner_ol(brick, &shape, t),
+        BrickKind::SideWedge => side_wedge_ol(brick, &shape, t),
+        BrickKind::Wedge => wedge_ol(brick, &shape, t),
+        BrickKind::Ramp => ramp_ol(brick, &shape, t),
+        BrickKind::RampCorner => ramp_corner_ol(brick, &shape, t),
+        BrickKind::RampCornerInverted => ramp_corner_inverted_ol(brick, &shape, t),
+        BrickKind::RampCrest => ramp_crest_ol(brick, &shape, t),
+        BrickKind::RampCrestEnd => ramp_crest_end_ol(brick, &shape, t),
+        BrickKind::Round => round_ol(brick, &shape, t),
+        BrickKind::Rect => rec_ol(&shape, t),
     }
 }
 
@@ -24,10 +24,10 @@ impl Shape {
         (self.x2 - self.x1) / (self.y2 - self.y1)
     }
 
-    pub fn angular_offsets(&self) -> (f32, f32) {
+    pub fn angular_offsets(&self, t: f32) -> (f32, f32) {
         let ar = self.aspect_ratio();
         let theta = (1.0 as f32).atan2(ar);
-        let dx = OUTLINE_THICKNESS / theta.sin();
+        let dx = t / theta.sin();
         let dy = dx / ar;
         (dx, dy)
     }
@@ -119,141 +119,148 @@ pub fn rec_br(shape: &Shape) -> Vec<f32> {
     rec(&Shape {x1: x1 + (x2-x1)/2.0, y1: y1 + (y2-y1)/2.0, x2, y2})
 }
 
-pub fn tri_ol(shape: &Shape, tri_type: Tri) -> Vec<f32> {
+pub fn tri_ol(shape: &Shape, tri_type: Tri, t: f32) -> Vec<f32> {
     match tri_type {
         Tri::TopLeft =>
-            vec![tri_ol_tl_top(shape), tri_ol_tl_left(shape), tri_ol_tl_diag(shape)].concat(),
+            vec![tri_ol_tl_top(shape, t), tri_ol_tl_left(shape, t), tri_ol_tl_diag(shape, t)].concat(),
         Tri::TopRight =>
-            vec![tri_ol_tr_top(shape), tri_ol_tr_right(shape), tri_ol_tr_diag(shape)].concat(),
+            vec![tri_ol_tr_top(shape, t), tri_ol_tr_right(shape, t), tri_ol_tr_diag(shape, t)].concat(),
         Tri::BotLeft =>
-            vec![tri_ol_bl_bot(shape), tri_ol_bl_left(shape), tri_ol_bl_diag(shape)].concat(),
+            vec![tri_ol_bl_bot(shape, t), tri_ol_bl_left(shape, t), tri_ol_bl_diag(shape, t)].concat(),
         Tri::BotRight =>
-            vec![tri_ol_br_bot(shape), tri_ol_br_right(shape), tri_ol_br_diag(shape)].concat(),
+            vec![tri_ol_br_bot(shape, t), tri_ol_br_right(shape, t), tri_ol_br_diag(shape, t)].concat(),
     }
 }
 
-pub fn tri_ol_tl_top(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_tl_top(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, y1, x2, _y2) = shape.unpack();
-    let dx = OUTLINE_THICKNESS * shape.aspect_ratio();
-    vec![x1, y1,  x1, y1 + OUTLINE_THICKNESS,  x2 - dx, y1 + OUTLINE_THICKNESS,
-         x1, y1,  x2 - dx, y1 + OUTLINE_THICKNESS,  x2, y1]
+    let dx = t * shape.aspect_ratio();
+    vec![x1, y1,  x1, y1 + t,  x2 - dx, y1 + t,
+         x1, y1,  x2 - dx, y1 + t,  x2, y1]
 }
 
-pub fn tri_ol_tl_left(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_tl_left(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, y1, _x2, y2) = shape.unpack();
-    let dy = OUTLINE_THICKNESS / shape.aspect_ratio();
-    vec![x1, y1,  x1, y2,  x1 + OUTLINE_THICKNESS, y2 - dy,
-         x1, y1,  x1 + OUTLINE_THICKNESS, y2 - dy,  x1 + OUTLINE_THICKNESS, y1]
+    let dy = t / shape.aspect_ratio();
+    vec![x1, y1,  x1, y2,  x1 + t, y2 - dy,
+         x1, y1,  x1 + t, y2 - dy,  x1 + t, y1]
 }
 
-pub fn tri_ol_tl_diag(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_tl_diag(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, y1, x2, y2) = shape.unpack();
-    let (dx, dy) = shape.angular_offsets();
+    let (dx, dy) = shape.angular_offsets(t);
     vec![x1, y2 - dy,  x1, y2,  x2 - dx, y1,
          x2 - dx, y1,  x1, y2,  x2, y1]
 }
 
-pub fn tri_ol_tr_top(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_tr_top(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, y1, x2, _y2) = shape.unpack();
-    let dx = OUTLINE_THICKNESS * shape.aspect_ratio();
-    vec![x1, y1,  x1 + dx, y1 + OUTLINE_THICKNESS,   x2,  y1 + OUTLINE_THICKNESS,
-         x1, y1,  x2,  y1 + OUTLINE_THICKNESS,   x2,  y1]
+    let dx = t * shape.aspect_ratio();
+    vec![x1, y1,  x1 + dx, y1 + t,   x2,  y1 + t,
+         x1, y1,  x2,  y1 + t,   x2,  y1]
 }
 
-pub fn tri_ol_tr_right(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_tr_right(shape: &Shape, t: f32) -> Vec<f32> {
     let (_x1, y1, x2, y2) = shape.unpack();
-    let dy = OUTLINE_THICKNESS / shape.aspect_ratio();
-    vec![x2 - OUTLINE_THICKNESS, y1,  x2 - OUTLINE_THICKNESS,  y2 - dy,  x2, y2,
-         x2 - OUTLINE_THICKNESS, y1,  x2, y2,  x2, y1]
+    let dy = t / shape.aspect_ratio();
+    vec![x2 - t, y1,  x2 - t,  y2 - dy,  x2, y2,
+         x2 - t, y1,  x2, y2,  x2, y1]
 }
 
-pub fn tri_ol_tr_diag(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_tr_diag(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, y1, x2, y2) = shape.unpack();
-    let (dx, dy) = shape.angular_offsets();
+    let (dx, dy) = shape.angular_offsets(t);
     vec![x1, y1,  x2,   y2,    x2,  y2 - dy,
          x1, y1,  x2,  y2 - dy,  x1 + dx, y1]
 }
 
-pub fn tri_ol_bl_bot(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_bl_bot(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, _y1, x2, y2) = shape.unpack();
-    let dx = OUTLINE_THICKNESS * shape.aspect_ratio();
-    vec![x1,   y2 - OUTLINE_THICKNESS,   x1,  y2,  x2, y2,
-         x1, y2 - OUTLINE_THICKNESS,   x2,  y2,   x2 - dx, y2 - OUTLINE_THICKNESS]
+    let dx = t * shape.aspect_ratio();
+    vec![x1,   y2 - t,   x1,  y2,  x2, y2,
+         x1, y2 - t,   x2,  y2,   x2 - dx, y2 - t]
 }
 
-pub fn tri_ol_bl_left(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_bl_left(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, y1, _x2, y2) = shape.unpack();
-    let dy = OUTLINE_THICKNESS / shape.aspect_ratio();
-    vec![x1, y1,  x1, y2,  x1 + OUTLINE_THICKNESS, y2,
-         x1, y1,  x1 + OUTLINE_THICKNESS, y2,  x1 + OUTLINE_THICKNESS, y1 + dy]
+    let dy = t / shape.aspect_ratio();
+    vec![x1, y1,  x1, y2,  x1 + t, y2,
+         x1, y1,  x1 + t, y2,  x1 + t, y1 + dy]
 }
 
-pub fn tri_ol_bl_diag(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_bl_diag(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, y1, x2, y2) = shape.unpack();
-    let (dx, dy) = shape.angular_offsets();
+    let (dx, dy) = shape.angular_offsets(t);
     vec![x1, y1 + dy,  x2 - dx, y2,  x2, y2,
          x1, y1 + dy,  x2,      y2,  x1, y1]
 }
 
-pub fn tri_ol_br_bot(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_br_bot(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, _y1, x2, y2) = shape.unpack();
-    let dx = OUTLINE_THICKNESS * shape.aspect_ratio();
-    vec![x1, y2,  x1 + dx, y2 - OUTLINE_THICKNESS,  x2, y2 - OUTLINE_THICKNESS,
-         x1, y2,  x2,      y2 - OUTLINE_THICKNESS,  x2, y2]
+    let dx = t * shape.aspect_ratio();
+    vec![x1, y2,  x1 + dx, y2 - t,  x2, y2 - t,
+         x1, y2,  x2,      y2 - t,  x2, y2]
 }
 
-pub fn tri_ol_br_right(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_br_right(shape: &Shape, t: f32) -> Vec<f32> {
     let (_x1, y1, x2, y2) = shape.unpack();
-    let dy = OUTLINE_THICKNESS / shape.aspect_ratio();
-    vec![x2, y2,  x2 - OUTLINE_THICKNESS, y2,       x2 - OUTLINE_THICKNESS, y1 + dy,
-         x2, y2,  x2 - OUTLINE_THICKNESS, y1 + dy,  x2, y1]
+    let dy = t / shape.aspect_ratio();
+    vec![x2, y2,  x2 - t, y2,       x2 - t, y1 + dy,
+         x2, y2,  x2 - t, y1 + dy,  x2, y1]
 }
 
-pub fn tri_ol_br_diag(shape: &Shape) -> Vec<f32> {
+pub fn tri_ol_br_diag(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, y1, x2, y2) = shape.unpack();
-    let (dx, dy) = shape.angular_offsets();
+    let (dx, dy) = shape.angular_offsets(t);
     vec![x1, y2,  x1 + dx, y2,       x2, y1 + dy,
          x1, y2,  x2,      y1 + dy,  x2, y1]
 }
 
-pub fn rec_ol(shape: &Shape) -> Vec<f32> {
-    [rec_ol_top(shape), rec_ol_right(shape), rec_ol_bot(shape), rec_ol_left(shape)].concat()
+pub fn rec_ol(shape: &Shape, t: f32) -> Vec<f32> {
+    [rec_ol_top(shape, t), rec_ol_right(shape, t), rec_ol_bot(shape, t), rec_ol_left(shape, t)].concat()
 }
 
-pub fn rec_ol_top(shape: &Shape) -> Vec<f32> {
+pub fn rec_ol_top(shape: &Shape, t: f32) -> Vec<f32> {
     rec(&Shape {x1: shape.x1, y1: shape.y1,
-                 x2: shape.x2, y2: shape.y1 + OUTLINE_THICKNESS})
+                 x2: shape.x2, y2: shape.y1 + t})
 }
 
-pub fn rec_ol_right(shape: &Shape) -> Vec<f32> {
-    rec(&Shape {x1: shape.x2 - OUTLINE_THICKNESS, y1: shape.y1,
+pub fn rec_ol_right(shape: &Shape, t: f32) -> Vec<f32> {
+    rec(&Shape {x1: shape.x2 - t, y1: shape.y1,
                  x2: shape.x2, y2: shape.y2})
 }
 
-pub fn rec_ol_bot(shape: &Shape) -> Vec<f32> {
-    rec(&Shape {x1: shape.x1, y1: shape.y2 - OUTLINE_THICKNESS,
+pub fn rec_ol_bot(shape: &Shape, t: f32) -> Vec<f32> {
+    rec(&Shape {x1: shape.x1, y1: shape.y2 - t,
                  x2: shape.x2, y2: shape.y2})
 }
 
-pub fn rec_ol_left(shape: &Shape) -> Vec<f32> {
+pub fn rec_ol_left(shape: &Shape, t: f32) -> Vec<f32> {
     rec(&Shape {x1: shape.x1, y1: shape.y1,
-                 x2: shape.x1 + OUTLINE_THICKNESS, y2: shape.y2})
+                 x2: shape.x1 + t, y2: shape.y2})
 }
 
-pub fn rec_ol_no_top(shape: &Shape) -> Vec<f32> {
-    [rec_ol_right(shape), rec_ol_bot(shape), rec_ol_left(shape)].concat()
+// Outline strip along an axis-aligned edge, on the side `inward` points to
+pub fn edge_ol(from: (f32, f32), to: (f32, f32), inward: (f32, f32), t: f32) -> Vec<f32> {
+    let (ox, oy) = (inward.0 * t, inward.1 * t);
+    rec(&Shape {x1: from.0.min(from.0 + ox), y1: from.1.min(from.1 + oy),
+                 x2: to.0.max(to.0 + ox), y2: to.1.max(to.1 + oy)})
 }
 
-pub fn rec_ol_no_right(shape: &Shape) -> Vec<f32> {
-    [rec_ol_top(shape), rec_ol_bot(shape), rec_ol_left(shape)].concat()
+pub fn rec_ol_no_top(shape: &Shape, t: f32) -> Vec<f32> {
+    [rec_ol_right(shape, t), rec_ol_bot(shape, t), rec_ol_left(shape, t)].concat()
 }
 
-pub fn rec_ol_no_bot(shape: &Shape) -> Vec<f32> {
-    [rec_ol_top(shape), rec_ol_right(shape), rec_ol_left(shape)].concat()
+pub fn rec_ol_no_right(shape: &Shape, t: f32) -> Vec<f32> {
+    [rec_ol_top(shape, t), rec_ol_bot(shape, t), rec_ol_left(shape, t)].concat()
 }
 
-pub fn rec_ol_no_left(shape: &Shape) -> Vec<f32> {
-    [rec_ol_top(shape), rec_ol_right(shape), rec_ol_bot(shape)].concat()
+pub fn rec_ol_no_bot(shape: &Shape, t: f32) -> Vec<f32> {
+    [rec_ol_top(shape, t), rec_ol_right(shape, t), rec_ol_left(shape, t)].concat()
+}
+
+pub fn rec_ol_no_left(shape: &Shape, t: f32) -> Vec<f32> {
+    [rec_ol_top(shape, t), rec_ol_right(shape, t), rec_ol_bot(shape, t)].concat()
 }
 
 pub fn circle(shape: &Shape) -> Vec<f32> {
@@ -278,7 +285,7 @@ pub fn circle(shape: &Shape) -> Vec<f32> {
     circle
 }
 
-pub fn circle_ol(shape: &Shape) -> Vec<f32> {
+pub fn circle_ol(shape: &Shape, t: f32) -> Vec<f32> {
     let (x1, y1, _x2, _y2) = shape.unpack();
     let (sx, sy) = shape.size();
     let x = x1 + sx;
@@ -291,13 +298,13 @@ pub fn circle_ol(shape: &Shape) -> Vec<f32> {
     while theta < DEG360 {
         let dx1 = radius * theta.cos();
         let dy1 = radius * theta.sin();
-        let dxi1 = (radius - OUTLINE_THICKNESS) * theta.cos();
-        let dyi1 = (radius - OUTLINE_THICKNESS) * theta.sin();
+        let dxi1 = (radius - t) * theta.cos();
+        let dyi1 = (radius - t) * theta.sin();
         theta += SLICE_ANGLE;
         let dx2 = radius * theta.cos();
         let dy2 = radius * theta.sin();
-        let dxi2 = (radius - OUTLINE_THICKNESS) * theta.cos();
-        let dyi2 = (radius - OUTLINE_THICKNESS) * theta.sin();
+        let dxi2 = (radius - t) * theta.cos();
+        let dyi2 = (radius - t) * theta.sin();
         circle.append(&mut vec![x + dxi2, y + dyi2,  x + dxi1, y + dyi1,  x + dx1, y + dy1,
                                 x + dxi2, y + dyi2,  x + dx1, y + dy1,  x + dx2, y + dy2]);
     }
@@ -9,9 +9,9 @@ pub fn round(brick: &Brick, shape: &Shape) -> Vec<f32> {
     }
 }
 
-pub fn round_ol(brick: &Brick, shape: &Shape) -> Vec<f32> {
+pub fn round_ol(brick: &Brick, shape: &Shape, t: f32) -> Vec<f32> {
     match brick.direction {
-        Direction::ZPositive | Direction::ZNegative => circle_ol(shape),
-        _ => rec_ol(shape)
+        Direction::ZPositive | Direction::ZNegative => circle_ol(shape, t),
+        _ => rec_ol(shape, t)
     }
 }
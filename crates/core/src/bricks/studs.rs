@@ -0,0 +1,89 @@
+use brickadia::save::Direction;
+use crate::brick::Brick;
+use crate::color::{mix, shade};
+use super::primitives::*;
+use super::BrickKind;
+
+// Radius of a stud seen from above
+const STUD_RADIUS: f32 = 3.0;
+
+// How far the shadow of a stud falls, down and to the right
+const STUD_SHADOW_OFFSET: f32 = 0.6;
+
+// Width of the bevel along the edges of a studded top face
+const BEVEL_WIDTH: f32 = 1.0;
+
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+// Where studs sit on a brick's top face
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Studs {
+    // Tiles, smooth and shaped bricks
+    None,
+    // One per stud of the footprint
+    Grid,
+    // A single stud in the middle, like a jumper plate
+    Center,
+}
+
+impl Studs {
+    pub fn from_name(name: &str) -> Studs {
+        match name {
+            "PB_DefaultBrick" | "B_1x1_Round" | "B_1x1F_Round" | "B_2x2_Round" | "B_2x2F_Round" => Studs::Grid,
+            "B_1x2f_Plate_Center" | "B_2x2f_Plate_Center" => Studs::Center,
+            _ => Studs::None,
+        }
+    }
+}
+
+// Stud circles and bevels drawn over a brick's fill for the brick look, as
+// triangle lists with their colors in draw order. Only bricks facing up
+// show their studs; rounds keep their curved edge unbevelled.
+pub fn stud_faces(name: &str, brick: &Brick, color: [u8; 4]) -> Vec<(Vec<f32>, [u8; 4])> {
+    let studs = Studs::from_name(name);
+    if studs == Studs::None || brick.direction != Direction::ZPositive {
+        return Vec::new();
+    }
+    let shape = Shape::from(brick);
+    let (x1, y1, x2, y2) = shape.unpack();
+    let centers: Vec<(f32, f32)> = match studs {
+        Studs::Grid => {
+            let cols = ((x2 - x1) / STUD_WIDTH).floor() as usize;
+            let rows = ((y2 - y1) / STUD_WIDTH).floor() as usize;
+            (0..rows)
+                .flat_map(|r| (0..cols).map(move |c| (c, r)))
+                .map(|(c, r)| (x1 + (c as f32 + 0.5) * STUD_WIDTH, y1 + (r as f32 + 0.5) * STUD_WIDTH))
+                .collect()
+        }
+        Studs::Center => vec![((x1 + x2) / 2.0, (y1 + y2) / 2.0)],
+        Studs::None => Vec::new(),
+    };
+
+    let mut faces = Vec::new();
+    let rect = BrickKind::from_name(name) == BrickKind::Rect;
+    if rect && x2 - x1 > BEVEL_WIDTH * 2.0 && y2 - y1 > BEVEL_WIDTH * 2.0 {
+        let (light, dark) = bevel(&shape, BEVEL_WIDTH);
+        faces.push((light, mix(color, WHITE, 0.2)));
+        faces.push((dark, shade(color, 0.8)));
+    }
+    let stud = |(x, y): (f32, f32)| circle(&Shape { x1: x - STUD_RADIUS, y1: y - STUD_RADIUS, x2: x + STUD_RADIUS, y2: y + STUD_RADIUS });
+    let shadows = centers.iter().flat_map(|&(x, y)| stud((x + STUD_SHADOW_OFFSET, y + STUD_SHADOW_OFFSET))).collect();
+    let tops = centers.iter().flat_map(|&center| stud(center)).collect();
+    faces.push((shadows, shade(color, 0.75)));
+    faces.push((tops, mix(color, WHITE, 0.12)));
+    faces
+}
+
+// Strips `t` wide just inside a rectangle's edges, mitred at the corners:
+// the top and left edges catching the light, then the bottom and right
+fn bevel(shape: &Shape, t: f32) -> (Vec<f32>, Vec<f32>) {
+    let (x1, y1, x2, y2) = shape.unpack();
+    let quad = |a: (f32, f32), b: (f32, f32), c: (f32, f32), d: (f32, f32)| {
+        vec![a.0, a.1,  b.0, b.1,  c.0, c.1,  a.0, a.1,  c.0, c.1,  d.0, d.1]
+    };
+    let top = quad((x1, y1), (x2, y1), (x2 - t, y1 + t), (x1 + t, y1 + t));
+    let left = quad((x1, y1), (x1 + t, y1 + t), (x1 + t, y2 - t), (x1, y2));
+    let bot = quad((x1, y2), (x1 + t, y2 - t), (x2 - t, y2 - t), (x2, y2));
+    let right = quad((x2, y1), (x2, y2), (x2 - t, y2 - t), (x2 - t, y1 + t));
+    ([top, left].concat(), [bot, right].concat())
+}
@@ -23,24 +23,24 @@ pub fn side_wedge(brick: &Brick, shape: &Shape) -> Vec<f32> {
     }
 }
 
-pub fn side_wedge_ol(brick: &Brick, shape: &Shape) -> Vec<f32> {
+pub fn side_wedge_ol(brick: &Brick, shape: &Shape, t: f32) -> Vec<f32> {
     match brick.direction {
         Direction::ZPositive => 
             match brick.rotation {
-                Rotation::Deg0   => tri_ol(shape, Tri::TopLeft),
-                Rotation::Deg90  => tri_ol(shape, Tri::TopRight),
-                Rotation::Deg180 => tri_ol(shape, Tri::BotRight),
-                Rotation::Deg270 => tri_ol(shape, Tri::BotLeft)
+                Rotation::Deg0   => tri_ol(shape, Tri::TopLeft, t),
+                Rotation::Deg90  => tri_ol(shape, Tri::TopRight, t),
+                Rotation::Deg180 => tri_ol(shape, Tri::BotRight, t),
+                Rotation::Deg270 => tri_ol(shape, Tri::BotLeft, t)
             },
         Direction::ZNegative =>
             match brick.rotation {
-                Rotation::Deg0   => tri_ol(shape, Tri::TopRight),
-                Rotation::Deg90  => tri_ol(shape, Tri::TopLeft),
-                Rotation::Deg180 => tri_ol(shape, Tri::BotLeft),
-                Rotation::Deg270 => tri_ol(shape, Tri::BotRight)
+                Rotation::Deg0   => tri_ol(shape, Tri::TopRight, t),
+                Rotation::Deg90  => tri_ol(shape, Tri::TopLeft, t),
+                Rotation::Deg180 => tri_ol(shape, Tri::BotLeft, t),
+                Rotation::Deg270 => tri_ol(shape, Tri::BotRight, t)
             },
         _ => 
-            rec_ol(shape),
+            rec_ol(shape, t),
     }
 }
 
@@ -69,27 +69,27 @@ pub fn wedge(brick: &Brick, shape: &Shape) -> Vec<f32> {
     }
 }
 
-pub fn wedge_ol(brick: &Brick, shape: &Shape) -> Vec<f32> {
+pub fn wedge_ol(brick: &Brick, shape: &Shape, t: f32) -> Vec<f32> {
     match brick.rotation {
         Rotation::Deg90 =>
             match brick.direction {
-                Direction::XPositive => tri_ol(shape, Tri::BotLeft),
-                Direction::XNegative => tri_ol(shape, Tri::TopRight),
-                Direction::YPositive => tri_ol(shape, Tri::TopLeft),
-                Direction::YNegative => tri_ol(shape, Tri::BotRight),
+                Direction::XPositive => tri_ol(shape, Tri::BotLeft, t),
+                Direction::XNegative => tri_ol(shape, Tri::TopRight, t),
+                Direction::YPositive => tri_ol(shape, Tri::TopLeft, t),
+                Direction::YNegative => tri_ol(shape, Tri::BotRight, t),
                 Direction::ZPositive | Direction::ZNegative =>
-                    rec_ol(shape),
+                    rec_ol(shape, t),
             },
         Rotation::Deg270 =>
             match brick.direction {
-                Direction::XPositive => tri_ol(shape, Tri::TopLeft),
-                Direction::XNegative => tri_ol(shape, Tri::BotRight),
-                Direction::YPositive => tri_ol(shape, Tri::TopRight),
-                Direction::YNegative => tri_ol(shape, Tri::BotLeft),
+                Direction::XPositive => tri_ol(shape, Tri::TopLeft, t),
+                Direction::XNegative => tri_ol(shape, Tri::BotRight, t),
+                Direction::YPositive => tri_ol(shape, Tri::TopRight, t),
+                Direction::YNegative => tri_ol(shape, Tri::BotLeft, t),
                 Direction::ZPositive | Direction::ZNegative =>
-                    rec_ol(shape),
+                    rec_ol(shape, t),
             },
         _ =>
-            rec_ol(shape),
+            rec_ol(shape, t),
     }
 }
\ No newline at end of file
@@ -31,6 +31,12 @@ impl Color {
         self.g = val_as_srgb(self.g);
         self.b = val_as_srgb(self.b);
     }
+
+    pub fn convert_to_linear(&mut self) {
+        self.r = val_as_linear(self.r);
+        self.g = val_as_linear(self.g);
+        self.b = val_as_linear(self.b);
+    }
 }
 
 pub fn convert_color(color: &brickadia::save::Color) -> Color {
@@ -42,10 +48,92 @@ pub fn convert_color(color: &brickadia::save::Color) -> Color {
     }
 }
 
+/// A legacy save's color palette as display sRGB (brs stores linear values).
+pub fn srgb_palette(colors: &[brickadia::save::Color]) -> Vec<Color> {
+    colors.iter().map(|color| {
+        let mut color = convert_color(color);
+        color.convert_to_srgb();
+        color
+    }).collect()
+}
+
+/// Display color of a legacy brick, from the save's palette or its own
+/// unique color.
+pub fn brick_color(color: &brickadia::save::BrickColor, palette: &[Color]) -> Color {
+    match color {
+        brickadia::save::BrickColor::Index(index) => palette[*index as usize],
+        brickadia::save::BrickColor::Unique(color) => {
+            let mut color = convert_color(color);
+            color.convert_to_srgb();
+            color
+        }
+    }
+}
+
 pub fn val_as_srgb(val: f32) -> f32 {
     if val > 0.003_130_8 {
         1.055 * val.powf(1.0 / 2.4) - 0.055
     } else {
         val * 12.92
     }    
-}
\ No newline at end of file
+}
+
+pub fn val_as_linear(val: f32) -> f32 {
+    if val > 0.040_45 {
+        ((val + 0.055) / 1.055).powf(2.4)
+    } else {
+        val / 12.92
+    }
+}
+
+/// The linear byte nearest an sRGB byte's value.
+pub fn byte_as_linear(value: u8) -> u8 {
+    (val_as_linear(value as f32 / 255.0) * 255.0).round() as u8
+}
+
+/// The sRGB byte nearest a linear byte's value.
+pub fn byte_as_srgb(value: u8) -> u8 {
+    (val_as_srgb(value as f32 / 255.0) * 255.0).round() as u8
+}
+
+/// Parses `#rrggbb` or `#rrggbbaa` (the `#` is optional) into rgba bytes.
+pub fn parse_hex(s: &str) -> Result<[u8; 4], String> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return Err(format!("invalid color '{}', expected #rrggbb or #rrggbbaa", s));
+    }
+    let mut rgba = [255u8; 4];
+    for (i, byte) in rgba.iter_mut().enumerate().take(hex.len() / 2) {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| format!("invalid color '{}', expected #rrggbb or #rrggbbaa", s))?;
+    }
+    Ok(rgba)
+}
+
+/// Halves a display color's brightness, keeping alpha.
+pub fn darken(color: [u8; 4]) -> [u8; 4] {
+    [color[0] / 2, color[1] / 2, color[2] / 2, color[3]]
+}
+
+/// Scales a display color's brightness by `factor` (0.0..=1.0), keeping
+/// alpha.
+pub fn shade(color: [u8; 4], factor: f32) -> [u8; 4] {
+    let scale = |c: u8| (c as f32 * factor).round().clamp(0.0, 255.0) as u8;
+    [scale(color[0]), scale(color[1]), scale(color[2]), color[3]]
+}
+
+/// Blends a display color `amount` (0.0..=1.0) of the way toward `toward`,
+/// keeping its own alpha.
+pub fn mix(color: [u8; 4], toward: [u8; 4], amount: f32) -> [u8; 4] {
+    let blend = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round().clamp(0.0, 255.0) as u8;
+    [blend(color[0], toward[0]), blend(color[1], toward[1]), blend(color[2], toward[2]), color[3]]
+}
+
+/// Formats rgba bytes as `#rrggbb`, with an alpha pair only when not opaque.
+pub fn to_hex(color: [u8; 4]) -> String {
+    if color[3] == 255 {
+        format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", color[0], color[1], color[2], color[3])
+    }
+}
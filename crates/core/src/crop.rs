@@ -0,0 +1,88 @@
+use brickadia::save::{Preview, SaveData};
+
+/// A box to cut out of a save, in save units (10 per stud). A brick is
+/// inside when its origin is: `rect` and `z` include their low edges and
+/// exclude their high ones, so adjacent crops never share a brick. Without
+/// `z`, every height is inside.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CropRegion {
+    pub rect: (i32, i32, i32, i32),
+    pub z: Option<(i32, i32)>,
+}
+
+impl CropRegion {
+    pub fn contains(&self, (x, y, z): (i32, i32, i32)) -> bool {
+        let within = |v: i32, a: i32, b: i32| a.min(b) <= v && v < a.max(b);
+        within(x, self.rect.0, self.rect.2)
+            && within(y, self.rect.1, self.rect.3)
+            && self.z.is_none_or(|(z1, z2)| within(z, z1, z2))
+    }
+}
+
+/// Keeps only the bricks inside `region`, leaving their assets, colors,
+/// materials, owners and components as they were. Component brick lists,
+/// owner brick counts and the header's brick count are updated to match,
+/// and the preview image, which no longer shows the save, is dropped.
+/// Returns the number of bricks kept.
+pub fn crop(save: &mut SaveData, region: &CropRegion) -> usize {
+    let mut kept: u32 = 0;
+    let new_index: Vec<Option<u32>> = save.bricks.iter()
+        .map(|brick| {
+            region.contains(brick.position).then(|| {
+                kept += 1;
+                kept - 1
+            })
+        })
+        .collect();
+    let mut keep = new_index.iter();
+    save.bricks.retain(|_| keep.next().unwrap().is_some());
+
+    for component in save.components.values_mut() {
+        component.brick_indices = component.brick_indices.iter()
+            .filter_map(|&i| new_index.get(i as usize).copied().flatten())
+            .collect();
+    }
+    save.components.retain(|_, component| !component.brick_indices.is_empty());
+
+    // Owner 0 is public; the rest index the owner list from 1
+    for owner in &mut save.header2.brick_owners {
+        owner.bricks = 0;
+    }
+    for brick in &save.bricks {
+        if let Some(owner) = (brick.owner_index as usize).checked_sub(1).and_then(|i| save.header2.brick_owners.get_mut(i)) {
+            owner.bricks += 1;
+        }
+    }
+
+    save.header1.brick_count = save.bricks.len() as u32;
+    save.preview = Preview::None;
+    save.bricks.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use brickadia::save::Brick;
+
+    #[test]
+    fn crop_keeps_bricks_inside_in_order() {
+        let mut save = SaveData::default();
+        for x in [0, 50, 100, 150] {
+            save.bricks.push(Brick { position: (x, 0, 6), ..Default::default() });
+        }
+        let region = CropRegion { rect: (200, 10, 40, -10), z: None };
+        assert_eq!(crop(&mut save, &region), 3);
+        let xs: Vec<i32> = save.bricks.iter().map(|b| b.position.0).collect();
+        assert_eq!(xs, vec![50, 100, 150]);
+        assert_eq!(save.header1.brick_count, 3);
+    }
+
+    #[test]
+    fn region_excludes_its_far_edges() {
+        let region = CropRegion { rect: (0, 0, 100, 100), z: Some((0, 50)) };
+        assert!(region.contains((0, 0, 0)));
+        assert!(!region.contains((100, 50, 10)));
+        assert!(!region.contains((50, 50, 50)));
+        assert!(region.contains((99, 99, 49)));
+    }
+}
@@ -0,0 +1,89 @@
+use std::str::FromStr;
+
+/// How a build fills an image whose width and height are both fixed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Fit {
+    /// All of the build shows, leaving space along one axis
+    #[default]
+    Contain,
+    /// The image is filled, cutting the build off along one axis
+    Cover,
+}
+
+impl FromStr for Fit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Fit, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "contain" => Ok(Fit::Contain),
+            "cover" => Ok(Fit::Cover),
+            _ => Err(format!("unknown fit '{}', expected contain or cover", s)),
+        }
+    }
+}
+
+/// Half-extent of an axis-aligned box rotated about its center.
+pub fn rotated_half_extent(half_w: f32, half_h: f32, rotation: f32) -> (f32, f32) {
+    let (sin, cos) = rotation.sin_cos();
+    (
+        half_w * cos.abs() + half_h * sin.abs(),
+        half_w * sin.abs() + half_h * cos.abs(),
+    )
+}
+
+/// Pixels per save unit at which `bounds` (x1, y1, x2, y2 in save units),
+/// rotated by `rotation` radians, fits a `width` by `height` pixel image
+/// inside a `margin` of pixels on every side. Either dimension can be left
+/// out to fit the other alone. `None` when neither is given or the margins
+/// leave no room.
+pub fn fit_scale(
+    bounds: (i32, i32, i32, i32),
+    rotation: f32,
+    width: Option<u32>,
+    height: Option<u32>,
+    margin: u32,
+    fit: Fit,
+) -> Option<f32> {
+    let (x1, y1, x2, y2) = bounds;
+    let (half_w, half_h) = rotated_half_extent(
+        (x2 - x1).max(1) as f32 / 2.0,
+        (y2 - y1).max(1) as f32 / 2.0,
+        rotation,
+    );
+    let room = |pixels: u32| pixels.checked_sub(margin * 2).filter(|&room| room > 0).map(|room| room as f32);
+    let scale_x = width.map(|w| room(w).map(|room| room / (half_w * 2.0)));
+    let scale_y = height.map(|h| room(h).map(|room| room / (half_h * 2.0)));
+    match (scale_x, scale_y) {
+        (Some(x), Some(y)) => {
+            let (x, y) = (x?, y?);
+            Some(match fit {
+                Fit::Contain => x.min(y),
+                Fit::Cover => x.max(y),
+            })
+        }
+        (Some(x), None) => x,
+        (None, Some(y)) => y,
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_rotated_bounds_inside_margins() {
+        let bounds = (-50, -25, 50, 25);
+        assert_eq!(fit_scale(bounds, 0.0, Some(220), Some(220), 10, Fit::Contain), Some(2.0));
+        assert_eq!(fit_scale(bounds, 0.0, Some(220), Some(220), 10, Fit::Cover), Some(4.0));
+        assert_eq!(fit_scale(bounds, 0.0, None, Some(120), 10, Fit::Contain), Some(2.0));
+
+        // A quarter turn swaps which side limits the fit
+        assert_eq!(fit_scale(bounds, 0.0, Some(120), Some(420), 10, Fit::Contain), Some(1.0));
+        let turned = fit_scale(bounds, std::f32::consts::FRAC_PI_2, Some(120), Some(420), 10, Fit::Contain).unwrap();
+        assert!((turned - 2.0).abs() < 1e-4);
+
+        assert_eq!(fit_scale(bounds, 0.0, Some(20), None, 10, Fit::Contain), None);
+        assert_eq!(fit_scale(bounds, 0.0, None, None, 10, Fit::Contain), None);
+    }
+}
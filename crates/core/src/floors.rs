@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use crate::brick::Brick;
+use crate::util;
+
+// A level counts as a floor when its walkable area is at least this share of
+// the largest level's, so wall tops and furniture don't
+const MIN_FLOOR_SHARE: f32 = 0.1;
+
+/// Least height between floors unless told otherwise, in save units: four
+/// bricks, about a player's height
+pub const DEFAULT_HEADROOM: i32 = 48;
+
+/// One level of a build: a walkable floor and everything standing on it up
+/// to the next floor.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Storey {
+    /// Height of the walkable surface, in save units
+    pub floor: i32,
+    /// Height of the next storey's floor, if there is one
+    pub next: Option<i32>,
+    /// Walkable area at floor height, in square save units
+    pub area: u64,
+    /// Whether this is the lowest storey, which also holds everything below
+    /// its floor
+    pub lowest: bool,
+}
+
+impl Storey {
+    /// The top surfaces of the bricks drawn for this storey, low inclusive
+    /// and high exclusive, for
+    /// [`GeometryState::set_heights`](crate::save::GeometryState::set_heights):
+    /// the floor and walls standing on it, but not the next floor covering
+    /// them. The lowest storey reaches all the way down, so foundations and
+    /// basements under the first floor aren't left out of every storey.
+    pub fn heights(&self) -> (i32, i32) {
+        let low = if self.lowest { i32::MIN } else { self.floor };
+        (low, self.next.unwrap_or(i32::MAX))
+    }
+}
+
+/// Finds the storeys of a build from a histogram of top surface heights
+/// weighted by footprint area. The most extensive levels become floors, the
+/// largest first, skipping any within `headroom` save units of a floor
+/// already found. Returned lowest first.
+pub fn detect_storeys<'a>(bricks: impl Iterator<Item = &'a Brick>, headroom: i32) -> Vec<Storey> {
+    let mut levels: HashMap<i32, u64> = HashMap::new();
+    for brick in bricks {
+        let size = util::sizer(brick);
+        *levels.entry(util::top_surface(brick)).or_default() += 4 * size.0 as u64 * size.1 as u64;
+    }
+    let mut levels: Vec<(i32, u64)> = levels.into_iter().collect();
+    // Ties go to the lower level, so the result doesn't depend on hash order
+    levels.sort_unstable_by_key(|&(height, area)| (std::cmp::Reverse(area), height));
+    let Some(&(_, largest)) = levels.first() else {
+        return Vec::new();
+    };
+
+    let mut floors: Vec<(i32, u64)> = Vec::new();
+    for (height, area) in levels {
+        if (area as f32) < largest as f32 * MIN_FLOOR_SHARE {
+            break;
+        }
+        if floors.iter().all(|&(floor, _)| (floor - height).abs() >= headroom) {
+            floors.push((height, area));
+        }
+    }
+    floors.sort_unstable();
+    floors
+        .iter()
+        .enumerate()
+        .map(|(i, &(floor, area))| Storey {
+            floor,
+            next: floors.get(i + 1).map(|&(next, _)| next),
+            area,
+            lowest: i == 0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floors_are_the_widest_levels_a_storey_apart() {
+        // A baseplate, walls up to a second floor slab, walls up to a roof,
+        // a table on the ground floor and a footing under the baseplate
+        let bricks = [
+            Brick::sized((0, 0, 2), (100, 100, 2)),
+            Brick::sized((-95, 0, 52), (5, 100, 48)),
+            Brick::sized((95, 0, 52), (5, 100, 48)),
+            Brick::sized((0, 0, 102), (100, 100, 2)),
+            Brick::sized((-95, 0, 152), (5, 100, 48)),
+            Brick::sized((0, 0, 202), (100, 100, 2)),
+            Brick::sized((0, 0, 20), (10, 10, 2)),
+            Brick::sized((0, 0, -6), (10, 10, 6)),
+        ];
+        let storeys = detect_storeys(bricks.iter(), 48);
+        assert_eq!(
+            storeys,
+            vec![
+                Storey { floor: 4, next: Some(104), area: 40000, lowest: true },
+                Storey { floor: 104, next: Some(204), area: 40000, lowest: false },
+                Storey { floor: 204, next: None, area: 40000, lowest: false },
+            ]
+        );
+
+        // The ground floor holds its walls, table and footing but not the
+        // slab above
+        let shown = |storey: &Storey| -> Vec<i32> {
+            let (low, high) = storey.heights();
+            bricks.iter().map(util::top_surface).filter(|top| (low..high).contains(top)).collect()
+        };
+        assert_eq!(shown(&storeys[0]), vec![4, 100, 100, 22, 0]);
+        assert_eq!(shown(&storeys[1]), vec![104, 200]);
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+use serde_json::{json, Value};
+
+use crate::brick::Brick;
+use crate::bricks::{calculate_brick_vertices, is_full_rect};
+use crate::color::to_hex;
+use crate::markers::{Marker, MarkerLayers};
+use crate::merge::{merge_rects, Rect};
+use crate::save::SaveData;
+use crate::util;
+
+/// What a GeoJSON export contains besides brick footprints.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GeoJsonOptions {
+    /// Merge abutting rectangular bricks sharing color, height, owner and
+    /// material into larger rectangles
+    pub merge: bool,
+    /// Marker categories to write as Point features
+    pub markers: MarkerLayers,
+}
+
+/// Writes a save's visible brick footprints as a GeoJSON FeatureCollection
+/// in save units, followed by its markers as points. Features stream out as
+/// they're built, so memory stays bounded by the save rather than the
+/// output. Returns the number of features written.
+///
+/// Footprints carry `asset`, `color`, `z_bottom`, `z_top`, `owner` and
+/// `material` properties; `owner` is null for public bricks, and merged
+/// regions of mixed brick types have a null `asset`.
+pub fn export_geojson<W: Write>(body: &[u8], out: W, options: &GeoJsonOptions) -> Result<usize, String> {
+    let save = SaveData::load(body)?;
+    let owners = save.owners.iter().map(String::as_str).collect();
+    let mut footprints = Footprints::new(FeatureWriter::new(out)?, &save.brick_assets, owners, &save.materials, options.merge);
+    for brick in save.all_bricks() {
+        // Owner 0 is public; the rest index the owner list from 1
+        let owner = (brick.owner as usize).checked_sub(1);
+        footprints.add(brick, owner, Some(brick.material as usize))?;
+    }
+    let mut writer = footprints.finish()?;
+    write_markers(&mut writer, &save.markers, &options.markers)?;
+    writer.finish()
+}
+
+fn write_markers<W: Write>(writer: &mut FeatureWriter<W>, markers: &[Marker], layers: &MarkerLayers) -> Result<(), String> {
+    for marker in markers.iter().filter(|m| layers.shows(m.kind)) {
+        let (x, y, z) = marker.position;
+        writer.write(
+            json!({ "type": "Point", "coordinates": [x, y] }),
+            json!({ "marker": marker.kind.name(), "z": z }),
+        )?;
+    }
+    Ok(())
+}
+
+/// Streams features into a GeoJSON FeatureCollection, one per line.
+pub struct FeatureWriter<W: Write> {
+    out: W,
+    count: usize,
+}
+
+impl<W: Write> FeatureWriter<W> {
+    pub fn new(mut out: W) -> Result<FeatureWriter<W>, String> {
+        out.write_all(br#"{"type":"FeatureCollection","features":["#).map_err(write_error)?;
+        Ok(FeatureWriter { out, count: 0 })
+    }
+
+    /// Appends one feature with the given geometry and properties objects.
+    pub fn write(&mut self, geometry: Value, properties: Value) -> Result<(), String> {
+        let separator: &[u8] = if self.count == 0 { b"\n" } else { b",\n" };
+        self.out.write_all(separator).map_err(write_error)?;
+        let feature = json!({ "type": "Feature", "geometry": geometry, "properties": properties });
+        serde_json::to_writer(&mut self.out, &feature).map_err(|e| format!("Error writing GeoJSON: {}", e))?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Closes the collection and flushes. Returns the number of features.
+    pub fn finish(mut self) -> Result<usize, String> {
+        self.out.write_all(b"\n]}\n").map_err(write_error)?;
+        self.out.flush().map_err(write_error)?;
+        Ok(self.count)
+    }
+}
+
+fn write_error(e: std::io::Error) -> String {
+    format!("Error writing GeoJSON: {}", e)
+}
+
+// Attributes a merged region's bricks all share
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct RegionKey {
+    z_top: i32,
+    z_bottom: i32,
+    color: [u8; 4],
+    owner: Option<usize>,
+    material: Option<usize>,
+}
+
+struct Region {
+    rects: Vec<Rect>,
+    // None once bricks of different types have joined
+    asset: Option<u32>,
+}
+
+// Writes footprints as bricks are added; with merging on, rectangular bricks
+// are held back and written as merged regions at the end
+struct Footprints<'a, W: Write> {
+    writer: FeatureWriter<W>,
+    assets: &'a [String],
+    owners: Vec<&'a str>,
+    materials: &'a [String],
+    merge: bool,
+    regions: BTreeMap<RegionKey, Region>,
+}
+
+impl<'a, W: Write> Footprints<'a, W> {
+    fn new(
+        writer: FeatureWriter<W>,
+        assets: &'a [String],
+        owners: Vec<&'a str>,
+        materials: &'a [String],
+        merge: bool,
+    ) -> Footprints<'a, W> {
+        Footprints { writer, assets, owners, materials, merge, regions: BTreeMap::new() }
+    }
+
+    // `owner` and `material` index the name lists; out of range reads as
+    // unknown
+    fn add(&mut self, brick: &Brick, owner: Option<usize>, material: Option<usize>) -> Result<(), String> {
+        let asset = &self.assets[brick.asset_name_index as usize];
+        let size = util::sizer(brick);
+        let z_bottom = brick.position.2 - size.2 as i32;
+        let z_top = brick.position.2 + size.2 as i32;
+        let owner = owner.filter(|&i| i < self.owners.len());
+        let material = material.filter(|&i| i < self.materials.len());
+
+        if self.merge && is_full_rect(asset) {
+            if size.0 == 0 || size.1 == 0 {
+                return Ok(());
+            }
+            let rect = (
+                brick.position.0 - size.0 as i32,
+                brick.position.1 - size.1 as i32,
+                brick.position.0 + size.0 as i32,
+                brick.position.1 + size.1 as i32,
+            );
+            let key = RegionKey { z_top, z_bottom, color: brick.color, owner, material };
+            let region = self.regions.entry(key).or_insert_with(|| Region {
+                rects: Vec::new(),
+                asset: Some(brick.asset_name_index),
+            });
+            region.rects.push(rect);
+            if region.asset != Some(brick.asset_name_index) {
+                region.asset = None;
+            }
+            return Ok(());
+        }
+
+        let rings = footprint_rings(&calculate_brick_vertices(asset, brick));
+        if rings.is_empty() {
+            return Ok(());
+        }
+        let properties = self.properties(Some(asset), brick.color, (z_bottom, z_top), owner, material);
+        self.writer.write(polygons(&rings), properties)
+    }
+
+    fn finish(mut self) -> Result<FeatureWriter<W>, String> {
+        let regions = std::mem::take(&mut self.regions);
+        for (key, region) in regions {
+            let asset = region.asset.map(|i| self.assets[i as usize].as_str());
+            for (x1, y1, x2, y2) in merge_rects(&region.rects).rects {
+                let ring = vec![(x1 as f32, y1 as f32), (x2 as f32, y1 as f32), (x2 as f32, y2 as f32), (x1 as f32, y2 as f32)];
+                let properties = self.properties(asset, key.color, (key.z_bottom, key.z_top), key.owner, key.material);
+                self.writer.write(polygons(&[ring]), properties)?;
+            }
+        }
+        Ok(self.writer)
+    }
+
+    fn properties(&self, asset: Option<&str>, color: [u8; 4], z: (i32, i32), owner: Option<usize>, material: Option<usize>) -> Value {
+        json!({
+            "asset": asset,
+            "color": to_hex(color),
+            "z_bottom": z.0,
+            "z_top": z.1,
+            "owner": owner.map(|i| self.owners[i]),
+            "material": material.map(|i| &self.materials[i]),
+        })
+    }
+}
+
+// A Polygon for one ring, otherwise a MultiPolygon of single-ring polygons.
+// Rings are closed by repeating their first point, as GeoJSON requires.
+fn polygons(rings: &[Vec<(f32, f32)>]) -> Value {
+    let closed: Vec<Vec<[f32; 2]>> = rings.iter()
+        .map(|ring| ring.iter().chain(ring.first()).map(|&(x, y)| [x, y]).collect())
+        .collect();
+    if let [ring] = closed.as_slice() {
+        json!({ "type": "Polygon", "coordinates": [ring] })
+    } else {
+        let polygons: Vec<[&Vec<[f32; 2]>; 1]> = closed.iter().map(|ring| [ring]).collect();
+        json!({ "type": "MultiPolygon", "coordinates": polygons })
+    }
+}
+
+// Vertices closer than 1/WELD save units are one point. Circle fans end a
+// rounding error away from where they started.
+const WELD: f32 = 1024.0;
+
+// Outline rings of a triangle list (x, y pairs): the edges only one triangle
+// uses, chained end to end and wound counterclockwise. Shapes built from
+// pieces that meet at T-junctions don't chain cleanly and fall back to one
+// ring per triangle, which covers the same area.
+fn footprint_rings(vertices: &[f32]) -> Vec<Vec<(f32, f32)>> {
+    let mut points: Vec<(f32, f32)> = Vec::new();
+    let mut index_of: HashMap<(i64, i64), usize> = HashMap::new();
+    let mut triangles: Vec<[usize; 3]> = Vec::new();
+    for tri in vertices.chunks_exact(6) {
+        let mut ids = [0; 3];
+        for (id, p) in ids.iter_mut().zip(tri.chunks_exact(2)) {
+            let key = ((p[0] * WELD).round() as i64, (p[1] * WELD).round() as i64);
+            *id = *index_of.entry(key).or_insert_with(|| {
+                points.push((p[0], p[1]));
+                points.len() - 1
+            });
+        }
+        let area = signed_area(&[points[ids[0]], points[ids[1]], points[ids[2]]]);
+        if area > 0.0 {
+            triangles.push(ids);
+        } else if area < 0.0 {
+            triangles.push([ids[0], ids[2], ids[1]]);
+        }
+    }
+
+    let mut uses: HashMap<(usize, usize), u32> = HashMap::new();
+    for t in &triangles {
+        for (a, b) in [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])] {
+            *uses.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+    let per_triangle = || -> Vec<Vec<(f32, f32)>> {
+        triangles.iter().map(|t| t.iter().map(|&i| points[i]).collect()).collect()
+    };
+
+    // Boundary edges keep their triangle's counterclockwise direction
+    let mut next: BTreeMap<usize, usize> = BTreeMap::new();
+    for t in &triangles {
+        for (a, b) in [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])] {
+            if uses[&(a.min(b), a.max(b))] == 1 && next.insert(a, b).is_some() {
+                return per_triangle();
+            }
+        }
+    }
+
+    let mut rings = Vec::new();
+    while let Some((&start, _)) = next.first_key_value() {
+        let mut ring = Vec::new();
+        let mut at = start;
+        loop {
+            ring.push(points[at]);
+            match next.remove(&at) {
+                Some(to) if to == start => break,
+                Some(to) => at = to,
+                None => return per_triangle(),
+            }
+        }
+        rings.push(ring);
+    }
+    rings
+}
+
+// Shoelace formula; positive for counterclockwise
+fn signed_area(points: &[(f32, f32)]) -> f32 {
+    let mut sum = 0.0;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        sum += a.0 * b.1 - b.0 * a.1;
+    }
+    sum / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_and_fan_triangles_chain_into_one_counterclockwise_ring() {
+        // Two triangles of a square, wound opposite ways
+        let square = [0.0, 0.0, 0.0, 10.0, 10.0, 0.0, 10.0, 10.0, 10.0, 0.0, 0.0, 10.0];
+        let rings = footprint_rings(&square);
+        assert_eq!(rings.len(), 1);
+        assert_eq!(rings[0].len(), 4);
+        assert!(signed_area(&rings[0]) > 0.0);
+
+        // A hexagonal fan around its center
+        let mut fan = Vec::new();
+        for i in 0..6 {
+            let (a, b) = (i as f32 * std::f32::consts::TAU / 6.0, (i + 1) as f32 * std::f32::consts::TAU / 6.0);
+            fan.extend_from_slice(&[0.0, 0.0, a.cos(), a.sin(), b.cos(), b.sin()]);
+        }
+        let rings = footprint_rings(&fan);
+        assert_eq!(rings.len(), 1);
+        assert_eq!(rings[0].len(), 6);
+    }
+
+    #[test]
+    fn t_junctions_fall_back_to_triangles() {
+        // A square's left half as one rectangle, its right half as two, so
+        // the middle edge meets a vertex halfway along
+        let rect = |x1: f32, y1: f32, x2: f32, y2: f32| [x1, y1, x2, y1, x1, y2, x2, y1, x2, y2, x1, y2];
+        let vertices = [rect(0.0, 0.0, 5.0, 10.0), rect(5.0, 0.0, 10.0, 5.0), rect(5.0, 5.0, 10.0, 10.0)].concat();
+        let rings = footprint_rings(&vertices);
+        assert_eq!(rings.len(), 6);
+        assert!(rings.iter().all(|ring| ring.len() == 3 && signed_area(ring) > 0.0));
+    }
+
+    #[test]
+    fn feature_collection_is_valid_json() {
+        let mut out = Vec::new();
+        let mut writer = FeatureWriter::new(&mut out).unwrap();
+        writer.write(polygons(&[vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]]), json!({ "asset": "B_Plate" })).unwrap();
+        writer.write(json!({ "type": "Point", "coordinates": [3, 4] }), json!({ "marker": "spawn" })).unwrap();
+        assert_eq!(writer.finish().unwrap(), 2);
+
+        let parsed: Value = serde_json::from_slice(&out).unwrap();
+        let features = parsed["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        let ring = &features[0]["geometry"]["coordinates"][0];
+        assert_eq!(ring[0], ring[3]);
+    }
+}
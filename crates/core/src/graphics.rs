@@ -1,22 +1,72 @@
 use std::collections::HashMap;
 
-// Bytes per vertex: x (f32), y (f32), rgba (4 x u8)
-pub const VERTEX_STRIDE: i32 = 12;
+// Bytes per vertex: x (f32), y (f32), depth (f32), extrusion (2 x snorm16),
+// rgba (4 x u8)
+pub const VERTEX_STRIDE: i32 = 20;
+
+// Depth of top-down geometry: the far plane, where the depth test always
+// passes, so top-down maps keep drawing in batch order
+pub const FLAT_DEPTH: f32 = 1.0;
+
+// Extrusions are stored as snorm16 fractions of this many outline widths.
+// Outline strips along the diagonal of a thin wedge offset by roughly its
+// aspect ratio per unit width, so this bounds the slimmest wedge whose
+// outline scales exactly; slimmer ones are clamped.
+pub const EXTRUDE_RANGE: f32 = 64.0;
 
 // Appends a shape's triangle-list positions as indexed geometry, deduplicating
 // vertices within the shape by exact bit pattern. Vertices are interleaved as
-// x: f32, y: f32, rgba: 4 x u8 (12 bytes per vertex, matches VERTEX_STRIDE).
+// x: f32, y: f32, depth: f32, extrusion: 2 x snorm16, rgba: 4 x u8 (20 bytes
+// per vertex, matches VERTEX_STRIDE). Fills never extrude.
 pub fn push_shape(vertices: &mut Vec<u8>, indices: &mut Vec<u32>, positions: &[f32], color: [u8; 4]) {
     let mut index_of: HashMap<(u32, u32), u32> = HashMap::new();
     for pos in positions.chunks_exact(2) {
         let key = (pos[0].to_bits(), pos[1].to_bits());
         let index = *index_of.entry(key).or_insert_with(|| {
-            let index = (vertices.len() / VERTEX_STRIDE as usize) as u32;
-            vertices.extend_from_slice(&pos[0].to_le_bytes());
-            vertices.extend_from_slice(&pos[1].to_le_bytes());
-            vertices.extend_from_slice(&color);
-            index
+            push_vertex(vertices, (pos[0], pos[1]), FLAT_DEPTH, (0.0, 0.0), color)
         });
         indices.push(index);
     }
 }
+
+// Like push_shape, for triangle-list positions given as (x, y, depth)
+// triples, depth in 0.0..1.0 with nearer geometry lower
+pub fn push_projected(vertices: &mut Vec<u8>, indices: &mut Vec<u32>, positions: &[f32], color: [u8; 4]) {
+    let mut index_of: HashMap<(u32, u32, u32), u32> = HashMap::new();
+    for pos in positions.chunks_exact(3) {
+        let key = (pos[0].to_bits(), pos[1].to_bits(), pos[2].to_bits());
+        let index = *index_of.entry(key).or_insert_with(|| {
+            push_vertex(vertices, (pos[0], pos[1]), pos[2], (0.0, 0.0), color)
+        });
+        indices.push(index);
+    }
+}
+
+// Appends outline geometry whose width the renderer sets at draw time.
+// `thin` and `unit` are the same outline built zero and one save unit wide;
+// since outline positions are linear in width, their difference is how far
+// each vertex moves per unit of width.
+pub fn push_outline(vertices: &mut Vec<u8>, indices: &mut Vec<u32>, thin: &[f32], unit: &[f32], color: [u8; 4]) {
+    let mut index_of: HashMap<(u32, u32, u32, u32), u32> = HashMap::new();
+    for (pos, wide) in thin.chunks_exact(2).zip(unit.chunks_exact(2)) {
+        let extrude = (wide[0] - pos[0], wide[1] - pos[1]);
+        let key = (pos[0].to_bits(), pos[1].to_bits(), extrude.0.to_bits(), extrude.1.to_bits());
+        let index = *index_of.entry(key).or_insert_with(|| {
+            push_vertex(vertices, (pos[0], pos[1]), FLAT_DEPTH, extrude, color)
+        });
+        indices.push(index);
+    }
+}
+
+fn push_vertex(vertices: &mut Vec<u8>, pos: (f32, f32), depth: f32, extrude: (f32, f32), color: [u8; 4]) -> u32 {
+    let index = (vertices.len() / VERTEX_STRIDE as usize) as u32;
+    vertices.extend_from_slice(&pos.0.to_le_bytes());
+    vertices.extend_from_slice(&pos.1.to_le_bytes());
+    vertices.extend_from_slice(&depth.to_le_bytes());
+    for e in [extrude.0, extrude.1] {
+        let snorm = (e / EXTRUDE_RANGE).clamp(-1.0, 1.0) * i16::MAX as f32;
+        vertices.extend_from_slice(&(snorm.round() as i16).to_le_bytes());
+    }
+    vertices.extend_from_slice(&color);
+    index
+}
@@ -0,0 +1,224 @@
+use crate::brick::Brick;
+use crate::merge::Rect;
+use crate::overlay::ImageView;
+use crate::raster::Raster;
+use crate::util;
+use crate::SaveData;
+
+// The grid's longer side is capped at this many cells, coarsening them past
+// a pixel on huge images
+const MAX_GRID_DIM: f32 = 4096.0;
+
+/// Height of cells without bricks, which neither cast nor catch shading.
+/// Finite, so blending with a neighbor stays a number.
+pub const EMPTY: f32 = -1.0e30;
+
+/// The highest top surface over each cell of a grid laid across the ground,
+/// for shading the finished plan view from brick heights. One grid spans
+/// the whole image, so the shading has no seams where render tiles meet.
+/// Fields under a view sit on a lattice fixed in world space, so separate
+/// images at one scale, such as served map tiles, agree where they meet.
+pub struct HeightField {
+    /// Top-left corner, in absolute save units
+    pub min: (f32, f32),
+    /// Save units across a cell
+    pub cell: f32,
+    pub cols: usize,
+    pub rows: usize,
+    /// Row by row, [`EMPTY`] where no brick covers a cell's center
+    pub heights: Vec<f32>,
+}
+
+impl HeightField {
+    /// The field over `area` (absolute save units, x1, y1, x2, y2) in cells
+    /// `cell` save units square, from `bricks`.
+    pub fn new<'a>(area: (f32, f32, f32, f32), cell: f32, bricks: impl Iterator<Item = &'a Brick>) -> HeightField {
+        let cols = ((area.2 - area.0) / cell).ceil().max(1.0) as usize;
+        let rows = ((area.3 - area.1) / cell).ceil().max(1.0) as usize;
+
+        let mut heights = vec![EMPTY; cols * rows];
+        for brick in bricks {
+            let size = util::sizer(brick);
+            let p = brick.position;
+            let span = |center: i32, half: u32, min: f32, len: usize| {
+                let lo = ((center - half as i32) as f32 - min) / cell - 0.5;
+                let hi = ((center + half as i32) as f32 - min) / cell - 0.5;
+                let (lo, hi) = match lo.ceil() <= hi.floor() {
+                    true => (lo.ceil(), hi.floor()),
+                    // Smaller than a cell: the one under its middle
+                    false => {
+                        let mid = ((center as f32 - min) / cell).floor();
+                        (mid, mid)
+                    }
+                };
+                let lo = lo.max(0.0) as usize;
+                let hi = hi.min(len as f32 - 1.0);
+                (hi >= lo as f32).then_some((lo, hi as usize))
+            };
+            let (Some((c1, c2)), Some((r1, r2))) = (span(p.0, size.0, area.0, cols), span(p.1, size.1, area.1, rows)) else {
+                continue;
+            };
+            let top = util::top_surface(brick) as f32;
+            for r in r1..=r2 {
+                for height in &mut heights[r * cols + c1..=r * cols + c2] {
+                    *height = height.max(top);
+                }
+            }
+        }
+        HeightField { min: (area.0, area.1), cell, cols, rows, heights }
+    }
+
+    /// The field under the image `view` describes, grown by `grow` save
+    /// units past its left, top, right and bottom edges so shading from
+    /// beyond them reaches in, with cells about a pixel across. Only bricks
+    /// reaching into `region` (absolute save units) count when given. None
+    /// when the image misses the build.
+    pub fn under_view(
+        save: &SaveData,
+        view: &ImageView,
+        grow: (f32, f32, f32, f32),
+        region: Option<Rect>,
+    ) -> Option<HeightField> {
+        // Nothing lies past the build
+        let (x1, y1, x2, y2) = view.world_bounds();
+        let (cx, cy) = save.centroid;
+        let b = save.bounds;
+        let area = (
+            (x1 - grow.0).max((b.0 + cx) as f32),
+            (y1 - grow.1).max((b.1 + cy) as f32),
+            (x2 + grow.2).min((b.2 + cx) as f32),
+            (y2 + grow.3).min((b.3 + cy) as f32),
+        );
+        if area.2 <= area.0 || area.3 <= area.1 {
+            return None;
+        }
+
+        let longer = (area.2 - area.0).max(area.3 - area.1);
+        let cell = (1.0 / view.scale()).max(longer / MAX_GRID_DIM);
+        let area = (
+            (area.0 / cell).floor() * cell,
+            (area.1 / cell).floor() * cell,
+            (area.2 / cell).ceil() * cell,
+            (area.3 / cell).ceil() * cell,
+        );
+        let near = (area.0.floor() as i32, area.1.floor() as i32, area.2.ceil() as i32, area.3.ceil() as i32);
+        let bricks = save.bricks_near(near).filter(|brick| {
+            let size = util::sizer(brick);
+            let p = brick.position;
+            let footprint = (p.0 - size.0 as i32, p.1 - size.1 as i32, p.0 + size.0 as i32, p.1 + size.1 as i32);
+            region.is_none_or(|r| footprint.0 < r.2 && r.0 < footprint.2 && footprint.1 < r.3 && r.1 < footprint.3)
+        });
+        Some(HeightField::new(area, cell, bricks))
+    }
+
+    /// Index of the cell holding an absolute position, if any.
+    pub fn cell_at(&self, (x, y): (f32, f32)) -> Option<usize> {
+        let c = ((x - self.min.0) / self.cell).floor();
+        let r = ((y - self.min.1) / self.cell).floor();
+        if c < 0.0 || r < 0.0 || c >= self.cols as f32 || r >= self.rows as f32 {
+            return None;
+        }
+        Some(r as usize * self.cols + c as usize)
+    }
+
+    /// `values` (one per cell) at an absolute position, blended between the
+    /// centers of the four nearest cells so coarse cells don't show as
+    /// blocks. Only cells with bricks blend, and none reach past the bricks'
+    /// edges.
+    pub fn sample(&self, values: &[f32], (x, y): (f32, f32)) -> f32 {
+        let Some(own) = self.cell_at((x, y)) else {
+            return 0.0;
+        };
+        if self.heights[own] <= EMPTY {
+            return 0.0;
+        }
+        let fc = (x - self.min.0) / self.cell - 0.5;
+        let fr = (y - self.min.1) / self.cell - 0.5;
+        let (c0, r0) = (fc.floor(), fr.floor());
+        let (tc, tr) = (fc - c0, fr - r0);
+        let (mut sum, mut weight) = (0.0, 0.0);
+        for (dc, wc) in [(0.0, 1.0 - tc), (1.0, tc)] {
+            for (dr, wr) in [(0.0, 1.0 - tr), (1.0, tr)] {
+                let (c, r) = (c0 + dc, r0 + dr);
+                if c < 0.0 || r < 0.0 || c >= self.cols as f32 || r >= self.rows as f32 {
+                    continue;
+                }
+                let i = r as usize * self.cols + c as usize;
+                if self.heights[i] > EMPTY {
+                    sum += values[i] * wc * wr;
+                    weight += wc * wr;
+                }
+            }
+        }
+        match weight > 0.0 {
+            true => sum / weight,
+            false => values[own],
+        }
+    }
+
+    /// Darkens `raster`, which holds the image `view` describes, by `shade`
+    /// (0.0..=1.0 per cell) times `darkness`.
+    pub fn darken(&self, raster: &mut Raster, view: &ImageView, shade: &[f32], darkness: f32) {
+        // The view is affine, so pixel centers step evenly across the world
+        let origin = view.to_world((0.5, 0.5));
+        let right = view.to_world((1.5, 0.5));
+        let down = view.to_world((0.5, 1.5));
+        let across = (right.0 - origin.0, right.1 - origin.1);
+        let along = (down.0 - origin.0, down.1 - origin.1);
+        for py in 0..raster.height() {
+            for px in 0..raster.width() {
+                let (fx, fy) = (px as f32, py as f32);
+                let world = (
+                    origin.0 + fx * across.0 + fy * along.0,
+                    origin.1 + fx * across.1 + fy * along.1,
+                );
+                let amount = self.sample(shade, world);
+                if amount > 0.0 {
+                    raster.shade(px as i32, py as i32, 1.0 - darkness * amount);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_blend_between_cells_with_bricks() {
+        // Two cells of a 3x1 field are covered, the last is empty
+        let floor = Brick::sized((10, 5, 2), (10, 5, 2));
+        let field = HeightField::new((0.0, 0.0, 30.0, 10.0), 10.0, [floor].iter());
+        let values = [0.0, 1.0, 1.0];
+
+        assert_eq!(field.sample(&values, (5.0, 5.0)), 0.0);
+        assert_eq!(field.sample(&values, (10.0, 5.0)), 0.5);
+        assert_eq!(field.sample(&values, (12.5, 2.0)), 0.75);
+        // The empty cell neither catches shading nor blends into its
+        // neighbor
+        assert_eq!(field.sample(&values, (25.0, 5.0)), 0.0);
+        assert_eq!(field.sample(&values, (19.0, 5.0)), 1.0);
+        assert_eq!(field.sample(&values, (40.0, 5.0)), 0.0);
+    }
+
+    #[test]
+    fn neighboring_tiles_share_one_lattice() {
+        let mut save = SaveData::new(String::new(), 2, (0, 0), (0, 0, 400, 200));
+        save.push_chunk(0, vec![Brick::sized((200, 100, 2), (200, 100, 2)), Brick::sized((210, 100, 20), (10, 10, 20))]);
+        // Two 200 unit tiles side by side at half a pixel per unit, their
+        // fields grown by a distance that isn't a whole number of cells
+        let tile = |center: f32| ImageView { width: 100, height: 100, matrix: save.view_matrix(100.0, 100.0, -center, -100.0, 0.5, 0.0) };
+        let grow = (25.0, 25.0, 25.0, 25.0);
+        let west = HeightField::under_view(&save, &tile(100.0), grow, None).unwrap();
+        let east = HeightField::under_view(&save, &tile(300.0), grow, None).unwrap();
+
+        assert_eq!(west.cell, east.cell);
+        assert_eq!(((east.min.0 - west.min.0) / west.cell).fract(), 0.0);
+        // The tower by the seam stands in both, in the same cells
+        let tower = (205.0, 99.0);
+        let height = |field: &HeightField| field.heights[field.cell_at(tower).unwrap()];
+        assert_eq!(height(&west), 40.0);
+        assert_eq!(height(&east), 40.0);
+    }
+}
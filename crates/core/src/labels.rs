@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use brickadia::save::UnrealType;
+
+use crate::overlay::ImageView;
+use crate::raster::{Font, Raster};
+
+// Brick component properties holding player-facing text: the message an
+// interactable shows, and the text of sign-like components
+const TEXT_PROPERTIES: [&str; 2] = ["Message", "Text"];
+
+// Longer texts are cut short with an ellipsis
+const MAX_LABEL_CHARS: usize = 40;
+
+const SIZE: f32 = 14.0;
+const DOT_RADIUS: f32 = 2.5;
+// Gap between a label's anchor dot and its text
+const OFFSET: f32 = 5.0;
+// Clearance kept around each placed label
+const PADDING: f32 = 2.0;
+// Spatial hash cell for collision checks, in pixels
+const CELL: f32 = 64.0;
+
+const INK: [u8; 4] = [24, 24, 24, 255];
+const HALO: [u8; 4] = [255, 255, 255, 230];
+
+/// Text carried by a brick's components, at the brick's position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub position: (i32, i32, i32),
+    pub text: String,
+}
+
+/// The first piece of display text among a brick's components, cleaned up
+/// for a map label: markup stripped, first line only, shortened.
+pub fn component_text(components: &HashMap<String, HashMap<String, UnrealType>>) -> Option<String> {
+    // Sorted so bricks with several text components label deterministically
+    let mut names: Vec<&String> = components.keys().collect();
+    names.sort();
+    names.into_iter().find_map(|name| {
+        let properties = &components[name];
+        TEXT_PROPERTIES.iter().find_map(|property| match properties.get(*property) {
+            Some(UnrealType::String(text)) => clean_text(text),
+            _ => None,
+        })
+    })
+}
+
+// Strips Brickadia's rich text tags (<b>, <color="...">, </>), keeps the
+// first non-blank line and collapses whitespace
+fn clean_text(text: &str) -> Option<String> {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    let line = plain.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let line = words.join(" ");
+    if line.chars().count() > MAX_LABEL_CHARS {
+        let short: String = line.chars().take(MAX_LABEL_CHARS - 1).collect();
+        Some(format!("{}…", short.trim_end()))
+    } else {
+        Some(line)
+    }
+}
+
+// Placed label and dot boxes as (x1, y1, x2, y2), bucketed by the cells
+// they touch
+#[derive(Default)]
+struct Occupancy {
+    boxes: Vec<(f32, f32, f32, f32)>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl Occupancy {
+    fn cells_of(b: (f32, f32, f32, f32)) -> impl Iterator<Item = (i32, i32)> {
+        let (c1, r1) = ((b.0 / CELL).floor() as i32, (b.1 / CELL).floor() as i32);
+        let (c2, r2) = ((b.2 / CELL).floor() as i32, (b.3 / CELL).floor() as i32);
+        (r1..=r2).flat_map(move |r| (c1..=c2).map(move |c| (c, r)))
+    }
+
+    fn is_free(&self, b: (f32, f32, f32, f32)) -> bool {
+        Self::cells_of(b).all(|cell| {
+            self.cells.get(&cell).is_none_or(|indices| {
+                indices.iter().all(|&i| {
+                    let o = self.boxes[i];
+                    b.2 <= o.0 || o.2 <= b.0 || b.3 <= o.1 || o.3 <= b.1
+                })
+            })
+        })
+    }
+
+    fn insert(&mut self, b: (f32, f32, f32, f32)) {
+        let index = self.boxes.len();
+        self.boxes.push(b);
+        for cell in Self::cells_of(b) {
+            self.cells.entry(cell).or_default().push(index);
+        }
+    }
+}
+
+/// Draws labels at their bricks' positions. Each takes the first of four
+/// spots around its anchor (above, below, right, left) that stays inside the
+/// image and clear of labels already placed; labels with no free spot are
+/// left out. Higher bricks place first, so rooftop signs win over whatever
+/// is beneath them. `avoid` holds pixel boxes already drawn over, such as
+/// marker icons.
+pub fn draw_labels(raster: &mut Raster, view: &ImageView, font: &Font, labels: &[Label], avoid: &[(f32, f32, f32, f32)]) {
+    let (w, h) = (view.width as f32, view.height as f32);
+
+    let mut order: Vec<&Label> = labels.iter().collect();
+    order.sort_by(|a, b| b.position.2.cmp(&a.position.2).then_with(|| a.text.cmp(&b.text)));
+
+    let mut placed = Occupancy::default();
+    for &b in avoid {
+        placed.insert(b);
+    }
+    for label in order {
+        let anchor = view.to_pixel((label.position.0 as f32, label.position.1 as f32));
+        if anchor.0 < 0.0 || anchor.1 < 0.0 || anchor.0 >= w || anchor.1 >= h {
+            continue;
+        }
+        let dot = (
+            anchor.0 - DOT_RADIUS,
+            anchor.1 - DOT_RADIUS,
+            anchor.0 + DOT_RADIUS,
+            anchor.1 + DOT_RADIUS,
+        );
+        let (tw, th) = font.measure(&label.text, SIZE);
+        let candidates = [
+            (anchor.0 - tw / 2.0, anchor.1 - OFFSET - th),
+            (anchor.0 - tw / 2.0, anchor.1 + OFFSET),
+            (anchor.0 + OFFSET + DOT_RADIUS, anchor.1 - th / 2.0),
+            (anchor.0 - OFFSET - DOT_RADIUS - tw, anchor.1 - th / 2.0),
+        ];
+        let spot = candidates.into_iter().find(|&(x, y)| {
+            let text_box = (x - PADDING, y - PADDING, x + tw + PADDING, y + th + PADDING);
+            text_box.0 >= 0.0
+                && text_box.1 >= 0.0
+                && text_box.2 <= w
+                && text_box.3 <= h
+                && placed.is_free(text_box)
+                && placed.is_free(dot)
+        });
+        let Some((x, y)) = spot else {
+            continue;
+        };
+        placed.insert((x - PADDING, y - PADDING, x + tw + PADDING, y + th + PADDING));
+        placed.insert(dot);
+
+        raster.fill_circle(anchor, DOT_RADIUS + 1.0, HALO);
+        raster.fill_circle(anchor, DOT_RADIUS, INK);
+        font.draw_haloed(raster, &label.text, SIZE, (x, y), INK, HALO);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleans_rich_text_messages() {
+        assert_eq!(clean_text("<b>Joe's</b>  <color=\"ff0000\">Diner</>"), Some("Joe's Diner".to_string()));
+        assert_eq!(clean_text("\n  \nMain St\nsecond line"), Some("Main St".to_string()));
+        assert_eq!(clean_text("<emoji>wave</>"), Some("wave".to_string()));
+        assert_eq!(clean_text("   "), None);
+        let long = clean_text(&"a".repeat(100)).unwrap();
+        assert_eq!(long.chars().count(), MAX_LABEL_CHARS);
+        assert!(long.ends_with('…'));
+    }
+
+    #[test]
+    fn occupancy_rejects_overlaps_across_cells() {
+        let mut placed = Occupancy::default();
+        placed.insert((50.0, 50.0, 150.0, 70.0));
+        assert!(!placed.is_free((140.0, 60.0, 200.0, 80.0)));
+        assert!(placed.is_free((150.0, 60.0, 200.0, 80.0)));
+        assert!(placed.is_free((0.0, 0.0, 40.0, 40.0)));
+    }
+}
@@ -0,0 +1,55 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use brickadia::read::SaveReader;
+use brickadia::save::SaveData;
+use brickadia::write::SaveWriter;
+
+use crate::save::SaveFormat;
+use crate::{world_load, world_write};
+
+/// A save read at full .brs fidelity, for writing back out.
+pub struct LegacySave {
+    pub save: SaveData,
+    /// What the source held that didn't carry over
+    pub lost: Vec<String>,
+}
+
+/// Reads any supported save format into brickadia's full save structure,
+/// unlike [`SaveData::load`](crate::SaveData::load) which keeps only what
+/// rendering needs. .brs saves come through whole; worlds lose what
+/// [`world_load::read_legacy_brz`] describes, listed in `lost`.
+pub fn read_legacy(body: &[u8]) -> Result<LegacySave, String> {
+    match SaveFormat::detect(body) {
+        SaveFormat::Brs => {
+            let mut reader = SaveReader::new(body)
+                .map_err(|_| "brickadia-rs error creating save reader".to_string())?;
+            let save = reader.read_all()
+                .map_err(|_| "brickadia-rs error reading file".to_string())?;
+            Ok(LegacySave { save, lost: Vec::new() })
+        }
+        SaveFormat::Brz => world_load::read_legacy_brz(body),
+        SaveFormat::Brdb => world_load::read_legacy_brdb(body),
+    }
+}
+
+/// Writes a save as .brs.
+pub fn write_brs<W: Write>(save: SaveData, out: W) -> Result<(), String> {
+    SaveWriter::new(out, save).write()
+        .map_err(|e| format!("brickadia-rs error writing save: {}", e))
+}
+
+/// Writes a save in `format` at `path`. Returns kinds of data the save
+/// held that the format can't, for worlds per [`world_write::write_world`].
+pub fn write_save(save: SaveData, format: SaveFormat, path: &Path) -> Result<Vec<String>, String> {
+    match format {
+        SaveFormat::Brs => {
+            let file = File::create(path)
+                .map_err(|e| format!("Error creating {}: {}", path.display(), e))?;
+            write_brs(save, BufWriter::new(file))?;
+            Ok(Vec::new())
+        }
+        SaveFormat::Brz | SaveFormat::Brdb => world_write::write_world(&save, format, path),
+    }
+}
@@ -1,13 +1,31 @@
 pub mod brick;
 pub mod bricks;
 pub mod color;
+pub mod crop;
+pub mod fit;
+pub mod floors;
+pub mod geojson;
 pub mod graphics;
+pub mod heightfield;
+pub mod labels;
+pub mod legacy;
 pub mod m3;
+pub mod markers;
+pub mod merge;
+pub mod occlusion;
+pub mod overlay;
+pub mod plan;
+pub mod projection;
+pub mod raster;
 pub mod render;
 pub mod save;
+pub mod shadow;
 pub mod stitcher;
+pub mod structures;
+pub mod tiles;
 pub mod util;
 pub mod world_load;
+pub mod world_write;
 
 pub use render::Renderer;
 pub use save::SaveData;
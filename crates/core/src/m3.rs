@@ -77,95 +77,25 @@ pub fn multiply(a: [f32; 9], b: [f32; 9]) -> [f32; 9] {
     ]
 }
 
-/*
-pub fn divide(m: [f32;9], x: f32) -> [f32;9] {
+/// Inverse of an affine 2d transform (the only kind this module builds), or
+/// the identity if it is singular.
+pub fn inverse(m: [f32; 9]) -> [f32; 9] {
+    let det = m[0] * (m[4] * m[8] - m[7] * m[5])
+        - m[3] * (m[1] * m[8] - m[7] * m[2])
+        + m[6] * (m[1] * m[5] - m[4] * m[2]);
+    if det == 0.0 {
+        return translation(0.0, 0.0);
+    }
+    // Transposed cofactors (the adjugate) over the determinant
     [
-        m[0]/x, m[1]/x, m[2]/x,
-        m[3]/x, m[4]/x, m[5]/x,
-        m[6]/x, m[7]/x, m[8]/x,
+        (m[4] * m[8] - m[5] * m[7]) / det,
+        (m[2] * m[7] - m[1] * m[8]) / det,
+        (m[1] * m[5] - m[2] * m[4]) / det,
+        (m[5] * m[6] - m[3] * m[8]) / det,
+        (m[0] * m[8] - m[2] * m[6]) / det,
+        (m[2] * m[3] - m[0] * m[5]) / det,
+        (m[3] * m[7] - m[4] * m[6]) / det,
+        (m[1] * m[6] - m[0] * m[7]) / det,
+        (m[0] * m[4] - m[1] * m[3]) / det,
     ]
 }
-
-pub fn inverse(m: [f32;9]) -> [f32;9] {
-    let det = determinant(m);
-    divide(transpose(cofactor(m)), det)
-}
-
-pub fn determinant(m: [f32;9]) -> f32 {
-    let a = m[0];
-    let b = m[1];
-    let c = m[2];
-    let d = m[3];
-    let e = m[4];
-    let f = m[5];
-    let g = m[6];
-    let h = m[7];
-    let i = m[8];
-    a*(e*i - f*h) - b*(d*i - f*g) + c*(d*h - e*g)
-}
-
-pub fn transpose(m: [f32;9]) -> [f32;9] {
-    [
-        m[0], m[3], m[6],
-        m[1], m[4], m[7],
-        m[2], m[5], m[8],
-    ]
-}
-
-pub fn cofactor(m: [f32;9]) -> [f32;9] {
-    let m00 = m[0];
-    let m01 = m[1];
-    let m02 = m[2];
-    let m10 = m[3];
-    let m11 = m[4];
-    let m12 = m[5];
-    let m20 = m[6];
-    let m21 = m[7];
-    let m22 = m[8];
-    let a00 = det2d([
-        m11, m12,
-        m21, m22,
-    ]);
-    let a01 = -det2d([
-        m10, m12,
-        m20, m22,
-    ]);
-    let a02 = det2d([
-        m21, m11,
-        m20, m21,
-    ]);
-    let a10 = -det2d([
-        m01, m02,
-        m21, m22,
-    ]);
-    let a11 = det2d([
-        m00, m02,
-        m20, m22,
-    ]);
-    let a12 = -det2d([
-        m00, m01,
-        m20, m21,
-    ]);
-    let a20 = det2d([
-        m01, m02,
-        m11, m12,
-    ]);
-    let a21 = -det2d([
-        m00, m02,
-        m10, m12,
-    ]);
-    let a22 = det2d([
-        m00, m01,
-        m10, m11,
-    ]);
-    [
-        a00, a01, a02,
-        a10, a11, a12,
-        a20, a21, a22,
-    ]
-}
-
-fn det2d(m: [f32;4]) -> f32 {
-    m[0]*m[3] - m[1]*m[2]
-}
-*/
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use brickadia::save::UnrealType;
+
+use crate::overlay::ImageView;
+use crate::raster::{Font, Raster};
+
+// Brick types players spawn at
+const SPAWN_ASSETS: [&str; 2] = ["B_SpawnPoint", "B_CheckPoint"];
+
+const RADIUS: f32 = 8.0;
+const GLYPH_SIZE: f32 = 11.0;
+const RIM: [u8; 4] = [255, 255, 255, 255];
+const SHADOW: [u8; 4] = [0, 0, 0, 96];
+
+/// Categories of point of interest found in saves.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum MarkerKind {
+    /// Spawn points and checkpoints
+    Spawn,
+    /// Point and spot lights
+    Light,
+    /// Audio emitters
+    Audio,
+    /// Bricks players can interact with
+    Interact,
+}
+
+impl MarkerKind {
+    pub const ALL: [MarkerKind; 4] = [MarkerKind::Spawn, MarkerKind::Light, MarkerKind::Audio, MarkerKind::Interact];
+
+    /// Kind for a brick of this type, if the type itself is a marker.
+    pub fn of_asset(asset: &str) -> Option<MarkerKind> {
+        SPAWN_ASSETS.contains(&asset).then_some(MarkerKind::Spawn)
+    }
+
+    /// Kind for a brick component, by its name: BCD_PointLight and so on
+    /// in .brs saves, Component_Internal_PointLight in worlds.
+    pub fn of_component(component: &str) -> Option<MarkerKind> {
+        let name = component.strip_prefix("BCD_").or_else(|| component.strip_prefix("Component_Internal_"))?;
+        match name {
+            "PointLight" | "SpotLight" => Some(MarkerKind::Light),
+            "AudioEmitter" => Some(MarkerKind::Audio),
+            "Interact" => Some(MarkerKind::Interact),
+            _ => None,
+        }
+    }
+
+    /// Lowercase name, as accepted by [`FromStr`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            MarkerKind::Spawn => "spawn",
+            MarkerKind::Light => "light",
+            MarkerKind::Audio => "audio",
+            MarkerKind::Interact => "interact",
+        }
+    }
+
+    fn icon(&self) -> (&'static str, [u8; 4]) {
+        match self {
+            MarkerKind::Spawn => ("S", [46, 160, 67, 255]),
+            MarkerKind::Light => ("L", [230, 170, 20, 255]),
+            MarkerKind::Audio => ("♪", [137, 87, 229, 255]),
+            MarkerKind::Interact => ("!", [31, 111, 235, 255]),
+        }
+    }
+}
+
+impl FromStr for MarkerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<MarkerKind, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "spawn" | "spawns" => Ok(MarkerKind::Spawn),
+            "light" | "lights" => Ok(MarkerKind::Light),
+            "audio" => Ok(MarkerKind::Audio),
+            "interact" | "interacts" => Ok(MarkerKind::Interact),
+            _ => Err(format!("unknown marker kind '{}', expected spawn, light, audio or interact", s)),
+        }
+    }
+}
+
+/// A point of interest at a brick's position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Marker {
+    pub kind: MarkerKind,
+    pub position: (i32, i32, i32),
+}
+
+/// Markers for one brick: its own type, then each recognized component. A
+/// lit interactable yields both a light and an interact marker.
+pub fn brick_markers(
+    asset: &str,
+    components: &HashMap<String, HashMap<String, UnrealType>>,
+    position: (i32, i32, i32),
+) -> Vec<Marker> {
+    let mut kinds: Vec<MarkerKind> = MarkerKind::of_asset(asset).into_iter().collect();
+    for name in components.keys() {
+        if let Some(kind) = MarkerKind::of_component(name) {
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+    }
+    kinds.sort_by_key(|kind| MarkerKind::ALL.iter().position(|k| k == kind));
+    kinds.into_iter().map(|kind| Marker { kind, position }).collect()
+}
+
+/// Which marker categories to draw. Parses from a comma-separated list of
+/// kinds, or `all`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct MarkerLayers {
+    pub spawns: bool,
+    pub lights: bool,
+    pub audio: bool,
+    pub interact: bool,
+}
+
+impl MarkerLayers {
+    pub const ALL: MarkerLayers = MarkerLayers { spawns: true, lights: true, audio: true, interact: true };
+
+    pub fn shows(&self, kind: MarkerKind) -> bool {
+        match kind {
+            MarkerKind::Spawn => self.spawns,
+            MarkerKind::Light => self.lights,
+            MarkerKind::Audio => self.audio,
+            MarkerKind::Interact => self.interact,
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        MarkerKind::ALL.iter().any(|&kind| self.shows(kind))
+    }
+
+    fn set(&mut self, kind: MarkerKind) {
+        match kind {
+            MarkerKind::Spawn => self.spawns = true,
+            MarkerKind::Light => self.lights = true,
+            MarkerKind::Audio => self.audio = true,
+            MarkerKind::Interact => self.interact = true,
+        }
+    }
+}
+
+impl FromStr for MarkerLayers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<MarkerLayers, String> {
+        let mut layers = MarkerLayers::default();
+        for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            if part.eq_ignore_ascii_case("all") {
+                layers = MarkerLayers::ALL;
+            } else {
+                layers.set(part.parse()?);
+            }
+        }
+        Ok(layers)
+    }
+}
+
+/// Draws the markers in the enabled categories as round icons at fixed
+/// pixel size, and returns the pixel boxes they cover for later layers to
+/// avoid. Markers past the image edges are skipped.
+pub fn draw_markers(
+    raster: &mut Raster,
+    view: &ImageView,
+    font: &Font,
+    markers: &[Marker],
+    layers: &MarkerLayers,
+) -> Vec<(f32, f32, f32, f32)> {
+    let (w, h) = (view.width as f32, view.height as f32);
+    let mut drawn = Vec::new();
+    for marker in markers.iter().filter(|m| layers.shows(m.kind)) {
+        let center = view.to_pixel((marker.position.0 as f32, marker.position.1 as f32));
+        if center.0 < -RADIUS || center.1 < -RADIUS || center.0 > w + RADIUS || center.1 > h + RADIUS {
+            continue;
+        }
+        let (glyph, color) = marker.kind.icon();
+        raster.fill_circle((center.0 + 1.0, center.1 + 1.5), RADIUS + 1.5, SHADOW);
+        raster.fill_circle(center, RADIUS + 1.5, RIM);
+        raster.fill_circle(center, RADIUS, color);
+        let (gw, gh) = font.measure(glyph, GLYPH_SIZE);
+        font.draw(raster, glyph, GLYPH_SIZE, (center.0 - gw / 2.0, center.1 - gh / 2.0), RIM);
+        let reach = RADIUS + 1.5;
+        drawn.push((center.0 - reach, center.1 - reach, center.0 + reach, center.1 + reach));
+    }
+    drawn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_marker_layers() {
+        assert_eq!("all".parse::<MarkerLayers>(), Ok(MarkerLayers::ALL));
+        assert_eq!(
+            "spawn, lights".parse::<MarkerLayers>(),
+            Ok(MarkerLayers { spawns: true, lights: true, audio: false, interact: false })
+        );
+        assert!("spawn,teapots".parse::<MarkerLayers>().is_err());
+    }
+
+    #[test]
+    fn brick_yields_one_marker_per_kind() {
+        let mut components = HashMap::new();
+        components.insert("BCD_Interact".to_string(), HashMap::new());
+        components.insert("BCD_PointLight".to_string(), HashMap::new());
+        components.insert("BCD_Unrelated".to_string(), HashMap::new());
+        let kinds: Vec<MarkerKind> = brick_markers("B_SpawnPoint", &components, (0, 0, 0))
+            .into_iter()
+            .map(|m| m.kind)
+            .collect();
+        assert_eq!(kinds, vec![MarkerKind::Spawn, MarkerKind::Light, MarkerKind::Interact]);
+    }
+
+    #[test]
+    fn world_components_mark_like_brs_ones() {
+        assert_eq!(MarkerKind::of_component("Component_Internal_SpotLight"), Some(MarkerKind::Light));
+        assert_eq!(MarkerKind::of_component("Component_Internal_AudioEmitter"), Some(MarkerKind::Audio));
+        assert_eq!(MarkerKind::of_component("BCD_AudioEmitter"), Some(MarkerKind::Audio));
+        assert_eq!(MarkerKind::of_component("Component_Internal_Sign"), None);
+        assert_eq!(MarkerKind::of_component("PointLight"), None);
+    }
+}
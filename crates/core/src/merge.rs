@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+// Components whose compressed grid would exceed this many cells are left
+// unmerged. Only pathological layouts (long diagonal staircases of
+// corner-touching bricks) get near it; ordinary floors and walls compress to
+// roughly one cell per brick.
+const MAX_COMPONENT_CELLS: usize = 1 << 22;
+
+/// Axis-aligned rectangle in save units as (x1, y1, x2, y2), x1 < x2, y1 < y2.
+pub type Rect = (i32, i32, i32, i32);
+
+/// A straight run of a merged region's boundary. `inward` is the unit
+/// direction pointing into the region, which is the side an outline strip
+/// along this edge is drawn on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Edge {
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+    pub inward: (i32, i32),
+}
+
+/// The result of merging one set of same-colored rectangles: maximal
+/// rectangles covering exactly the same area, and that area's boundary.
+#[derive(Debug, Default)]
+pub struct Merged {
+    pub rects: Vec<Rect>,
+    pub boundary: Vec<Edge>,
+}
+
+/// Greedily merges rectangles into larger ones covering the same area.
+///
+/// Rectangles are grouped into components by touching anywhere along their
+/// sides, so staggered rows of a running bond or a 1x2 beside the middle of
+/// a 1x4 join one region, and walls meeting in a T share a single outline
+/// rather than crossing at the junction. Each component is rasterized onto a
+/// grid compressed to its distinct edge coordinates, then swept row-major:
+/// each free cell grows as far right as it can, then as far down as the
+/// whole span allows. Not optimal, but close on the grid-aligned layouts
+/// builds are made of, and linear in the component's cell count.
+pub fn merge_rects(rects: &[Rect]) -> Merged {
+    let mut merged = Merged::default();
+    for component in touching_components(rects) {
+        let component: Vec<Rect> = component.into_iter().map(|i| rects[i]).collect();
+        match CompressedGrid::new(&component) {
+            Some(grid) => {
+                merged.rects.extend(grid.greedy_rects());
+                merged.boundary.extend(grid.boundary());
+            }
+            None => {
+                for &rect in &component {
+                    merged.rects.push(rect);
+                    merged.boundary.extend(rect_boundary(rect));
+                }
+            }
+        }
+    }
+    merged
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+// Groups rectangle indices by touching or overlapping
+fn touching_components(rects: &[Rect]) -> Vec<Vec<usize>> {
+    let boxes: Vec<([i32; 2], [i32; 2])> = rects.iter().map(|&(x1, y1, x2, y2)| ([x1, y1], [x2, y2])).collect();
+    touching_groups(&boxes)
+}
+
+/// Groups boxes, given as their low and high corners, by touching or
+/// overlapping along every axis (union-find). Candidates are found through
+/// buckets of the plane of the first two axes. Returns lists of indices into
+/// `boxes`, ordered by each group's first box.
+pub fn touching_groups<const N: usize>(boxes: &[([i32; N], [i32; N])]) -> Vec<Vec<usize>> {
+    const BUCKET: i32 = 80;
+    let mut parent: Vec<usize> = (0..boxes.len()).collect();
+    let mut buckets: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+    for (i, (lo, hi)) in boxes.iter().enumerate() {
+        for bx in lo[0].div_euclid(BUCKET)..=hi[0].div_euclid(BUCKET) {
+            for by in lo[1].div_euclid(BUCKET)..=hi[1].div_euclid(BUCKET) {
+                let bucket = buckets.entry((bx, by)).or_default();
+                for &j in bucket.iter() {
+                    let (other_lo, other_hi) = &boxes[j];
+                    if (0..N).all(|k| lo[k] <= other_hi[k] && other_lo[k] <= hi[k]) {
+                        let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                        parent[a] = b;
+                    }
+                }
+                bucket.push(i);
+            }
+        }
+    }
+    groups(&mut parent)
+}
+
+fn groups(parent: &mut [usize]) -> Vec<Vec<usize>> {
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..parent.len() {
+        let root = find(parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+    // Deterministic output order: by each component's first rectangle
+    let mut groups: Vec<Vec<usize>> = groups.into_values().collect();
+    groups.sort_unstable_by_key(|g| g[0]);
+    groups
+}
+
+fn rect_boundary((x1, y1, x2, y2): Rect) -> [Edge; 4] {
+    [
+        Edge { from: (x1, y1), to: (x2, y1), inward: (0, 1) },
+        Edge { from: (x1, y2), to: (x2, y2), inward: (0, -1) },
+        Edge { from: (x1, y1), to: (x1, y2), inward: (1, 0) },
+        Edge { from: (x2, y1), to: (x2, y2), inward: (-1, 0) },
+    ]
+}
+
+// Occupancy over a component, with columns and rows at its distinct x and y
+// edge coordinates
+struct CompressedGrid {
+    xs: Vec<i32>,
+    ys: Vec<i32>,
+    cols: usize,
+    rows: usize,
+    occupied: Vec<bool>,
+}
+
+impl CompressedGrid {
+    fn new(rects: &[Rect]) -> Option<CompressedGrid> {
+        let mut xs: Vec<i32> = rects.iter().flat_map(|r| [r.0, r.2]).collect();
+        let mut ys: Vec<i32> = rects.iter().flat_map(|r| [r.1, r.3]).collect();
+        xs.sort_unstable();
+        xs.dedup();
+        ys.sort_unstable();
+        ys.dedup();
+        let cols = xs.len() - 1;
+        let rows = ys.len() - 1;
+        if cols * rows > MAX_COMPONENT_CELLS {
+            return None;
+        }
+
+        let mut occupied = vec![false; cols * rows];
+        for &(x1, y1, x2, y2) in rects {
+            let (c1, c2) = (index_of(&xs, x1), index_of(&xs, x2));
+            let (r1, r2) = (index_of(&ys, y1), index_of(&ys, y2));
+            for r in r1..r2 {
+                occupied[r * cols + c1..r * cols + c2].fill(true);
+            }
+        }
+        Some(CompressedGrid { xs, ys, cols, rows, occupied })
+    }
+
+    fn at(&self, c: usize, r: usize) -> bool {
+        self.occupied[r * self.cols + c]
+    }
+
+    fn greedy_rects(&self) -> Vec<Rect> {
+        let mut used = vec![false; self.occupied.len()];
+        let free = |used: &[bool], c: usize, r: usize| self.at(c, r) && !used[r * self.cols + c];
+
+        let mut rects = Vec::new();
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                if !free(&used, c, r) {
+                    continue;
+                }
+                let mut w = 1;
+                while c + w < self.cols && free(&used, c + w, r) {
+                    w += 1;
+                }
+                let mut h = 1;
+                while r + h < self.rows && (c..c + w).all(|cc| free(&used, cc, r + h)) {
+                    h += 1;
+                }
+                for rr in r..r + h {
+                    used[rr * self.cols + c..rr * self.cols + c + w].fill(true);
+                }
+                rects.push((self.xs[c], self.ys[r], self.xs[c + w], self.ys[r + h]));
+            }
+        }
+        rects
+    }
+
+    // Cell sides with occupancy on exactly one side, joined into runs
+    fn boundary(&self) -> Vec<Edge> {
+        let mut edges = Vec::new();
+
+        // Horizontal edges along each row line
+        for j in 0..=self.rows {
+            let mut run: Option<(usize, i32)> = None;
+            for c in 0..=self.cols {
+                let side = if c == self.cols {
+                    0
+                } else {
+                    let above = j > 0 && self.at(c, j - 1);
+                    let below = j < self.rows && self.at(c, j);
+                    below as i32 - above as i32
+                };
+                if let Some((start, inward)) = run {
+                    if side == inward {
+                        continue;
+                    }
+                    edges.push(Edge {
+                        from: (self.xs[start], self.ys[j]),
+                        to: (self.xs[c], self.ys[j]),
+                        inward: (0, inward),
+                    });
+                    run = None;
+                }
+                if side != 0 {
+                    run = Some((c, side));
+                }
+            }
+        }
+
+        // Vertical edges along each column line
+        for i in 0..=self.cols {
+            let mut run: Option<(usize, i32)> = None;
+            for r in 0..=self.rows {
+                let side = if r == self.rows {
+                    0
+                } else {
+                    let left = i > 0 && self.at(i - 1, r);
+                    let right = i < self.cols && self.at(i, r);
+                    right as i32 - left as i32
+                };
+                if let Some((start, inward)) = run {
+                    if side == inward {
+                        continue;
+                    }
+                    edges.push(Edge {
+                        from: (self.xs[i], self.ys[start]),
+                        to: (self.xs[i], self.ys[r]),
+                        inward: (inward, 0),
+                    });
+                    run = None;
+                }
+                if side != 0 {
+                    run = Some((r, side));
+                }
+            }
+        }
+
+        edges
+    }
+}
+
+fn index_of(sorted: &[i32], value: i32) -> usize {
+    sorted.binary_search(&value).expect("edge coordinate collected from these rects")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(rects: &[Rect]) -> i64 {
+        rects.iter().map(|r| (r.2 - r.0) as i64 * (r.3 - r.1) as i64).sum()
+    }
+
+    #[test]
+    fn merges_a_floor_of_plates_into_one_rect() {
+        let mut plates = Vec::new();
+        for y in 0..10 {
+            for x in 0..10 {
+                plates.push((x * 10, y * 10, x * 10 + 10, y * 10 + 10));
+            }
+        }
+        let merged = merge_rects(&plates);
+        assert_eq!(merged.rects, vec![(0, 0, 100, 100)]);
+        // One run per side of the square
+        assert_eq!(merged.boundary.len(), 4);
+    }
+
+    #[test]
+    fn keeps_an_l_shape_area_and_outlines_its_six_sides() {
+        // 2x2 square missing its top-right quarter
+        let rects = [(0, 0, 10, 10), (0, 10, 10, 20), (10, 10, 20, 20)];
+        let merged = merge_rects(&rects);
+        assert_eq!(area(&merged.rects), area(&rects));
+        assert_eq!(merged.rects.len(), 2);
+        assert_eq!(merged.boundary.len(), 6);
+        for edge in &merged.boundary {
+            let len = (edge.to.0 - edge.from.0) + (edge.to.1 - edge.from.1);
+            assert!(len > 0, "degenerate edge {:?}", edge);
+        }
+    }
+
+    #[test]
+    fn separate_bricks_stay_separate() {
+        let rects = [(0, 0, 10, 10), (30, 0, 40, 10)];
+        let merged = merge_rects(&rects);
+        assert_eq!(merged.rects, rects.to_vec());
+        assert_eq!(merged.boundary.len(), 8);
+    }
+
+    #[test]
+    fn touching_walls_share_one_outline() {
+        // A T: the stem's top corners meet the middle of the bar's side
+        let rects = [(0, 0, 30, 10), (10, 10, 20, 30)];
+        let merged = merge_rects(&rects);
+        assert_eq!(area(&merged.rects), area(&rects));
+        assert_eq!(merged.boundary.len(), 8);
+        // Nothing runs across the junction
+        assert!(merged.boundary.iter().all(|edge| edge.from.1 != 10 || edge.to.0 <= 10 || edge.from.0 >= 20));
+    }
+
+    #[test]
+    fn merges_a_running_bond() {
+        // 1x2 bricks, the second row offset by half a brick, so no two rows
+        // share a corner
+        let rects = [(0, 0, 20, 10), (20, 0, 40, 10), (40, 0, 60, 10), (10, 10, 30, 20), (30, 10, 50, 20)];
+        let merged = merge_rects(&rects);
+        assert_eq!(merged.rects, vec![(0, 0, 60, 10), (10, 10, 50, 20)]);
+        // One outline around both rows: nothing along the seam between them
+        assert_eq!(merged.boundary.len(), 8);
+        assert!(merged.boundary.iter().all(|edge| edge.from.1 != 10 || edge.to.0 <= 10 || edge.from.0 >= 50));
+    }
+
+    #[test]
+    fn merges_a_brick_abutting_the_middle_of_a_longer_one() {
+        // A 1x2 against the middle of a 1x4's side
+        let rects = [(0, 10, 10, 30), (10, 0, 20, 40)];
+        let merged = merge_rects(&rects);
+        assert_eq!(area(&merged.rects), area(&rects));
+        assert_eq!(merged.boundary.len(), 8);
+        assert!(!merged.boundary.iter().any(|edge| edge.from.0 == 10 && edge.to.0 == 10 && edge.from.1 < 30 && edge.to.1 > 10));
+    }
+
+    #[test]
+    fn boundary_edges_point_into_the_region() {
+        let merged = merge_rects(&[(0, 0, 10, 20)]);
+        for edge in &merged.boundary {
+            let mid = ((edge.from.0 + edge.to.0) as f32 / 2.0, (edge.from.1 + edge.to.1) as f32 / 2.0);
+            let probe = (mid.0 + edge.inward.0 as f32, mid.1 + edge.inward.1 as f32);
+            assert!(probe.0 > 0.0 && probe.0 < 10.0 && probe.1 > 0.0 && probe.1 < 20.0, "{:?}", edge);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::brick::Brick;
+use crate::heightfield::{HeightField, EMPTY};
+use crate::merge::Rect;
+use crate::overlay::ImageView;
+use crate::raster::Raster;
+use crate::SaveData;
+
+// Save units per stud
+const STUD: f32 = 10.0;
+
+// How much light a fully enclosed surface loses
+const OCCLUSION_DARKNESS: f32 = 0.6;
+
+// Compass directions searched for a horizon, diagonals included
+const DIRECTIONS: [(f32, f32); 8] = [
+    (1.0, 0.0),
+    (std::f32::consts::FRAC_1_SQRT_2, std::f32::consts::FRAC_1_SQRT_2),
+    (0.0, 1.0),
+    (-std::f32::consts::FRAC_1_SQRT_2, std::f32::consts::FRAC_1_SQRT_2),
+    (-1.0, 0.0),
+    (-std::f32::consts::FRAC_1_SQRT_2, -std::f32::consts::FRAC_1_SQRT_2),
+    (0.0, -1.0),
+    (std::f32::consts::FRAC_1_SQRT_2, -std::f32::consts::FRAC_1_SQRT_2),
+];
+
+// Most samples taken along each direction, however many cells the radius
+// spans
+const MAX_STEPS: usize = 12;
+
+/// How much of the sky each cell of the ground sees past the bricks around
+/// it, which darkens alleys, courtyards and the base of walls.
+pub struct OcclusionMap {
+    field: HeightField,
+    // 0.0 open sky to 1.0 fully enclosed
+    shade: Vec<f32>,
+}
+
+impl OcclusionMap {
+    /// Occlusion over `area` (absolute save units, x1, y1, x2, y2) in cells
+    /// `cell` save units square, from bricks within `radius` save units.
+    /// Only `bricks` occlude and are shaded.
+    pub fn new<'a>(radius: f32, area: (f32, f32, f32, f32), cell: f32, bricks: impl Iterator<Item = &'a Brick>) -> OcclusionMap {
+        OcclusionMap::over(radius, HeightField::new(area, cell, bricks))
+    }
+
+    // Each direction is blocked as far as the steepest rise along it within
+    // the radius, and a cell is as occluded as its directions on average
+    fn over(radius: f32, field: HeightField) -> OcclusionMap {
+        let HeightField { cols, rows, cell, .. } = field;
+        let steps = ((radius / cell).ceil() as usize).clamp(1, MAX_STEPS);
+        let height_at = |c: f32, r: f32| {
+            if c < 0.0 || r < 0.0 || c >= cols as f32 || r >= rows as f32 {
+                return EMPTY;
+            }
+            field.heights[r as usize * cols + c as usize]
+        };
+
+        let mut shade = vec![0.0; cols * rows];
+        for r in 0..rows {
+            for c in 0..cols {
+                let height = field.heights[r * cols + c];
+                if height <= EMPTY {
+                    continue;
+                }
+                let mut blocked = 0.0;
+                for (dx, dy) in DIRECTIONS {
+                    let mut steepest: f32 = 0.0;
+                    for step in 1..=steps {
+                        let distance = radius * step as f32 / steps as f32;
+                        let other = height_at(
+                            (c as f32 + 0.5 + dx * distance / cell).floor(),
+                            (r as f32 + 0.5 + dy * distance / cell).floor(),
+                        );
+                        if other > EMPTY {
+                            steepest = steepest.max((other - height) / distance);
+                        }
+                    }
+                    // Sine of the horizon's angle above level
+                    blocked += steepest / (1.0 + steepest * steepest).sqrt();
+                }
+                shade[r * cols + c] = blocked / DIRECTIONS.len() as f32;
+            }
+        }
+        OcclusionMap { field, shade }
+    }
+
+    /// How occluded the ground is at an absolute position, 0.0..=1.0.
+    pub fn at(&self, position: (f32, f32)) -> f32 {
+        self.field.cell_at(position).map_or(0.0, |i| self.shade[i])
+    }
+
+    /// Darkens the occluded parts of `raster`, which holds the image `view`
+    /// describes.
+    pub fn apply(&self, raster: &mut Raster, view: &ImageView) {
+        self.field.darken(raster, view, &self.shade, OCCLUSION_DARKNESS);
+    }
+}
+
+/// Darkens `raster` (the image `view` describes) with ambient occlusion from
+/// bricks within `radius` studs, over the save's bricks or those reaching
+/// into `region` when given (absolute save units).
+pub fn occlude(save: &SaveData, radius: f32, region: Option<Rect>, raster: &mut Raster, view: &ImageView) {
+    let radius = radius * STUD;
+    // Bricks just past the image's edges still occlude the ground inside
+    let grow = (radius, radius, radius, radius);
+    if let Some(field) = HeightField::under_view(save, view, grow, region) {
+        OcclusionMap::over(radius, field).apply(raster, view);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walls_darken_the_ground_at_their_base() {
+        // A floor 200 units square with a wall along its west edge and
+        // another along its north edge, 50 taller
+        let bricks = [
+            Brick::sized((100, 100, 2), (100, 100, 2)),
+            Brick::sized((5, 100, 29), (5, 100, 25)),
+            Brick::sized((100, 5, 29), (100, 5, 25)),
+        ];
+        let map = OcclusionMap::new(30.0, (0.0, 0.0, 200.0, 200.0), 5.0, bricks.iter());
+
+        let beside = map.at((12.0, 150.0));
+        let corner = map.at((12.0, 12.0));
+        assert!(beside > 0.2);
+        assert!(corner > beside);
+        // Open floor past the radius and the tops of the walls see the sky
+        assert_eq!(map.at((150.0, 150.0)), 0.0);
+        assert_eq!(map.at((2.0, 150.0)), 0.0);
+    }
+}
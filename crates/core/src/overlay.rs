@@ -0,0 +1,408 @@
+use std::str::FromStr;
+
+use crate::labels::{draw_labels, Label};
+use crate::m3;
+use crate::markers::{draw_markers, Marker, MarkerLayers};
+use crate::raster::{Font, Raster};
+
+// Save units per stud
+const STUD: f32 = 10.0;
+
+// Edge length of the chunks Brickadia stores worlds in, in save units
+const CHUNK: f32 = 2048.0;
+
+// Grids denser than this many pixels per cell would just tint the image
+const MIN_GRID_PX: f32 = 4.0;
+
+// Rough on-image length of the scale bar and spacing of unlabeled ticks
+const TARGET_SPAN_PX: f32 = 150.0;
+
+// Closest two coordinate labels get along an edge
+const MIN_LABEL_GAP_PX: f32 = 80.0;
+
+const MARGIN_PX: f32 = 16.0;
+const GRID_COLOR: [u8; 4] = [0, 0, 0, 72];
+const INK: [u8; 4] = [24, 24, 24, 255];
+const HALO: [u8; 4] = [255, 255, 255, 220];
+const PANEL: [u8; 4] = [255, 255, 255, 160];
+
+/// Grid line spacing in save units.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GridSpacing(pub f32);
+
+/// Parses a number of studs, or `chunk` for Brickadia's world chunks.
+impl FromStr for GridSpacing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GridSpacing, String> {
+        if s.eq_ignore_ascii_case("chunk") || s.eq_ignore_ascii_case("chunks") {
+            return Ok(GridSpacing(CHUNK));
+        }
+        match s.parse::<f32>() {
+            Ok(studs) if studs.is_finite() && studs > 0.0 => Ok(GridSpacing(studs * STUD)),
+            _ => Err(format!("invalid grid spacing '{}', expected a number of studs or 'chunk'", s)),
+        }
+    }
+}
+
+/// Map furniture drawn over a finished image.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Overlays {
+    /// Grid lines aligned to world coordinates
+    pub grid: Option<GridSpacing>,
+    /// Coordinate labels (in studs) where grid lines meet the top and left
+    /// edges. Without a grid they follow an automatic spacing.
+    pub ticks: bool,
+    /// A bar of a round number of studs in the bottom-left corner
+    pub scale_bar: bool,
+    /// A north arrow in the top-right corner that turns with the rotation
+    pub compass: bool,
+    /// The save's text labels, placed clear of each other
+    pub labels: bool,
+    /// Marker categories to draw icons for
+    pub markers: MarkerLayers,
+}
+
+impl Overlays {
+    pub fn any(&self) -> bool {
+        self.grid.is_some() || self.needs_font()
+    }
+
+    /// Whether any enabled overlay draws text; only the grid doesn't.
+    pub fn needs_font(&self) -> bool {
+        self.ticks || self.scale_bar || self.compass || self.labels || self.markers.any()
+    }
+}
+
+/// Where a finished image sits in the world: its pixel size and the view
+/// matrix for the whole image, as from
+/// [`SaveData::view_matrix`](crate::SaveData::view_matrix). Stitched images
+/// use the matrix of one big tile covering all of them.
+#[derive(Clone, Copy, Debug)]
+pub struct ImageView {
+    pub width: u32,
+    pub height: u32,
+    pub matrix: [f32; 9],
+}
+
+impl ImageView {
+    /// World position to pixel position, (0, 0) being the top-left corner.
+    pub fn to_pixel(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let m = &self.matrix;
+        let clip_x = m[0] * x + m[3] * y + m[6];
+        let clip_y = m[1] * x + m[4] * y + m[7];
+        (
+            (clip_x + 1.0) / 2.0 * self.width as f32,
+            (1.0 - clip_y) / 2.0 * self.height as f32,
+        )
+    }
+
+    /// Pixel position to world position.
+    pub fn to_world(&self, (px, py): (f32, f32)) -> (f32, f32) {
+        let m = m3::inverse(self.matrix);
+        let clip_x = px / self.width as f32 * 2.0 - 1.0;
+        let clip_y = 1.0 - py / self.height as f32 * 2.0;
+        (
+            m[0] * clip_x + m[3] * clip_y + m[6],
+            m[1] * clip_x + m[4] * clip_y + m[7],
+        )
+    }
+
+    /// Pixels per save unit.
+    pub fn scale(&self) -> f32 {
+        let px = self.matrix[0] * self.width as f32 / 2.0;
+        let py = self.matrix[1] * self.height as f32 / 2.0;
+        px.hypot(py)
+    }
+
+    /// World-space bounding box of the image's corners, as (x1, y1, x2, y2).
+    pub fn world_bounds(&self) -> (f32, f32, f32, f32) {
+        let (w, h) = (self.width as f32, self.height as f32);
+        let corners = [(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)].map(|p| self.to_world(p));
+        corners.iter().fold(
+            (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            |b, c| (b.0.min(c.0), b.1.min(c.1), b.2.max(c.0), b.3.max(c.1)),
+        )
+    }
+}
+
+/// Draws the enabled overlays onto `raster`, which holds the image `view`
+/// describes, setting text in `font`; without one, only the grid draws.
+/// `labels` and `markers` are the save's, drawn when enabled.
+pub fn draw_overlays(
+    raster: &mut Raster,
+    view: &ImageView,
+    overlays: &Overlays,
+    font: Option<&Font>,
+    labels: &[Label],
+    markers: &[Marker],
+) {
+    let scale = view.scale();
+    if !overlays.any() || !scale.is_normal() {
+        return;
+    }
+
+    if let Some(GridSpacing(spacing)) = overlays.grid {
+        if spacing * scale >= MIN_GRID_PX {
+            for (from, to) in grid_lines(view, spacing, false).chain(grid_lines(view, spacing, true)) {
+                raster.line(view.to_pixel(from), view.to_pixel(to), 1.0, GRID_COLOR);
+            }
+        }
+    }
+    let Some(font) = font else {
+        return;
+    };
+    // Labels go around marker icons rather than over them
+    let icons = if overlays.markers.any() {
+        draw_markers(raster, view, font, markers, &overlays.markers)
+    } else {
+        Vec::new()
+    };
+    if overlays.labels {
+        draw_labels(raster, view, font, labels, &icons);
+    }
+    if overlays.ticks {
+        let spacing = match overlays.grid {
+            Some(GridSpacing(spacing)) => spacing,
+            None => nice_floor(TARGET_SPAN_PX / scale / STUD) * STUD,
+        };
+        draw_ticks(raster, view, font, spacing);
+    }
+    if overlays.scale_bar {
+        draw_scale_bar(raster, view, font);
+    }
+    if overlays.compass {
+        draw_compass(raster, view, font);
+    }
+}
+
+// World-space segments of the lines at multiples of `spacing` crossing the
+// image: x = const lines, or y = const lines when `horizontal`
+fn grid_lines(view: &ImageView, spacing: f32, horizontal: bool) -> impl Iterator<Item = ((f32, f32), (f32, f32))> {
+    let (x1, y1, x2, y2) = view.world_bounds();
+    let (lo, hi, across) = if horizontal { (y1, y2, (x1, x2)) } else { (x1, x2, (y1, y2)) };
+    let first = (lo / spacing).ceil() as i64;
+    let last = (hi / spacing).floor() as i64;
+    (first..=last).map(move |k| {
+        let at = k as f32 * spacing;
+        if horizontal {
+            ((across.0, at), (across.1, at))
+        } else {
+            ((at, across.0), (at, across.1))
+        }
+    })
+}
+
+fn draw_ticks(raster: &mut Raster, view: &ImageView, font: &Font, spacing: f32) {
+    const SIZE: f32 = 13.0;
+    const TICK: f32 = 6.0;
+    // Keeps the two edges' labels apart where they meet in the corner
+    const CORNER: f32 = 40.0;
+    let every = (MIN_LABEL_GAP_PX / (spacing * view.scale())).ceil().max(1.0) as i64;
+    let (w, h) = (view.width as f32, view.height as f32);
+
+    // Each family of lines is labeled along the edge it crosses more
+    // steeply: x = const lines along the top until rotation turns them
+    // closer to horizontal, and y = const lines along the other edge
+    let origin = view.to_pixel((0.0, 0.0));
+    let along_x_line = view.to_pixel((0.0, 1.0));
+    let x_on_top = (along_x_line.1 - origin.1).abs() >= (along_x_line.0 - origin.0).abs();
+
+    for horizontal in [false, true] {
+        let on_top = x_on_top != horizontal;
+        for (from, to) in grid_lines(view, spacing, horizontal) {
+            let value = if horizontal { from.1 } else { from.0 };
+            if ((value / spacing).round() as i64).rem_euclid(every) != 0 {
+                continue;
+            }
+            let Some((a, b)) = clip_to_image(view.to_pixel(from), view.to_pixel(to), w, h) else {
+                continue;
+            };
+            let on_edge = |p: (f32, f32)| {
+                if on_top {
+                    p.1 < 0.5 && p.0 >= CORNER
+                } else {
+                    p.0 < 0.5 && p.1 >= CORNER / 2.0
+                }
+            };
+            let (edge, inner) = if on_edge(a) {
+                (a, b)
+            } else if on_edge(b) {
+                (b, a)
+            } else {
+                continue;
+            };
+            let length = (inner.0 - edge.0).hypot(inner.1 - edge.1);
+            if length < TICK {
+                continue;
+            }
+            let dir = ((inner.0 - edge.0) / length, (inner.1 - edge.1) / length);
+            raster.line(edge, (edge.0 + dir.0 * TICK, edge.1 + dir.1 * TICK), 1.5, INK);
+
+            let text = format_studs(value / STUD);
+            let (tw, th) = font.measure(&text, SIZE);
+            let at = if on_top {
+                (edge.0 - tw / 2.0, edge.1 + TICK + 2.0)
+            } else {
+                (edge.0 + TICK + 3.0, edge.1 - th / 2.0)
+            };
+            let at = (at.0.clamp(2.0, (w - tw - 2.0).max(2.0)), at.1.clamp(2.0, (h - th - 2.0).max(2.0)));
+            font.draw_haloed(raster, &text, SIZE, at, INK, HALO);
+        }
+    }
+}
+
+fn draw_scale_bar(raster: &mut Raster, view: &ImageView, font: &Font) {
+    const SIZE: f32 = 14.0;
+    const BAR: f32 = 6.0;
+    let studs = nice_floor(TARGET_SPAN_PX / view.scale() / STUD);
+    let length = studs * STUD * view.scale();
+    let text = if studs == 1.0 {
+        "1 stud".to_string()
+    } else {
+        format!("{} studs", format_studs(studs))
+    };
+    let (tw, th) = font.measure(&text, SIZE);
+
+    let panel_w = length.max(tw) + 12.0;
+    let panel_h = th + BAR + 14.0;
+    let (w, h) = (view.width as f32, view.height as f32);
+    if panel_w + MARGIN_PX * 2.0 > w || panel_h + MARGIN_PX * 2.0 > h {
+        return;
+    }
+    let left = MARGIN_PX;
+    let top = h - MARGIN_PX - panel_h;
+    raster.fill_rect(left as i32, top as i32, panel_w.ceil() as i32, panel_h.ceil() as i32, PANEL);
+    font.draw(raster, &text, SIZE, (left + 6.0, top + 4.0), INK);
+
+    // Black bar with a white second half, like a printed map's
+    let bar_x = (left + 6.0) as i32;
+    let bar_y = (top + th + 8.0) as i32;
+    let bar_len = length.round() as i32;
+    raster.fill_rect(bar_x, bar_y, bar_len, BAR as i32, INK);
+    raster.fill_rect(bar_x + bar_len / 2, bar_y + 1, bar_len - bar_len / 2 - 1, BAR as i32 - 2, [255, 255, 255, 255]);
+}
+
+fn draw_compass(raster: &mut Raster, view: &ImageView, font: &Font) {
+    const SIZE: f32 = 15.0;
+    const RADIUS: f32 = 18.0;
+    let (w, h) = (view.width as f32, view.height as f32);
+    let reach = RADIUS + SIZE + 8.0;
+    if w < reach * 2.0 + MARGIN_PX || h < reach * 2.0 + MARGIN_PX {
+        return;
+    }
+    let center = (w - MARGIN_PX - reach, MARGIN_PX + reach);
+
+    // North is world -y, the top of an unrotated map
+    let origin = view.to_pixel((0.0, 0.0));
+    let north = view.to_pixel((0.0, -1.0));
+    let (dx, dy) = (north.0 - origin.0, north.1 - origin.1);
+    let length = dx.hypot(dy);
+    if length == 0.0 {
+        return;
+    }
+    let dir = (dx / length, dy / length);
+    let side = (-dir.1, dir.0);
+    let point = |forward: f32, across: f32| {
+        (
+            center.0 + dir.0 * forward + side.0 * across,
+            center.1 + dir.1 * forward + side.1 * across,
+        )
+    };
+
+    raster.fill_circle(center, RADIUS + 5.0, PANEL);
+    let (tip, tail) = (point(RADIUS, 0.0), point(-RADIUS, 0.0));
+    let (left, right) = (point(0.0, -RADIUS * 0.4), point(0.0, RADIUS * 0.4));
+    raster.fill_polygon(&[tail, left, right], [255, 255, 255, 255]);
+    for (a, b) in [(tail, left), (left, right), (right, tail)] {
+        raster.line(a, b, 1.0, INK);
+    }
+    raster.fill_polygon(&[tip, right, left], INK);
+
+    let (tw, th) = font.measure("N", SIZE);
+    let label = point(RADIUS + 5.0 + th / 2.0, 0.0);
+    font.draw_haloed(raster, "N", SIZE, (label.0 - tw / 2.0, label.1 - th / 2.0), INK, HALO);
+}
+
+// Liang-Barsky clip of a pixel-space segment to the image rectangle
+fn clip_to_image(a: (f32, f32), b: (f32, f32), w: f32, h: f32) -> Option<((f32, f32), (f32, f32))> {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+    for (p, q) in [(-dx, a.0), (dx, w - a.0), (-dy, a.1), (dy, h - a.1)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = q / p;
+        if p < 0.0 {
+            t0 = t0.max(t);
+        } else {
+            t1 = t1.min(t);
+        }
+    }
+    (t0 < t1).then_some(((a.0 + t0 * dx, a.1 + t0 * dy), (a.0 + t1 * dx, a.1 + t1 * dy)))
+}
+
+// Largest 1, 2 or 5 times a power of ten not above `value`
+fn nice_floor(value: f32) -> f32 {
+    let magnitude = 10f32.powf(value.log10().floor());
+    let leading = value / magnitude;
+    let nice = if leading >= 5.0 {
+        5.0
+    } else if leading >= 2.0 {
+        2.0
+    } else {
+        1.0
+    };
+    nice * magnitude
+}
+
+fn format_studs(studs: f32) -> String {
+    if (studs - studs.round()).abs() < 0.05 {
+        format!("{}", studs.round() as i64)
+    } else {
+        format!("{:.1}", studs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_view_round_trips_world_positions() {
+        let mut matrix = m3::projection(800.0, 600.0);
+        matrix = m3::translate(matrix, 400.0, 300.0);
+        matrix = m3::scale(matrix, 0.25, 0.25);
+        matrix = m3::rotate(matrix, 0.6);
+        matrix = m3::translate(matrix, -120.0, 45.0);
+        let view = ImageView { width: 800, height: 600, matrix };
+
+        assert!((view.scale() - 0.25).abs() < 1e-4);
+        for world in [(120.0, -45.0), (0.0, 0.0), (-1000.0, 2500.0)] {
+            let back = view.to_world(view.to_pixel(world));
+            assert!((back.0 - world.0).abs() < 0.05 && (back.1 - world.1).abs() < 0.05, "{:?}", back);
+        }
+        // The pan target lands in the middle of the image
+        let center = view.to_pixel((120.0, -45.0));
+        assert!((center.0 - 400.0).abs() < 1e-2 && (center.1 - 300.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn scale_bar_lengths_are_round_numbers() {
+        assert_eq!(nice_floor(1.0), 1.0);
+        assert_eq!(nice_floor(3.7), 2.0);
+        assert_eq!(nice_floor(74.0), 50.0);
+        assert!((nice_floor(0.13) - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn parses_grid_spacing() {
+        assert_eq!("8".parse::<GridSpacing>(), Ok(GridSpacing(80.0)));
+        assert_eq!("chunk".parse::<GridSpacing>(), Ok(GridSpacing(CHUNK)));
+        assert!("0".parse::<GridSpacing>().is_err());
+        assert!("lots".parse::<GridSpacing>().is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use crate::brick::Brick;
+use crate::color::mix;
+use crate::util;
+
+// A brick must rise this far from the floor to stand as a wall: two bricks,
+// so furniture and curbs stay part of the floor
+const WALL_MIN_RISE: i32 = 24;
+
+// How far a wall's base may sit off the floor, a plate either way, for
+// walls set on a baseplate or sunk into the floor
+const WALL_BASE_GAP: i32 = 4;
+
+/// Fill over the area walls enclose, beneath their outlines.
+pub const WALL_FILL: [u8; 4] = [255, 255, 255, 255];
+
+/// Color of wall outlines.
+pub const WALL_LINE: [u8; 4] = [0, 0, 0, 255];
+
+const PAPER: [u8; 4] = [255, 255, 255, 255];
+
+// How far floor colors fade toward the paper
+const FLOOR_FADE: f32 = 0.8;
+
+/// Whether a brick stands as a wall on a floor at height `floor`: standing
+/// on it and at least two bricks tall itself, with a footprint no wider than
+/// its height, like a wall, column or post rather than a platform. Walls of
+/// the storeys above or below don't count, however far they reach.
+pub fn is_wall(brick: &Brick, floor: i32) -> bool {
+    let size = util::sizer(brick);
+    let height = 2 * size.2 as i32;
+    let narrow = 2 * size.0.min(size.1) as i32;
+    (util::bottom_surface(brick) - floor).abs() <= WALL_BASE_GAP && height >= WALL_MIN_RISE && narrow <= height
+}
+
+/// The light fill a floor brick of `color` gets on the plan.
+pub fn floor_fill(color: [u8; 4]) -> [u8; 4] {
+    mix(color, PAPER, FLOOR_FADE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tall_narrow_bricks_are_walls() {
+        // On a floor at 4: a 1x8 wall three bricks tall, a 4x4 column four
+        // bricks tall, a table and a wide platform
+        assert!(is_wall(&Brick::sized((0, 0, 22), (5, 40, 18)), 4));
+        assert!(is_wall(&Brick::sized((0, 0, 28), (20, 20, 24)), 4));
+        assert!(!is_wall(&Brick::sized((0, 0, 20), (10, 10, 2)), 4));
+        assert!(!is_wall(&Brick::sized((0, 0, 22), (40, 40, 18)), 4));
+        // The floor itself
+        assert!(!is_wall(&Brick::sized((0, 0, 2), (100, 100, 2)), 4));
+    }
+
+    #[test]
+    fn walls_belong_to_the_storey_they_stand_on() {
+        // Two storeys with floors at 4 and 104: a ground floor wall, a tall
+        // upstairs wall, a short upstairs shelf and a ground floor pillar
+        // that was stacked on a crate
+        let ground = Brick::sized((0, 0, 52), (5, 100, 48));
+        let upstairs = Brick::sized((50, 0, 152), (5, 100, 48));
+        let shelf = Brick::sized((50, 0, 110), (5, 20, 6));
+        let perched = Brick::sized((-50, 0, 64), (5, 5, 24));
+        assert!(is_wall(&ground, 4));
+        assert!(!is_wall(&ground, 104));
+        // Its top rises far above the ground floor, but it stands upstairs
+        assert!(is_wall(&upstairs, 104));
+        assert!(!is_wall(&upstairs, 4));
+        assert!(!is_wall(&shelf, 104));
+        assert!(!is_wall(&perched, 4));
+
+        // Each storey's heights keep the other's walls out of its plan
+        let floors = [Brick::sized((0, 0, 2), (100, 100, 2)), Brick::sized((0, 0, 102), (100, 100, 2))];
+        let bricks = [&floors[0], &floors[1], &ground, &upstairs, &shelf, &perched];
+        let storeys = crate::floors::detect_storeys(bricks.into_iter(), 48);
+        let walls: Vec<Vec<i32>> = storeys
+            .iter()
+            .map(|storey| {
+                let (low, high) = storey.heights();
+                bricks
+                    .iter()
+                    .filter(|brick| (low..high).contains(&util::top_surface(brick)) && is_wall(brick, storey.floor))
+                    .map(|brick| brick.position.0)
+                    .collect()
+            })
+            .collect();
+        assert_eq!(walls, vec![vec![0], vec![50]]);
+    }
+}
@@ -0,0 +1,443 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, FRAC_PI_6, PI};
+use std::str::FromStr;
+
+use crate::brick::Brick;
+use crate::color::{mix, shade};
+use crate::merge::Rect;
+use crate::util;
+
+// Side faces darken by how they face the viewer: those turned left catch
+// the light, those turned right sit in shade
+const LEFT_SHADE: f32 = 0.8;
+const RIGHT_SHADE: f32 = 0.6;
+
+// Depth covers this far above and below the ground, in save units, on top
+// of the build's horizontal reach. The depth buffer's 24 bits leave a step
+// well under a save unit for all but the largest worlds.
+const HEIGHT_REACH: f32 = 1_048_576.0;
+
+// How much of the farthest faces' color fog covers
+const FOG_MAX: f32 = 0.85;
+
+/// A compass direction to look at the build from, level with the ground.
+/// North is world -y, the top of an unrotated map, and east is +x.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl FromStr for Side {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Side, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "north" | "n" => Ok(Side::North),
+            "east" | "e" => Ok(Side::East),
+            "south" | "s" => Ok(Side::South),
+            "west" | "w" => Ok(Side::West),
+            _ => Err(format!("invalid side '{}', expected north, east, south or west", s)),
+        }
+    }
+}
+
+/// A parallel projection looking down on the build at an angle, so height
+/// shows. The build turns by `azimuth` as a rotated map would, then tips
+/// away from the viewer until it's seen from `elevation` above the horizon.
+/// At an elevation of 90 degrees it's the top-down map again.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Oblique {
+    /// Radians, turning the same way as a map's rotation
+    pub azimuth: f32,
+    /// Radians above the horizon, in [0, π/2]; 0 only for facades
+    pub elevation: f32,
+    /// Color farther faces fade toward, if any
+    pub fog: Option<[u8; 4]>,
+    /// For cross-sections, the only part of the build shown
+    pub slab: Option<Slab>,
+}
+
+/// A vertical slice through the build along a line, for cross-sections.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Slab {
+    /// Start of the cut line, in absolute save units
+    pub from: (f32, f32),
+    /// End of the cut line
+    pub to: (f32, f32),
+    /// Width of the slice across the line, centered on it
+    pub thickness: f32,
+}
+
+impl Slab {
+    /// Whether a brick's footprint reaches into the slice. Tests the
+    /// footprint against the slice's rectangle along all four of their axes.
+    pub fn reaches(&self, brick: &Brick) -> bool {
+        let size = util::sizer(brick);
+        let p = brick.position;
+        let (x1, y1) = ((p.0 - size.0 as i32) as f32, (p.1 - size.1 as i32) as f32);
+        let (x2, y2) = ((p.0 + size.0 as i32) as f32, (p.1 + size.1 as i32) as f32);
+
+        let (dx, dy) = (self.to.0 - self.from.0, self.to.1 - self.from.1);
+        let length = dx.hypot(dy);
+        let along = (dx / length, dy / length);
+        let across = (-along.1, along.0);
+        let half = self.thickness / 2.0;
+        let slab = [
+            (self.from.0 + across.0 * half, self.from.1 + across.1 * half),
+            (self.from.0 - across.0 * half, self.from.1 - across.1 * half),
+            (self.to.0 + across.0 * half, self.to.1 + across.1 * half),
+            (self.to.0 - across.0 * half, self.to.1 - across.1 * half),
+        ];
+        let footprint = [(x1, y1), (x2, y1), (x1, y2), (x2, y2)];
+
+        let range = |points: &[(f32, f32)], axis: (f32, f32)| {
+            points.iter().fold((f32::MAX, f32::MIN), |(lo, hi), &(x, y)| {
+                let d = (x - self.from.0) * axis.0 + (y - self.from.1) * axis.1;
+                (lo.min(d), hi.max(d))
+            })
+        };
+        let apart = |a: (f32, f32), b: (f32, f32)| a.1 < b.0 || b.1 < a.0;
+        [(1.0, 0.0), (0.0, 1.0), along, across]
+            .into_iter()
+            .all(|axis| !apart(range(&slab, axis), range(&footprint, axis)))
+    }
+}
+
+impl Oblique {
+    /// Corners toward the viewer and 2:1 diagonals, as in most isometric
+    /// games.
+    pub const ISOMETRIC: Oblique = Oblique { azimuth: FRAC_PI_4, elevation: FRAC_PI_6, fog: None, slab: None };
+
+    /// From angles in degrees.
+    pub fn new(azimuth: f32, elevation: f32) -> Result<Oblique, String> {
+        if !(elevation > 0.0 && elevation <= 90.0) {
+            return Err("elevation must be above 0 and at most 90 degrees".to_string());
+        }
+        Ok(Oblique { azimuth: azimuth.to_radians(), elevation: elevation.to_radians().min(FRAC_PI_2), fog: None, slab: None })
+    }
+
+    /// An elevation drawing: the build seen level from `side`, heights up
+    /// the map and nearer walls hiding those behind.
+    pub fn facade(side: Side) -> Oblique {
+        // The viewer sits toward +y once the build has turned
+        let azimuth = match side {
+            Side::South => 0.0,
+            Side::West => FRAC_PI_2,
+            Side::North => PI,
+            Side::East => -FRAC_PI_2,
+        };
+        Oblique { azimuth, elevation: 0.0, fog: None, slab: None }
+    }
+
+    /// A cross-section: the bricks within `thickness` of the line `from`
+    /// to `to` (absolute save units), seen level from its right-hand side
+    /// so distance along the line runs left to right and height up the
+    /// map.
+    pub fn section(from: (i32, i32), to: (i32, i32), thickness: i32) -> Result<Oblique, String> {
+        if from == to {
+            return Err("a section needs a line between two different points".to_string());
+        }
+        if thickness <= 0 {
+            return Err("section thickness must be greater than zero".to_string());
+        }
+        let (dx, dy) = ((to.0 - from.0) as f32, (to.1 - from.1) as f32);
+        Ok(Oblique {
+            azimuth: dy.atan2(dx),
+            elevation: 0.0,
+            fog: None,
+            slab: Some(Slab {
+                from: (from.0 as f32, from.1 as f32),
+                to: (to.0 as f32, to.1 as f32),
+                thickness: thickness as f32,
+            }),
+        })
+    }
+
+    /// The same view with farther faces fading toward `color`.
+    pub fn with_fog(self, color: Option<[u8; 4]>) -> Oblique {
+        Oblique { fog: color, ..self }
+    }
+
+    /// Extent of the projected bricks as x1, y1, x2, y2 relative to the
+    /// centroid, like [`SaveData::bounds`](crate::SaveData::bounds) is for
+    /// the top-down map. `None` without bricks.
+    pub fn bounds<'a>(&self, centroid: (i32, i32), bricks: impl Iterator<Item = &'a Brick>) -> Option<(i32, i32, i32, i32)> {
+        let projector = Projector::new(*self, centroid, 0.0);
+        let mut bounds: Option<(f32, f32, f32, f32)> = None;
+        for brick in bricks.filter(|brick| projector.shows(brick)) {
+            let (x1, y1, x2, y2) = projector.extent(brick);
+            bounds = Some(match bounds {
+                Some(b) => (b.0.min(x1), b.1.min(y1), b.2.max(x2), b.3.max(y2)),
+                None => (x1, y1, x2, y2),
+            });
+        }
+        bounds.map(|(x1, y1, x2, y2)| {
+            (
+                x1.floor() as i32 - centroid.0,
+                y1.floor() as i32 - centroid.1,
+                x2.ceil() as i32 - centroid.0,
+                y2.ceil() as i32 - centroid.1,
+            )
+        })
+    }
+}
+
+/// Projects brick boxes for one save. Points turn and tip about the
+/// centroid at ground level, so the projected map stays centered where the
+/// top-down one is.
+pub struct Projector {
+    anchor: (f32, f32),
+    sin_a: f32,
+    cos_a: f32,
+    sin_e: f32,
+    cos_e: f32,
+    // Nearness maps from -reach..reach onto depth 1.0..0.0
+    reach: f32,
+    slab: Option<Slab>,
+    fog: Option<[u8; 4]>,
+    // Nearness of the nearest and farthest points fog spans
+    fog_range: (f32, f32),
+}
+
+impl Projector {
+    /// `reach` is how far from the centroid, horizontally, bricks may lie.
+    pub fn new(oblique: Oblique, centroid: (i32, i32), reach: f32) -> Projector {
+        let (sin_a, cos_a) = oblique.azimuth.sin_cos();
+        let (sin_e, cos_e) = oblique.elevation.sin_cos();
+        // cos(π/2) rounds to a hair below zero
+        let cos_e = cos_e.max(0.0);
+        Projector {
+            anchor: (centroid.0 as f32, centroid.1 as f32),
+            sin_a,
+            cos_a,
+            sin_e,
+            cos_e,
+            reach: reach + HEIGHT_REACH,
+            slab: oblique.slab,
+            fog: oblique.fog,
+            fog_range: (0.0, 0.0),
+        }
+    }
+
+    /// Whether a brick is part of the view; outside a section's slice it
+    /// isn't.
+    pub fn shows(&self, brick: &Brick) -> bool {
+        self.slab.is_none_or(|slab| slab.reaches(brick))
+    }
+
+    /// Spreads fog over `area` (absolute save units) between `heights`, from
+    /// none at its nearest point to the most at its farthest.
+    pub fn fog_over(&mut self, area: Rect, heights: (i32, i32)) {
+        let mut range = (f32::MIN, f32::MAX);
+        for x in [area.0, area.2] {
+            for y in [area.1, area.3] {
+                for z in [heights.0, heights.1] {
+                    let (_, _, near) = self.project((x as f32, y as f32, z as f32));
+                    range = (range.0.max(near), range.1.min(near));
+                }
+            }
+        }
+        self.fog_range = range;
+    }
+
+    fn fogged(&self, color: [u8; 4], nearness: f32) -> [u8; 4] {
+        let Some(fog) = self.fog else {
+            return color;
+        };
+        let (nearest, farthest) = self.fog_range;
+        let distance = ((nearest - nearness) / (nearest - farthest).max(1.0)).clamp(0.0, 1.0);
+        mix(color, fog, distance * FOG_MAX)
+    }
+
+    // Map position and nearness toward the viewer, both in save units
+    fn project(&self, (x, y, z): (f32, f32, f32)) -> (f32, f32, f32) {
+        let (dx, dy) = (x - self.anchor.0, y - self.anchor.1);
+        // Same turn as m3::rotation, then the viewer sits toward +y
+        let rx = dx * self.cos_a + dy * self.sin_a;
+        let ry = dy * self.cos_a - dx * self.sin_a;
+        (
+            self.anchor.0 + rx,
+            self.anchor.1 + ry * self.sin_e - z * self.cos_e,
+            ry * self.cos_e + z * self.sin_e,
+        )
+    }
+
+    fn depth(&self, nearness: f32) -> f32 {
+        (0.5 - nearness / (2.0 * self.reach)).clamp(0.0, 1.0)
+    }
+
+    fn corners(brick: &Brick) -> ((f32, f32, f32), (f32, f32, f32)) {
+        let size = util::sizer(brick);
+        let p = brick.position;
+        (
+            ((p.0 - size.0 as i32) as f32, (p.1 - size.1 as i32) as f32, (p.2 - size.2 as i32) as f32),
+            ((p.0 + size.0 as i32) as f32, (p.1 + size.1 as i32) as f32, (p.2 + size.2 as i32) as f32),
+        )
+    }
+
+    /// Projected extent of a brick's box, as x1, y1, x2, y2 in map
+    /// coordinates. A section cuts bricks at the ends of its line.
+    pub fn extent(&self, brick: &Brick) -> (f32, f32, f32, f32) {
+        let (lo, hi) = Self::corners(brick);
+        let mut extent = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for x in [lo.0, hi.0] {
+            for y in [lo.1, hi.1] {
+                for z in [lo.2, hi.2] {
+                    let (px, py, _) = self.project((x, y, z));
+                    extent = (extent.0.min(px), extent.1.min(py), extent.2.max(px), extent.3.max(py));
+                }
+            }
+        }
+        // Distance along the line runs left to right, from its start
+        if let Some(slab) = self.slab {
+            let (start, _, _) = self.project((slab.from.0, slab.from.1, 0.0));
+            let (end, _, _) = self.project((slab.to.0, slab.to.1, 0.0));
+            extent.0 = extent.0.clamp(start, end);
+            extent.2 = extent.2.clamp(start, end);
+        }
+        extent
+    }
+
+    /// The faces of a brick's box the viewer sees, as triangle lists of
+    /// (x, y, depth) with their shaded colors: the top in `color`, and the
+    /// sides facing the viewer darker. With fog, each face fades by how far
+    /// its middle lies.
+    pub fn faces(&self, brick: &Brick, color: [u8; 4]) -> Vec<(Vec<f32>, [u8; 4])> {
+        let (lo, hi) = Self::corners(brick);
+        let corner = |x: f32, y: f32, z: f32| {
+            let (px, py, near) = self.project((x, y, z));
+            [px, py, self.depth(near)]
+        };
+        let quad = |a: [f32; 3], b: [f32; 3], c: [f32; 3], d: [f32; 3]| -> Vec<f32> {
+            [a, b, c, a, c, d].concat()
+        };
+        let fogged = |color: [u8; 4], x: f32, y: f32, z: f32| self.fogged(color, self.project((x, y, z)).2);
+
+        // A section shows each brick's profile flat in its own color, as
+        // near as the brick's nearest point
+        if self.slab.is_some() {
+            let (x1, y1, x2, y2) = self.extent(brick);
+            let near = [lo.0, hi.0]
+                .into_iter()
+                .flat_map(|x| [lo.1, hi.1].map(|y| self.project((x, y, lo.2)).2))
+                .fold(f32::MIN, f32::max);
+            let depth = self.depth(near);
+            return vec![(quad([x1, y1, depth], [x2, y1, depth], [x2, y2, depth], [x1, y2, depth]), self.fogged(color, near))];
+        }
+
+        let mut faces = Vec::new();
+        // Seen level, the top is edge-on
+        if self.sin_e > 1e-4 {
+            faces.push((
+                quad(corner(lo.0, lo.1, hi.2), corner(hi.0, lo.1, hi.2), corner(hi.0, hi.1, hi.2), corner(lo.0, hi.1, hi.2)),
+                fogged(color, (lo.0 + hi.0) / 2.0, (lo.1 + hi.1) / 2.0, hi.2),
+            ));
+        }
+        // Each side by its outward normal: it shows when the normal, turned
+        // with the build, points toward the viewer
+        let sides = [
+            ((1.0, 0.0), [(hi.0, lo.1), (hi.0, hi.1)]),
+            ((-1.0, 0.0), [(lo.0, hi.1), (lo.0, lo.1)]),
+            ((0.0, 1.0), [(hi.0, hi.1), (lo.0, hi.1)]),
+            ((0.0, -1.0), [(lo.0, lo.1), (hi.0, lo.1)]),
+        ];
+        for ((nx, ny), [(x1, y1), (x2, y2)]) in sides {
+            let toward_viewer = ny * self.cos_a - nx * self.sin_a;
+            if toward_viewer <= 1e-4 || self.cos_e <= 1e-4 {
+                continue;
+            }
+            let leftward = nx * self.cos_a + ny * self.sin_a < 0.0;
+            // Without a top in view, a wall squarely facing the viewer takes
+            // its place in the brick's own color
+            let factor = if self.sin_e <= 1e-4 && toward_viewer > 1.0 - 1e-4 {
+                1.0
+            } else if leftward {
+                LEFT_SHADE
+            } else {
+                RIGHT_SHADE
+            };
+            faces.push((
+                quad(corner(x1, y1, lo.2), corner(x2, y2, lo.2), corner(x2, y2, hi.2), corner(x1, y1, hi.2)),
+                fogged(shade(color, factor), (x1 + x2) / 2.0, (y1 + y2) / 2.0, (lo.2 + hi.2) / 2.0),
+            ));
+        }
+        faces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shows_top_and_two_sides() {
+        let projector = Projector::new(Oblique::ISOMETRIC, (0, 0), 1000.0);
+        let faces = projector.faces(&Brick::sized((0, 0, 6), (10, 10, 6)), [200, 100, 50, 255]);
+        let colors: Vec<[u8; 4]> = faces.iter().map(|(_, color)| *color).collect();
+        assert_eq!(colors, vec![[200, 100, 50, 255], [160, 80, 40, 255], [120, 60, 30, 255]]);
+
+        // Higher bricks sit nearer the viewer and further up the map
+        let (_, low_y, low_near) = projector.project((0.0, 0.0, 0.0));
+        let (_, high_y, high_near) = projector.project((0.0, 0.0, 100.0));
+        assert!(high_y < low_y);
+        assert!(projector.depth(high_near) < projector.depth(low_near));
+    }
+
+    #[test]
+    fn straight_down_is_the_top_down_map() {
+        let oblique = Oblique::new(0.0, 90.0).unwrap();
+        let bounds = oblique.bounds((5, 5), [Brick::sized((0, 0, 50), (10, 20, 50))].iter());
+        assert_eq!(bounds, Some((-15, -25, 5, 15)));
+        assert!(Oblique::new(0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn facade_shows_one_wall_and_fades_with_distance() {
+        // From the south, east is to the right and up is up the map
+        let mut projector = Projector::new(Oblique::facade(Side::South).with_fog(Some([255, 255, 255, 255])), (0, 0), 1000.0);
+        let bounds = Oblique::facade(Side::South).bounds((0, 0), [Brick::sized((100, 0, 20), (10, 10, 20))].iter());
+        assert_eq!(bounds, Some((90, -40, 110, 0)));
+
+        projector.fog_over((-100, -100, 100, 100), (0, 40));
+        let near = projector.faces(&Brick::sized((0, 90, 20), (10, 10, 20)), [200, 100, 50, 255]);
+        let far = projector.faces(&Brick::sized((0, -90, 20), (10, 10, 20)), [200, 100, 50, 255]);
+        assert_eq!(near.len(), 1);
+        assert_eq!(near[0].1, [200, 100, 50, 255]);
+        assert!(far[0].1[2] > 200);
+
+        assert_eq!("West".parse::<Side>(), Ok(Side::West));
+        assert!("up".parse::<Side>().is_err());
+    }
+
+    #[test]
+    fn section_keeps_bricks_in_the_slice() {
+        // A diagonal cut, 20 units thick, from the origin toward +x +y
+        let section = Oblique::section((0, 0), (100, 100), 20).unwrap();
+        let slab = section.slab.unwrap();
+        assert!(slab.reaches(&Brick::sized((50, 50, 10), (5, 5, 10))));
+        assert!(slab.reaches(&Brick::sized((60, 40, 10), (5, 5, 10))));
+        assert!(!slab.reaches(&Brick::sized((80, 20, 10), (5, 5, 10))));
+        // Past the line's end, though across from it
+        assert!(!slab.reaches(&Brick::sized((120, 120, 10), (5, 5, 10))));
+
+        let projector = Projector::new(section, (0, 0), 1000.0);
+        let faces = projector.faces(&Brick::sized((50, 50, 10), (5, 5, 10)), [200, 100, 50, 255]);
+        assert_eq!(faces.len(), 1);
+        assert_eq!(faces[0].1, [200, 100, 50, 255]);
+
+        // Bricks over either end of the line are cut off at it
+        let section = Oblique::section((0, 0), (100, 0), 20).unwrap();
+        let projector = Projector::new(section, (0, 0), 1000.0);
+        let (x1, _, x2, _) = projector.extent(&Brick::sized((0, 0, 10), (20, 5, 10)));
+        assert_eq!((x1, x2), (0.0, 20.0));
+        let (x1, _, x2, _) = projector.extent(&Brick::sized((95, 0, 10), (10, 5, 10)));
+        assert_eq!((x1, x2), (85.0, 100.0));
+        let bounds = section.bounds((0, 0), [Brick::sized((0, 0, 10), (20, 5, 10)), Brick::sized((95, 0, 10), (10, 5, 10))].iter());
+        assert_eq!(bounds.map(|(x1, _, x2, _)| (x1, x2)), Some((0, 100)));
+
+        assert!(Oblique::section((5, 5), (5, 5), 20).is_err());
+        assert!(Oblique::section((0, 0), (5, 5), 0).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use ab_glyph::{Font as _, FontArc, Glyph, PxScale, ScaleFont};
+
+#[cfg(feature = "bundled-font")]
+static BUNDLED_FONT: &[u8] = include_bytes!("../assets/DejaVuSans-Bold.ttf");
+
+/// Antialiased drawing onto a finished RGBA image, for the overlays and
+/// labels composited after rendering. Colors are straight (not premultiplied)
+/// RGBA and blend source-over.
+pub struct Raster<'a> {
+    pixels: &'a mut [u8],
+    width: u32,
+    height: u32,
+}
+
+impl<'a> Raster<'a> {
+    pub fn new(pixels: &'a mut [u8], width: u32, height: u32) -> Result<Raster<'a>, String> {
+        if pixels.len() != (width as usize) * (height as usize) * 4 {
+            return Err(format!("pixel buffer does not match a {}x{} image", width, height));
+        }
+        Ok(Raster { pixels, width, height })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Blends `color` over one pixel at `coverage` (0.0..=1.0). Pixels
+    /// outside the image are ignored.
+    pub fn blend(&mut self, x: i32, y: i32, color: [u8; 4], coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let src_a = color[3] as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+        if src_a <= 0.0 {
+            return;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let dst = &mut self.pixels[i..i + 4];
+        let dst_a = dst[3] as f32 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        for c in 0..3 {
+            let mixed = (color[c] as f32 * src_a + dst[c] as f32 * dst_a * (1.0 - src_a)) / out_a;
+            dst[c] = mixed.round() as u8;
+        }
+        dst[3] = (out_a * 255.0).round() as u8;
+    }
+
+    /// Scales one pixel's color by `factor` (0.0..=1.0), keeping its alpha.
+    /// Pixels outside the image are ignored.
+    pub fn shade(&mut self, x: i32, y: i32, factor: f32) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let shaded = crate::color::shade([self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]], factor);
+        self.pixels[i..i + 4].copy_from_slice(&shaded);
+    }
+
+    /// Fills the pixel rectangle from (x, y), `w` by `h` pixels.
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: [u8; 4]) {
+        for py in y.max(0)..(y + h).min(self.height as i32) {
+            for px in x.max(0)..(x + w).min(self.width as i32) {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    /// Strokes a segment `width` pixels wide, coordinates in pixels with
+    /// (0, 0) at the top-left corner of the image.
+    ///
+    /// Walks the segment's major axis and only visits pixels within reach of
+    /// it, so full-image lines on large renders stay cheap at any angle.
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, color: [u8; 4]) {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = dx.hypot(dy);
+        if length == 0.0 {
+            return;
+        }
+        let half = width / 2.0;
+        let steep = dy.abs() > dx.abs();
+        // Work in (major, minor) coordinates and swap back when blending
+        let (a, b, da, db) = if steep {
+            (from.1, from.0, dy, dx)
+        } else {
+            (from.0, from.1, dx, dy)
+        };
+        let (major_len, minor_len) = if steep {
+            (self.height as i32, self.width as i32)
+        } else {
+            (self.width as i32, self.height as i32)
+        };
+        // Minor-axis reach of the stroke from its center line
+        let reach = half * length / da.abs() + 1.0;
+
+        let lo = (a.min(a + da) - half - 1.0).floor().max(0.0) as i32;
+        let hi = ((a.max(a + da) + half + 1.0).ceil() as i32).min(major_len - 1);
+        for m in lo..=hi {
+            let center = b + (m as f32 + 0.5 - a) * db / da;
+            let n_lo = ((center - reach).floor() as i32).max(0);
+            let n_hi = ((center + reach).ceil() as i32).min(minor_len - 1);
+            for n in n_lo..=n_hi {
+                let (px, py) = if steep { (n, m) } else { (m, n) };
+                let distance = segment_distance((px as f32 + 0.5, py as f32 + 0.5), from, to);
+                let coverage = half + 0.5 - distance;
+                if coverage > 0.0 {
+                    self.blend(px, py, color, coverage);
+                }
+            }
+        }
+    }
+
+    /// Fills a simple polygon in pixel coordinates, antialiased by 4x4
+    /// supersampling. Meant for small shapes like icons and arrows.
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)], color: [u8; 4]) {
+        if points.len() < 3 {
+            return;
+        }
+        let x1 = points.iter().map(|p| p.0).fold(f32::INFINITY, f32::min).floor().max(0.0) as i32;
+        let y1 = points.iter().map(|p| p.1).fold(f32::INFINITY, f32::min).floor().max(0.0) as i32;
+        let x2 = (points.iter().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max).ceil() as i32).min(self.width as i32);
+        let y2 = (points.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max).ceil() as i32).min(self.height as i32);
+        for py in y1..y2 {
+            for px in x1..x2 {
+                let mut inside = 0;
+                for sy in 0..4 {
+                    for sx in 0..4 {
+                        let sample = (px as f32 + (sx as f32 + 0.5) / 4.0, py as f32 + (sy as f32 + 0.5) / 4.0);
+                        inside += contains(points, sample) as u32;
+                    }
+                }
+                self.blend(px, py, color, inside as f32 / 16.0);
+            }
+        }
+    }
+
+    /// Fills a circle in pixel coordinates.
+    pub fn fill_circle(&mut self, center: (f32, f32), radius: f32, color: [u8; 4]) {
+        let x1 = (center.0 - radius - 1.0).floor() as i32;
+        let y1 = (center.1 - radius - 1.0).floor() as i32;
+        let x2 = (center.0 + radius + 1.0).ceil() as i32;
+        let y2 = (center.1 + radius + 1.0).ceil() as i32;
+        for py in y1..y2 {
+            for px in x1..x2 {
+                let distance = (px as f32 + 0.5 - center.0).hypot(py as f32 + 0.5 - center.1);
+                self.blend(px, py, color, radius + 0.5 - distance);
+            }
+        }
+    }
+}
+
+fn segment_distance(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / (dx * dx + dy * dy)).clamp(0.0, 1.0);
+    (p.0 - a.0 - t * dx).hypot(p.1 - a.1 - t * dy)
+}
+
+// Even-odd point in polygon
+fn contains(points: &[(f32, f32)], p: (f32, f32)) -> bool {
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        if (a.1 > p.1) != (b.1 > p.1) && p.0 < (b.0 - a.0) * (p.1 - a.1) / (b.1 - a.1) + a.0 {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// A typeface for overlay text. The `bundled-font` feature builds in DejaVu
+/// Sans Bold, so text renders without relying on system fonts; builds
+/// without it, like the website's, load the same file when they need text.
+#[derive(Clone)]
+pub struct Font {
+    font: FontArc,
+}
+
+#[cfg(feature = "bundled-font")]
+impl Default for Font {
+    fn default() -> Font {
+        Font::from_bytes(BUNDLED_FONT.to_vec()).expect("bundled font is valid")
+    }
+}
+
+impl Font {
+    /// Loads a TrueType or OpenType font.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Font, String> {
+        let font = FontArc::try_from_vec(data).map_err(|e| format!("Error reading font: {}", e))?;
+        Ok(Font { font })
+    }
+
+    /// Width and height in pixels of one line of `text` set at `size` px.
+    pub fn measure(&self, text: &str, size: f32) -> (f32, f32) {
+        let (_, width) = self.layout(text, size);
+        let scaled = self.font.as_scaled(PxScale::from(size));
+        (width, scaled.ascent() - scaled.descent())
+    }
+
+    /// Draws one line of `text` with the top-left of its line box at `at`.
+    pub fn draw(&self, raster: &mut Raster, text: &str, size: f32, at: (f32, f32), color: [u8; 4]) {
+        let (glyphs, _) = self.layout(text, size);
+        for mut glyph in glyphs {
+            glyph.position.x += at.0;
+            glyph.position.y += at.1;
+            if let Some(outline) = self.font.outline_glyph(glyph) {
+                let bounds = outline.px_bounds();
+                outline.draw(|x, y, coverage| {
+                    raster.blend(bounds.min.x as i32 + x as i32, bounds.min.y as i32 + y as i32, color, coverage);
+                });
+            }
+        }
+    }
+
+    /// Like [`draw`](Self::draw), over a `halo` colored outline that keeps
+    /// text legible on any map.
+    pub fn draw_haloed(&self, raster: &mut Raster, text: &str, size: f32, at: (f32, f32), color: [u8; 4], halo: [u8; 4]) {
+        let r = (size / 8.0).max(1.0);
+        for (ox, oy) in [(-r, 0.0), (r, 0.0), (0.0, -r), (0.0, r), (-r, -r), (r, -r), (-r, r), (r, r)] {
+            self.draw(raster, text, size, (at.0 + ox, at.1 + oy), halo);
+        }
+        self.draw(raster, text, size, at, color);
+    }
+
+    // Glyphs positioned along a baseline one ascent below the origin, and
+    // the total advance
+    fn layout(&self, text: &str, size: f32) -> (Vec<Glyph>, f32) {
+        let scaled = self.font.as_scaled(PxScale::from(size));
+        let mut glyphs = Vec::with_capacity(text.len());
+        let mut caret = 0.0;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = scaled.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += scaled.kern(previous, id);
+            }
+            glyphs.push(id.with_scale_and_position(size, ab_glyph::point(caret, scaled.ascent())));
+            caret += scaled.h_advance(id);
+            previous = Some(id);
+        }
+        (glyphs, caret)
+    }
+}
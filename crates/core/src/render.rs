@@ -1,10 +1,25 @@
-use crate::graphics::VERTEX_STRIDE;
+use std::str::FromStr;
+
+use crate::bricks::OUTLINE_THICKNESS;
+use crate::color::parse_hex;
+use crate::graphics::{EXTRUDE_RANGE, VERTEX_STRIDE};
+use crate::m3;
 
 // Matches the old WebGL default of antialias: true (typically 4x MSAA)
 const MSAA_SAMPLE_COUNT: u32 = 4;
 
-// mat3x3<f32> in uniform space: 3 columns, each padded to 16 bytes
-const UNIFORM_BUFFER_SIZE: u64 = 48;
+// Only projected views write depth; top-down geometry sits at the far plane
+// and draws in batch order. Depth24Plus multisamples on every backend,
+// WebGL2 included.
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24Plus;
+
+// Uniforms struct: mat3x3<f32> as 3 columns each padded to 16 bytes, then
+// the outline width, with the struct rounded up to 16-byte alignment
+const UNIFORM_BUFFER_SIZE: u64 = 64;
+
+// Background uniforms: the inverse view matrix (3 padded columns), the two
+// pattern colors, then (kind, cell size, pixel size, unused)
+const BACKGROUND_BUFFER_SIZE: u64 = 96;
 
 // Largest offscreen tile we ask for, regardless of what the device claims to
 // allow. Reported limits are theoretical maxima the driver won't necessarily
@@ -14,8 +29,15 @@ const UNIFORM_BUFFER_SIZE: u64 = 48;
 // device can manage, and larger images simply use more tiles.
 const MAX_TILE_DIM: u32 = 4096;
 
+// EXTRUDE_RANGE is spliced in at pipeline creation
 const SHADER_CODE: &str = r#"
-    @group(0) @binding(0) var<uniform> u_matrix: mat3x3<f32>;
+    struct Uniforms {
+        matrix: mat3x3<f32>,
+        // Outline width in save units
+        outline_width: f32,
+    };
+
+    @group(0) @binding(0) var<uniform> u: Uniforms;
 
     struct VertexOutput {
         @builtin(position) position: vec4<f32>,
@@ -23,9 +45,15 @@ const SHADER_CODE: &str = r#"
     };
 
     @vertex
-    fn vs_main(@location(0) position: vec2<f32>, @location(1) color: vec4<f32>) -> VertexOutput {
+    fn vs_main(
+        @location(0) position: vec2<f32>,
+        @location(1) extrude: vec2<f32>,
+        @location(2) color: vec4<f32>,
+        @location(3) depth: f32,
+    ) -> VertexOutput {
         var out: VertexOutput;
-        out.position = vec4<f32>((u_matrix * vec3<f32>(position, 1.0)).xy, 0.0, 1.0);
+        let world = position + extrude * (EXTRUDE_RANGE * u.outline_width);
+        out.position = vec4<f32>((u.matrix * vec3<f32>(world, 1.0)).xy, depth, 1.0);
         out.color = color;
         return out;
     }
@@ -36,12 +64,143 @@ const SHADER_CODE: &str = r#"
     }
 "#;
 
+// Patterned backgrounds: one triangle covering the target, with each pixel's
+// world position interpolated from the inverse view transform so the pattern
+// stays put under panning and lines up across tiles
+const BACKGROUND_SHADER_CODE: &str = r#"
+    struct Background {
+        inverse: mat3x3<f32>,
+        a: vec4<f32>,
+        b: vec4<f32>,
+        // x: 0 checkerboard, 1 grid; y: cell size in save units;
+        // z: one pixel in save units
+        params: vec4<f32>,
+    };
+
+    @group(0) @binding(0) var<uniform> bg: Background;
+
+    struct VertexOutput {
+        @builtin(position) position: vec4<f32>,
+        @location(0) world: vec2<f32>,
+    };
+
+    @vertex
+    fn vs_main(@builtin(vertex_index) i: u32) -> VertexOutput {
+        let clip = vec2<f32>(f32((i & 1u) << 2u) - 1.0, f32((i & 2u) << 1u) - 1.0);
+        var out: VertexOutput;
+        out.position = vec4<f32>(clip, 0.0, 1.0);
+        out.world = (bg.inverse * vec3<f32>(clip, 1.0)).xy;
+        return out;
+    }
+
+    @fragment
+    fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+        let size = bg.params.y;
+        if bg.params.x < 0.5 {
+            let cell = floor(in.world / size);
+            let parity = abs(cell.x + cell.y) % 2.0;
+            return select(bg.a, bg.b, parity > 0.5);
+        }
+        let d = abs(in.world - round(in.world / size) * size);
+        return select(bg.a, bg.b, min(d.x, d.y) < bg.params.z * 0.5);
+    }
+"#;
+
+/// What a render target shows where no brick covers it. Patterns are laid out
+/// in save units, so they pan and zoom with the map and stay continuous
+/// across the tiles of a large render.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Background {
+    #[default]
+    Transparent,
+    Solid([u8; 4]),
+    /// Alternating `a`/`b` squares `cell` save units wide
+    Checkerboard { a: [u8; 4], b: [u8; 4], cell: f32 },
+    /// One pixel wide `line`s every `spacing` save units over `fill`
+    Grid { fill: [u8; 4], line: [u8; 4], spacing: f32 },
+}
+
+impl Background {
+    // Default pattern sizes, in studs (10 save units each)
+    const CHECKER_STUDS: f32 = 4.0;
+    const GRID_STUDS: f32 = 10.0;
+
+    fn clear_color(&self) -> wgpu::Color {
+        match self {
+            Background::Solid(c) => wgpu::Color {
+                r: c[0] as f64 / 255.0,
+                g: c[1] as f64 / 255.0,
+                b: c[2] as f64 / 255.0,
+                a: c[3] as f64 / 255.0,
+            },
+            _ => wgpu::Color::TRANSPARENT,
+        }
+    }
+}
+
+/// Parses `transparent`, a `#rrggbb[aa]` color, or `checker` / `grid` with an
+/// optional `:<studs>` cell size, e.g. `grid:32`.
+impl FromStr for Background {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Background, String> {
+        let (kind, studs) = match s.split_once(':') {
+            Some((kind, studs)) => {
+                let studs: f32 = studs
+                    .parse()
+                    .ok()
+                    .filter(|n: &f32| n.is_finite() && *n > 0.0)
+                    .ok_or_else(|| format!("invalid pattern size '{}', expected a positive number of studs", studs))?;
+                (kind, Some(studs))
+            }
+            None => (s, None),
+        };
+        match kind.to_ascii_lowercase().as_str() {
+            "transparent" if studs.is_none() => Ok(Background::Transparent),
+            "checker" | "checkerboard" => Ok(Background::Checkerboard {
+                a: [0xff, 0xff, 0xff, 0xff],
+                b: [0xcc, 0xcc, 0xcc, 0xff],
+                cell: studs.unwrap_or(Self::CHECKER_STUDS) * 10.0,
+            }),
+            "grid" => Ok(Background::Grid {
+                fill: [0xff, 0xff, 0xff, 0xff],
+                line: [0xc8, 0xc8, 0xc8, 0xff],
+                spacing: studs.unwrap_or(Self::GRID_STUDS) * 10.0,
+            }),
+            _ if kind.starts_with('#') && studs.is_none() => parse_hex(kind).map(Background::Solid),
+            _ => Err(format!(
+                "invalid background '{}', expected transparent, #rrggbb, checker[:studs] or grid[:studs]",
+                s
+            )),
+        }
+    }
+}
+
+/// View scales (pixels per world unit) a batch draws at, `min` inclusive and
+/// `max` exclusive. Detail geometry and its coarser levels of detail cover
+/// the same bricks over disjoint ranges, so exactly one of them draws.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScaleRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ScaleRange {
+    pub const ALL: ScaleRange = ScaleRange { min: 0.0, max: f32::INFINITY };
+
+    pub fn contains(&self, scale: f32) -> bool {
+        scale >= self.min && scale < self.max
+    }
+}
+
 struct Batch {
     // Draw-order key; batches render in ascending key order (map layers,
     // bottom first). Ties keep upload order.
     key: i32,
     // World-space xy AABB of the contained geometry, for viewport culling
     bounds: (f32, f32, f32, f32),
+    // Level-of-detail selection
+    scales: ScaleRange,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     index_count: u32,
@@ -68,6 +227,29 @@ fn batch_visible(matrix: &[f32; 9], bounds: (f32, f32, f32, f32)) -> bool {
         || clip.iter().all(|c| c.1 > 1.0))
 }
 
+/// How wide outlines draw. World widths scale with zoom like the bricks do;
+/// pixel widths stay the same on screen at any zoom.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OutlineWidth {
+    World(f32),
+    Pixels(f32),
+}
+
+impl Default for OutlineWidth {
+    fn default() -> OutlineWidth {
+        OutlineWidth::World(OUTLINE_THICKNESS)
+    }
+}
+
+// Pixels per world unit under the view transform for a target of this width
+// and height. Rotation mixes the axes, so this is the length of the
+// transformed unit x vector in pixels.
+fn view_scale(matrix: &[f32; 9], width: u32, height: u32) -> f32 {
+    let px = matrix[0] * width as f32 / 2.0;
+    let py = matrix[1] * height as f32 / 2.0;
+    px.hypot(py)
+}
+
 pub struct Renderer {
     // Kept alive deliberately: on the browser backend, dropping the Instance
     // aborts every later buffer mapAsync with "A valid external Instance
@@ -78,13 +260,19 @@ pub struct Renderer {
     pipeline: wgpu::RenderPipeline,
     uniform_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
+    background_pipeline: wgpu::RenderPipeline,
+    background_buffer: wgpu::Buffer,
+    background_bind_group: wgpu::BindGroup,
     format: wgpu::TextureFormat,
     sample_count: u32,
     surface: Option<wgpu::Surface<'static>>,
     surface_config: Option<wgpu::SurfaceConfiguration>,
-    // MSAA color target matching the surface size, recreated on resize
+    // MSAA color and depth targets matching the surface size, recreated on
+    // resize
     msaa_texture: Option<wgpu::Texture>,
+    depth_texture: Option<wgpu::Texture>,
     batches: Vec<Batch>,
+    outline_width: OutlineWidth,
     max_texture_dim: u32,
     max_buffer_size: u64,
 }
@@ -199,7 +387,9 @@ impl Renderer {
 
         let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
-            source: wgpu::ShaderSource::Wgsl(SHADER_CODE.into()),
+            source: wgpu::ShaderSource::Wgsl(
+                SHADER_CODE.replace("EXTRUDE_RANGE", &format!("{:.1}", EXTRUDE_RANGE)).into(),
+            ),
         });
 
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -219,10 +409,20 @@ impl Renderer {
                             shader_location: 0,
                         },
                         wgpu::VertexAttribute {
-                            format: wgpu::VertexFormat::Unorm8x4,
+                            format: wgpu::VertexFormat::Float32,
                             offset: 8,
+                            shader_location: 3,
+                        },
+                        wgpu::VertexAttribute {
+                            format: wgpu::VertexFormat::Snorm16x2,
+                            offset: 12,
                             shader_location: 1,
                         },
+                        wgpu::VertexAttribute {
+                            format: wgpu::VertexFormat::Unorm8x4,
+                            offset: 16,
+                            shader_location: 2,
+                        },
                     ],
                 })],
             },
@@ -237,7 +437,13 @@ impl Renderer {
                 })],
             }),
             primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: Some(true),
+                depth_compare: Some(wgpu::CompareFunction::LessEqual),
+                stencil: Default::default(),
+                bias: Default::default(),
+            }),
             multisample: wgpu::MultisampleState {
                 count: sample_count,
                 ..Default::default()
@@ -262,6 +468,63 @@ impl Renderer {
             }],
         });
 
+        let background_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(BACKGROUND_SHADER_CODE.into()),
+        });
+
+        let background_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &background_module,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &background_module,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // Beneath everything, whatever the depth
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: Some(false),
+                depth_compare: Some(wgpu::CompareFunction::Always),
+                stencil: Default::default(),
+                bias: Default::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview_mask: None,
+            cache: None,
+        });
+
+        let background_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: BACKGROUND_BUFFER_SIZE,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let background_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &background_pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: background_buffer.as_entire_binding(),
+            }],
+        });
+
         Ok(Renderer {
             _instance: instance,
             device,
@@ -269,12 +532,17 @@ impl Renderer {
             pipeline,
             uniform_buffer,
             bind_group,
+            background_pipeline,
+            background_buffer,
+            background_bind_group,
             format,
             sample_count,
             surface,
             surface_config,
             msaa_texture: None,
+            depth_texture: None,
             batches: Vec::new(),
+            outline_width: OutlineWidth::default(),
             max_texture_dim,
             max_buffer_size,
         })
@@ -299,15 +567,23 @@ impl Renderer {
         bytes_per_row as u64 * height as u64
     }
 
-    /// Uploads a geometry batch. `key` is the ascending draw-order key and
-    /// `bounds` the world-space xy AABB used for viewport culling.
-    pub fn upload_batch(&mut self, key: i32, bounds: (f32, f32, f32, f32), vertices: &[u8], indices: &[u32]) {
+    /// Uploads a geometry batch. `key` is the ascending draw-order key,
+    /// `bounds` the world-space xy AABB used for viewport culling, and
+    /// `scales` the view scales it draws at.
+    pub fn upload_batch(
+        &mut self,
+        key: i32,
+        bounds: (f32, f32, f32, f32),
+        scales: ScaleRange,
+        vertices: &[u8],
+        indices: &[u32],
+    ) {
         // Upload via write_buffer, not a mapped-at-creation buffer: on the
         // browser backends wgpu shadows every mapped range with a wasm-heap
         // copy of the whole buffer, which for large builds spikes wasm memory
         // by the chunk size per upload. write_buffer hands the browser the
         // wasm slice directly with no allocation. Both slices are already
-        // 4-byte-sized (stride 12 / u32), as writeBuffer requires.
+        // 4-byte-sized (stride 20 / u32), as writeBuffer requires.
         //
         // The u32 indices are viewed as bytes in place (all supported targets
         // are little-endian).
@@ -335,12 +611,17 @@ impl Renderer {
         self.batches.insert(at, Batch {
             key,
             bounds,
+            scales,
             vertex_buffer,
             index_buffer,
             index_count: indices.len() as u32,
         });
     }
 
+    pub fn set_outline_width(&mut self, width: OutlineWidth) {
+        self.outline_width = width;
+    }
+
     pub fn clear_batches(&mut self) {
         for batch in self.batches.drain(..) {
             batch.vertex_buffer.destroy();
@@ -348,20 +629,36 @@ impl Renderer {
         }
     }
 
-    fn write_uniform(&self, matrix: &[f32; 9]) {
-        // Expand column-major 3x3 to WGSL uniform layout: vec3 columns padded
-        // to 16 bytes
+    fn write_uniform(&self, matrix: &[f32; 9], scale: f32) {
         let mut bytes = [0u8; UNIFORM_BUFFER_SIZE as usize];
-        for col in 0..3 {
-            for row in 0..3 {
-                let offset = col * 16 + row * 4;
-                bytes[offset..offset + 4]
-                    .copy_from_slice(&matrix[col * 3 + row].to_le_bytes());
-            }
-        }
+        write_matrix(&mut bytes, matrix);
+        let outline_width = match self.outline_width {
+            OutlineWidth::World(width) => width,
+            OutlineWidth::Pixels(px) => px / scale.max(f32::EPSILON),
+        };
+        bytes[48..52].copy_from_slice(&outline_width.to_le_bytes());
         self.queue.write_buffer(&self.uniform_buffer, 0, &bytes);
     }
 
+    // Queues the pattern uniforms, or returns false when the background is
+    // just a clear color
+    fn write_background(&self, background: &Background, matrix: &[f32; 9], scale: f32) -> bool {
+        let (kind, a, b, size) = match *background {
+            Background::Checkerboard { a, b, cell } => (0.0, a, b, cell),
+            Background::Grid { fill, line, spacing } => (1.0, fill, line, spacing),
+            Background::Transparent | Background::Solid(_) => return false,
+        };
+        let mut bytes = [0u8; BACKGROUND_BUFFER_SIZE as usize];
+        write_matrix(&mut bytes, &m3::inverse(*matrix));
+        let params = [kind, size, 1.0 / scale.max(f32::EPSILON), 0.0];
+        let colors = [a, b].map(|c| c.map(|v| v as f32 / 255.0));
+        for (i, value) in colors.iter().flatten().chain(&params).enumerate() {
+            bytes[48 + i * 4..52 + i * 4].copy_from_slice(&value.to_le_bytes());
+        }
+        self.queue.write_buffer(&self.background_buffer, 0, &bytes);
+        true
+    }
+
     fn create_target_texture(&self, width: u32, height: u32, sample_count: u32, usage: wgpu::TextureUsages) -> wgpu::Texture {
         self.device.create_texture(&wgpu::TextureDescriptor {
             label: None,
@@ -375,16 +672,38 @@ impl Renderer {
         })
     }
 
-    // Encodes one pass drawing all chunks into `target`, multisampled when
-    // the device supports it. The uniform write is queued first.
+    fn create_depth_texture(&self, width: u32, height: u32) -> wgpu::Texture {
+        self.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: self.sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+    }
+
+    // Encodes one pass drawing the background and all chunks into `target`,
+    // multisampled when the device supports it. Uniform writes are queued
+    // first. `size` is the target's pixel size, which picks each batch's
+    // level of detail.
+    #[allow(clippy::too_many_arguments)]
     fn encode_render_pass(
         &self,
         encoder: &mut wgpu::CommandEncoder,
         msaa_view: Option<&wgpu::TextureView>,
         target_view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        size: (u32, u32),
         matrix: &[f32; 9],
+        background: &Background,
     ) {
-        self.write_uniform(matrix);
+        let scale = view_scale(matrix, size.0, size.1);
+        self.write_uniform(matrix, scale);
+        let patterned = self.write_background(background, matrix, scale);
+        let clear = background.clear_color();
 
         let attachment = match msaa_view {
             Some(msaa_view) => wgpu::RenderPassColorAttachment {
@@ -392,7 +711,7 @@ impl Renderer {
                 depth_slice: None,
                 resolve_target: Some(target_view),
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    load: wgpu::LoadOp::Clear(clear),
                     store: wgpu::StoreOp::Discard,
                 },
             },
@@ -401,7 +720,7 @@ impl Renderer {
                 depth_slice: None,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    load: wgpu::LoadOp::Clear(clear),
                     store: wgpu::StoreOp::Store,
                 },
             },
@@ -410,16 +729,29 @@ impl Renderer {
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(attachment)],
-            depth_stencil_attachment: None,
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
             timestamp_writes: None,
             occlusion_query_set: None,
             multiview_mask: None,
         });
 
+        if patterned {
+            pass.set_pipeline(&self.background_pipeline);
+            pass.set_bind_group(0, &self.background_bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+
         pass.set_pipeline(&self.pipeline);
         pass.set_bind_group(0, &self.bind_group, &[]);
         for batch in &self.batches {
-            if !batch_visible(matrix, batch.bounds) {
+            if !batch.scales.contains(scale) || !batch_visible(matrix, batch.bounds) {
                 continue;
             }
             pass.set_vertex_buffer(0, batch.vertex_buffer.slice(..));
@@ -430,7 +762,13 @@ impl Renderer {
 
     /// Draws to the presentation surface. `width`/`height` must match the
     /// canvas/window size; the surface is reconfigured when they change.
-    pub fn render_to_surface(&mut self, width: u32, height: u32, matrix: &[f32; 9]) -> Result<(), String> {
+    pub fn render_to_surface(
+        &mut self,
+        width: u32,
+        height: u32,
+        matrix: &[f32; 9],
+        background: &Background,
+    ) -> Result<(), String> {
         if width == 0 || height == 0 {
             return Ok(());
         }
@@ -442,6 +780,7 @@ impl Renderer {
             config.height = height;
             surface.configure(&self.device, config);
             self.msaa_texture = None;
+            self.depth_texture = None;
         }
 
         use wgpu::CurrentSurfaceTexture as Cst;
@@ -472,10 +811,18 @@ impl Renderer {
             .msaa_texture
             .as_ref()
             .map(|t| t.create_view(&wgpu::TextureViewDescriptor::default()));
+        if self.depth_texture.is_none() {
+            self.depth_texture = Some(self.create_depth_texture(width, height));
+        }
+        let depth_view = self
+            .depth_texture
+            .as_ref()
+            .map(|t| t.create_view(&wgpu::TextureViewDescriptor::default()))
+            .expect("depth texture was just created");
 
         let frame_view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
-        self.encode_render_pass(&mut encoder, msaa_view.as_ref(), &frame_view, matrix);
+        self.encode_render_pass(&mut encoder, msaa_view.as_ref(), &frame_view, &depth_view, (width, height), matrix, background);
         self.queue.submit([encoder.finish()]);
         self.queue.present(frame);
 
@@ -491,6 +838,7 @@ impl Renderer {
         width: u32,
         height: u32,
         matrix: &[f32; 9],
+        background: &Background,
     ) -> Result<PixelReadback, String> {
         if width == 0 || height == 0 {
             return Err(format!("invalid render size {}x{}", width, height));
@@ -538,9 +886,12 @@ impl Renderer {
             .as_ref()
             .map(|t| t.create_view(&wgpu::TextureViewDescriptor::default()));
         let resolve_view = resolve_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = self
+            .create_depth_texture(width, height)
+            .create_view(&wgpu::TextureViewDescriptor::default());
 
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
-        self.encode_render_pass(&mut encoder, msaa_view.as_ref(), &resolve_view, matrix);
+        self.encode_render_pass(&mut encoder, msaa_view.as_ref(), &resolve_view, &depth_view, (width, height), matrix, background);
         encoder.copy_texture_to_buffer(
             wgpu::TexelCopyTextureInfo {
                 texture: &resolve_texture,
@@ -579,6 +930,17 @@ impl Renderer {
     }
 }
 
+// Expands a column-major 3x3 into the start of a WGSL uniform buffer: vec3
+// columns padded to 16 bytes
+fn write_matrix(bytes: &mut [u8], matrix: &[f32; 9]) {
+    for col in 0..3 {
+        for row in 0..3 {
+            let offset = col * 16 + row * 4;
+            bytes[offset..offset + 4].copy_from_slice(&matrix[col * 3 + row].to_le_bytes());
+        }
+    }
+}
+
 /// An in-flight offscreen readback. On the WebGL backend, mapping callbacks
 /// only fire while the device is polled, so consumers that can't block must
 /// pump: call [`poll`](Self::poll) then [`try_finish`](Self::try_finish),
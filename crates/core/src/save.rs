@@ -1,16 +1,23 @@
 use crate::brick::Brick;
 use crate::color::*;
 use crate::bricks::*;
-use crate::graphics::push_shape;
+use crate::graphics::{push_outline, push_projected, push_shape};
+use crate::labels::{component_text, Label};
 use crate::m3;
-use crate::render::Renderer;
+use crate::plan;
+use crate::markers::{brick_markers, Marker};
+use crate::merge::{merge_rects, Edge, Rect};
+use crate::projection::{Oblique, Projector};
+use crate::render::{Renderer, ScaleRange};
 use crate::util;
 use crate::world_load;
 
-use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 use brickadia::read::SaveReader;
-use brickadia::save::{Rotation, Direction, BrickColor};
+use brickadia::save::{Rotation, Direction};
 
 // Geometry batches flush to the GPU around this many indices. Batch AABBs are
 // what viewport culling skips, so this is the culling granularity; smaller
@@ -25,6 +32,17 @@ const CULL_MAX_GRID_DIM: i32 = 4096;
 // Outlines extend past brick footprints; batch AABBs pad by this much
 const AABB_PAD: f32 = 1.0;
 
+// Region outlines look up same-region fills of neighboring chunks through
+// buckets of the plane this many save units square
+const REGION_BUCKET: i32 = 160;
+
+// Coarse levels of detail as (cell size in save units, view scale below which
+// the level replaces the finer one), finest first. Each switches in where its
+// cells shrink to about a pixel, so the swap is invisible.
+const LOD_LEVELS: [(i32, f32); 2] = [(20, 0.05), (80, 0.0125)];
+
+// Identical bricks sharing a footprint. Top-down maps keep only the topmost;
+// copies stacked at other heights are set aside for projected views.
 #[derive(PartialEq, Eq, Hash)]
 struct BrickShape {
     name_index: u32,
@@ -46,10 +64,93 @@ impl BrickShape {
     }
 }
 
+// What loading does with a brick
+#[derive(Clone, Copy)]
+enum Fate {
+    Keep,
+    // Set aside as a copy stacked under an identical brick
+    Stack,
+    Drop,
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum GeometryMode {
-    Map { outlines: bool, fills: bool },
+    Map {
+        fills: bool,
+        /// Greedily merge abutting rectangular bricks that share a color and
+        /// top surface, cutting flat floors of small plates down to a few
+        /// large quads
+        merge: bool,
+        outlines: Option<OutlineStyle>,
+        /// Draw studs and bevels over the fills of studded bricks, for
+        /// close-up renders that look like bricks
+        studs: bool,
+    },
     Heightmap,
+    /// An architectural floor plan of the floor at height `floor`: walls
+    /// standing on it as merged outlines, everything else as light fills
+    Plan { floor: i32 },
+}
+
+/// Which bricks [`SaveData::build_geometry_within`] builds and how they're
+/// seen. The default is every brick, top-down.
+#[derive(Clone, Copy, Default)]
+pub struct BuildOptions {
+    /// Only bricks reaching into this region (absolute save units, x1, y1,
+    /// x2, y2) get geometry, and chunks wholly outside it aren't visited
+    pub region: Option<Rect>,
+    /// Only bricks with their top surface between these (low inclusive,
+    /// high exclusive) get geometry
+    pub heights: Option<(i32, i32)>,
+    /// Draws bricks as boxes seen from this angle
+    pub oblique: Option<Oblique>,
+    /// Maps the build from below
+    pub underside: bool,
+}
+
+/// How brick outlines are drawn. Their width is a draw-time setting on the
+/// [`Renderer`], so zooming never needs a geometry rebuild.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct OutlineStyle {
+    pub color: OutlineColor,
+    /// Outline only the boundaries between regions of differing color or top
+    /// surface rather than every brick. Shaped bricks still outline
+    /// individually, since they never merge into a region.
+    pub regions: bool,
+}
+
+impl Default for OutlineStyle {
+    fn default() -> OutlineStyle {
+        OutlineStyle { color: OutlineColor::Solid(Color::black().to_bytes()), regions: false }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OutlineColor {
+    Solid([u8; 4]),
+    /// Each brick's own fill color, darkened
+    Darkened,
+}
+
+impl OutlineColor {
+    fn resolve(&self, fill: [u8; 4]) -> [u8; 4] {
+        match self {
+            OutlineColor::Solid(color) => *color,
+            OutlineColor::Darkened => darken(fill),
+        }
+    }
+}
+
+impl std::str::FromStr for OutlineColor {
+    type Err = String;
+
+    /// `darken` or a hex color like `#202020`
+    fn from_str(s: &str) -> Result<OutlineColor, String> {
+        match s {
+            "darken" => Ok(OutlineColor::Darkened),
+            _ => parse_hex(s).map(OutlineColor::Solid),
+        }
+    }
 }
 
 // One spatial chunk of the save. brdb worlds provide these natively; other
@@ -59,16 +160,24 @@ struct MapChunk {
     // can draw out of height order against the neighboring layer — the price
     // of chunked drawing.
     layer: i16,
+    // Absolute extent of the chunk's brick footprints
+    bounds: Rect,
+    // Lowest bottom and highest top surface of its bricks
+    heights: (i32, i32),
     // Sorted by top surface, so draw order within a chunk is exact
     bricks: Vec<Brick>,
+    // Copies of bricks in the same footprint further up, which only
+    // projected views draw
+    stacked: Vec<Brick>,
 }
 
 /// A save's render-ready bricks, grouped into spatial chunks. Populated
 /// incrementally through [`SaveLoading`]; complete data is also available in
 /// one call via [`SaveData::load`].
 pub struct SaveData {
-    // Processing order: top layer first, the order the occlusion grid needs.
-    // Draw order (bottom layer first) comes from batch sort keys.
+    // Processing order: top layer first, the order the occlusion grid needs
+    // (an underside map walks them backwards). Draw order (bottom layer
+    // first) comes from batch sort keys.
     chunks: Vec<MapChunk>,
     pub brick_assets: Vec<String>,
     pub description: String,
@@ -82,10 +191,26 @@ pub struct SaveData {
     // Absolute superset of all brick footprints, fixed up front, sizing the
     // occlusion grid
     grid_bounds: (i32, i32, i32, i32),
-    /// Duplicate bricks dropped during load (same footprint stacked exactly)
+    /// Duplicate bricks left out of top-down maps (same footprint under an
+    /// identical brick). Copies at other heights still show in projected
+    /// views.
     pub discarded: usize,
-    // Cross-chunk duplicate suppression; only lives while loading
-    dedupe: HashSet<BrickShape>,
+    /// Text from visible bricks' components, in world coordinates. Worlds
+    /// only yield those on their main brick grid.
+    pub labels: Vec<Label>,
+    /// Points of interest from any brick, visible or not (lights and spawns
+    /// often sit in hidden bricks). Worlds only yield those on their main
+    /// brick grid.
+    pub markers: Vec<Marker>,
+    /// Names of whoever placed bricks, which [`Brick::owner`] indexes from 1
+    pub owners: Vec<String>,
+    /// Material names, which [`Brick::material`] indexes
+    pub materials: Vec<String>,
+    // Cross-chunk duplicate suppression, with the height and depth of the
+    // first brick in each footprint; only lives while loading
+    dedupe: HashMap<BrickShape, (i32, u16)>,
+    // Heights and depths of stacked copies set aside so far
+    stacks: HashSet<(BrickShape, i32, u16)>,
 }
 
 impl SaveData {
@@ -97,7 +222,7 @@ impl SaveData {
         loading.finish()
     }
 
-    fn new(
+    pub(crate) fn new(
         description: String,
         brick_count: i32,
         centroid: (i32, i32),
@@ -112,22 +237,48 @@ impl SaveData {
             bounds: (i32::MAX, i32::MAX, i32::MIN, i32::MIN),
             grid_bounds,
             discarded: 0,
-            dedupe: HashSet::new(),
+            labels: Vec::new(),
+            markers: Vec::new(),
+            owners: Vec::new(),
+            materials: Vec::new(),
+            dedupe: HashMap::new(),
+            stacks: HashSet::new(),
         }
     }
 
     // Sorts, deduplicates, and stores one chunk's bricks
-    fn push_chunk(&mut self, layer: i16, mut bricks: Vec<Brick>) {
+    pub(crate) fn push_chunk(&mut self, layer: i16, mut bricks: Vec<Brick>) {
         bricks.sort_unstable_by_key(util::top_surface);
 
-        // Walked top-down so the topmost copy — drawn last — survives
-        let mut keep = vec![true; bricks.len()];
+        // Walked top-down so the topmost copy — drawn last — survives. Copies
+        // at other heights are set aside; exact duplicates are dropped.
+        let mut fate = vec![Fate::Keep; bricks.len()];
         for i in (0..bricks.len()).rev() {
-            keep[i] = self.dedupe.insert(BrickShape::of(&bricks[i]));
+            let brick = &bricks[i];
+            let height = (brick.position.2, brick.size.2);
+            fate[i] = match self.dedupe.entry(BrickShape::of(brick)) {
+                Entry::Vacant(entry) => {
+                    entry.insert(height);
+                    Fate::Keep
+                }
+                Entry::Occupied(entry) if *entry.get() == height => Fate::Drop,
+                Entry::Occupied(_) => match self.stacks.insert((BrickShape::of(brick), height.0, height.1)) {
+                    true => Fate::Stack,
+                    false => Fate::Drop,
+                },
+            };
         }
         let before = bricks.len();
-        let mut it = keep.iter();
-        bricks.retain(|_| *it.next().unwrap());
+        let mut kept = Vec::with_capacity(before);
+        let mut stacked = Vec::new();
+        for (brick, fate) in bricks.into_iter().zip(fate) {
+            match fate {
+                Fate::Keep => kept.push(brick),
+                Fate::Stack => stacked.push(brick),
+                Fate::Drop => {}
+            }
+        }
+        let bricks = kept;
         self.discarded += before - bricks.len();
 
         for brick in &bricks {
@@ -138,8 +289,13 @@ impl SaveData {
             self.bounds.3 = self.bounds.3.max(brick.position.1 + size.1 as i32 - self.centroid.1);
         }
 
-        if !bricks.is_empty() {
-            self.chunks.push(MapChunk { layer, bricks });
+        if !bricks.is_empty() || !stacked.is_empty() {
+            let (a, b) = (util::footprint_bounds(&bricks), util::footprint_bounds(&stacked));
+            let bounds = (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3));
+            let heights = bricks.iter().chain(&stacked).fold((i32::MAX, i32::MIN), |(low, high), brick| {
+                (low.min(util::bottom_surface(brick)), high.max(util::top_surface(brick)))
+            });
+            self.chunks.push(MapChunk { layer, bounds, heights, bricks, stacked });
         }
     }
 
@@ -147,6 +303,28 @@ impl SaveData {
         self.chunks.is_empty()
     }
 
+    /// Every loaded brick top-down maps draw, top layer first. Copies stacked
+    /// under an identical brick in the same footprint are left out; see
+    /// [`all_bricks`](Self::all_bricks).
+    pub fn bricks(&self) -> impl Iterator<Item = &Brick> {
+        self.chunks.iter().flat_map(|chunk| chunk.bricks.iter())
+    }
+
+    /// The bricks [`bricks`](Self::bricks) yields from chunks reaching into
+    /// `area` (absolute save units, x1, y1, x2, y2): every one whose
+    /// footprint does, and some nearby.
+    pub fn bricks_near(&self, area: Rect) -> impl Iterator<Item = &Brick> {
+        self.chunks.iter()
+            .filter(move |chunk| overlaps(chunk.bounds, area))
+            .flat_map(|chunk| chunk.bricks.iter())
+    }
+
+    /// Every loaded brick, copies stacked under an identical one included,
+    /// for projected views and anything that counts or measures bricks.
+    pub fn all_bricks(&self) -> impl Iterator<Item = &Brick> {
+        self.chunks.iter().flat_map(|chunk| chunk.bricks.iter().chain(&chunk.stacked))
+    }
+
     /// Screen-space transform for the given viewport, pan (world units),
     /// scale, and rotation, centered on the save's centroid.
     pub fn view_matrix(&self, size_x: f32, size_y: f32, pan_x: f32, pan_y: f32, scale: f32, rotation: f32) -> [f32; 9] {
@@ -161,26 +339,71 @@ impl SaveData {
     /// Rebuilds all geometry for the currently loaded chunks. Returns the
     /// number of bricks skipped by occlusion culling.
     pub fn build_geometry(&self, mode: GeometryMode, renderer: &mut Renderer) -> Result<usize, String> {
+        self.build_geometry_within(mode, &BuildOptions::default(), renderer)
+    }
+
+    /// Like [`build_geometry`](Self::build_geometry), limited and viewed as
+    /// `options` describe.
+    pub fn build_geometry_within(&self, mode: GeometryMode, options: &BuildOptions, renderer: &mut Renderer) -> Result<usize, String> {
         renderer.clear_batches();
         let mut state = GeometryState::new(self, mode);
+        state.set_region(options.region);
+        state.set_heights(options.heights);
+        state.set_projection(self, options.oblique);
+        state.set_underside(options.underside);
         state.build_pending(self, renderer)?;
         state.flush(renderer);
         Ok(state.culled)
     }
 
-    fn height_extent(&self) -> (i32, i32) {
-        let mut min_height = i32::MAX;
-        let mut max_height = i32::MIN;
-        for chunk in &self.chunks {
-            for brick in &chunk.bricks {
-                let size = util::sizer(brick);
-                let top = brick.position.2 + size.2 as i32;
-                let bot = brick.position.2 - size.2 as i32;
-                min_height = std::cmp::min(min_height, bot);
-                max_height = std::cmp::max(max_height, top);
-            }
+    /// Lowest bottom and highest top surface of the loaded bricks.
+    pub fn height_extent(&self) -> (i32, i32) {
+        self.chunks.iter().fold((i32::MAX, i32::MIN), |(low, high), chunk| {
+            (low.min(chunk.heights.0), high.max(chunk.heights.1))
+        })
+    }
+}
+
+/// The save formats Brick Cartographer reads.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SaveFormat {
+    /// Legacy single-file saves
+    Brs,
+    /// Zstd-compressed world archives
+    Brz,
+    /// World databases (SQLite)
+    Brdb,
+}
+
+impl SaveFormat {
+    /// Format of a save's contents. Anything unrecognized is assumed to be
+    /// .brs, whose reader reports a proper error if it isn't.
+    pub fn detect(body: &[u8]) -> SaveFormat {
+        if body.starts_with(b"BRZ") {
+            SaveFormat::Brz
+        } else if body.starts_with(b"SQLite format 3\0") {
+            SaveFormat::Brdb
+        } else {
+            SaveFormat::Brs
+        }
+    }
+
+    /// Format named by a path's extension, if it's one of ours.
+    pub fn from_path(path: &Path) -> Option<SaveFormat> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "brs" => Some(SaveFormat::Brs),
+            "brz" => Some(SaveFormat::Brz),
+            "brdb" => Some(SaveFormat::Brdb),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SaveFormat::Brs => "brs",
+            SaveFormat::Brz => "brz",
+            SaveFormat::Brdb => "brdb",
         }
-        (min_height, max_height)
     }
 }
 
@@ -200,12 +423,10 @@ pub struct SaveLoading {
 
 impl SaveLoading {
     pub fn open(body: &[u8]) -> Result<SaveLoading, String> {
-        if body.starts_with(b"BRZ") {
-            Ok(Self::streamed(world_load::open_brz(body)?))
-        } else if body.starts_with(b"SQLite format 3\0") {
-            Ok(Self::streamed(world_load::open_brdb(body)?))
-        } else {
-            Self::open_brs(body)
+        match SaveFormat::detect(body) {
+            SaveFormat::Brz => Ok(Self::streamed(world_load::open_brz(body)?)),
+            SaveFormat::Brdb => Ok(Self::streamed(world_load::open_brdb(body)?)),
+            SaveFormat::Brs => Self::open_brs(body),
         }
     }
 
@@ -214,7 +435,8 @@ impl SaveLoading {
         // Center the view on the chunk extent; the true bounds aren't known
         // until every chunk has streamed in
         let centroid = ((gb.0 + gb.2) / 2, (gb.1 + gb.3) / 2);
-        let save = SaveData::new(stream.description().to_string(), stream.brick_count(), centroid, gb);
+        let mut save = SaveData::new(stream.description().to_string(), stream.brick_count(), centroid, gb);
+        save.owners = stream.owners().to_vec();
         SaveLoading { save, source: LoadSource::Stream(stream) }
     }
 
@@ -224,23 +446,11 @@ impl SaveLoading {
         let save = reader.read_all()
             .map_err(|_| "brickadia-rs error reading file".to_string())?;
 
-        // Get color list as display-ready sRGB (brs stores linear values)
-        let mut colors: Vec<Color> = save.header2.colors.iter().map(convert_color).collect();
-        for color in &mut colors {
-            color.convert_to_srgb();
-        }
-
+        let palette = srgb_palette(&save.header2.colors);
         let brick_assets = save.header2.brick_assets;
         let bricks: Vec<Brick> = save.bricks.iter()
             .filter_map(|brick| {
-                let color = match &brick.color {
-                    BrickColor::Index(color_index) => colors[*color_index as usize],
-                    BrickColor::Unique(color) => {
-                        let mut color = convert_color(color);
-                        color.convert_to_srgb();
-                        color
-                    }
-                };
+                let color = brick_color(&brick.color, &palette);
                 util::slim_brick(brick, &brick_assets, color.to_bytes())
             })
             .collect();
@@ -249,6 +459,21 @@ impl SaveLoading {
             return Err("save contains no visible bricks".to_string());
         }
 
+        let labels = save.bricks.iter()
+            .filter(|brick| brick.visibility && !brick.components.is_empty())
+            .filter_map(|brick| {
+                component_text(&brick.components).map(|text| Label { position: brick.position, text })
+            })
+            .collect();
+
+        let markers = save.bricks.iter()
+            .filter_map(|brick| {
+                let asset = brick_assets.get(brick.asset_name_index as usize)?;
+                Some(brick_markers(asset, &brick.components, brick.position))
+            })
+            .flatten()
+            .collect();
+
         let centroid = util::calculate_centroid(&bricks);
         let grid_bounds = util::footprint_bounds(&bricks);
 
@@ -259,6 +484,10 @@ impl SaveLoading {
             grid_bounds,
         );
         data.brick_assets = brick_assets;
+        data.labels = labels;
+        data.markers = markers;
+        data.owners = save.header2.brick_owners.into_iter().map(|owner| owner.name).collect();
+        data.materials = save.header2.materials;
 
         Ok(SaveLoading { save: data, source: LoadSource::Whole(Some((0, bricks))) })
     }
@@ -280,7 +509,12 @@ impl SaveLoading {
     pub fn step(&mut self) -> Result<bool, String> {
         let next = match &mut self.source {
             LoadSource::Whole(pending) => pending.take(),
-            LoadSource::Stream(stream) => stream.next_chunk(&mut self.save.brick_assets)?,
+            LoadSource::Stream(stream) => stream.next_chunk(
+                &mut self.save.brick_assets,
+                &mut self.save.materials,
+                &mut self.save.markers,
+                &mut self.save.labels,
+            )?,
         };
         match next {
             Some((layer, bricks)) => {
@@ -296,11 +530,26 @@ impl SaveLoading {
         if save.is_empty() {
             return Err("save contains no visible bricks".to_string());
         }
-        save.dedupe = HashSet::new();
+        save.dedupe = HashMap::new();
+        save.stacks = HashSet::new();
         Ok(save)
     }
 }
 
+fn footprint(brick: &Brick) -> Rect {
+    let size = util::sizer(brick);
+    (
+        brick.position.0 - size.0 as i32,
+        brick.position.1 - size.1 as i32,
+        brick.position.0 + size.0 as i32,
+        brick.position.1 + size.1 as i32,
+    )
+}
+
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.0 < b.2 && b.0 < a.2 && a.1 < b.3 && b.1 < a.3
+}
+
 // Boolean coverage over the save's footprint for occlusion culling
 struct CoverGrid {
     min_x: i32,
@@ -351,6 +600,10 @@ impl CoverGrid {
         true
     }
 
+    fn clear(&mut self) {
+        self.covered.fill(false);
+    }
+
     // Mark only cells lying entirely inside the footprint
     fn cover(&mut self, brick: &Brick) {
         let Some((x1, y1, x2, y2)) = self.cells(brick) else {
@@ -364,10 +617,219 @@ impl CoverGrid {
     }
 }
 
+// Geometry accumulating toward one batch upload
+struct Staging {
+    vertices: Vec<u8>,
+    indices: Vec<u32>,
+    aabb: (f32, f32, f32, f32),
+    scales: ScaleRange,
+}
+
+impl Staging {
+    fn new(scales: ScaleRange) -> Staging {
+        Staging {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            aabb: (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            scales,
+        }
+    }
+
+    fn push(&mut self, positions: &[f32], color: [u8; 4]) {
+        push_shape(&mut self.vertices, &mut self.indices, positions, color);
+    }
+
+    fn push_projected(&mut self, positions: &[f32], color: [u8; 4]) {
+        push_projected(&mut self.vertices, &mut self.indices, positions, color);
+    }
+
+    fn push_outline(&mut self, thin: &[f32], unit: &[f32], color: [u8; 4]) {
+        push_outline(&mut self.vertices, &mut self.indices, thin, unit, color);
+    }
+
+    fn extend_aabb(&mut self, (x1, y1, x2, y2): (f32, f32, f32, f32)) {
+        self.aabb.0 = self.aabb.0.min(x1 - AABB_PAD);
+        self.aabb.1 = self.aabb.1.min(y1 - AABB_PAD);
+        self.aabb.2 = self.aabb.2.max(x2 + AABB_PAD);
+        self.aabb.3 = self.aabb.3.max(y2 + AABB_PAD);
+    }
+
+    fn flush(&mut self, layer: i16, renderer: &mut Renderer) {
+        if self.indices.is_empty() {
+            return;
+        }
+        renderer.upload_batch(layer as i32, self.aabb, self.scales, &self.vertices, &self.indices);
+        self.vertices.clear();
+        self.indices.clear();
+        self.aabb = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    }
+}
+
+// Coarse stand-in for one chunk at one level of detail: the chunk's visible
+// fills resampled onto square cells, each taking the color of the highest
+// brick over its center. Sparse, since most of a chunk's extent is empty.
+struct LodGrid {
+    cell: i32,
+    cells: HashMap<(i32, i32), [u8; 4]>,
+}
+
+impl LodGrid {
+    fn new(cell: i32) -> LodGrid {
+        LodGrid { cell, cells: HashMap::new() }
+    }
+
+    // Bricks arrive in ascending top-surface order, so later ones overwrite.
+    // A brick too small to cover any cell center still claims the cell
+    // holding its own center, or fine detail would vanish when zoomed out.
+    fn paint(&mut self, brick: &Brick, color: [u8; 4]) {
+        let size = util::sizer(brick);
+        let x1 = brick.position.0 - size.0 as i32;
+        let y1 = brick.position.1 - size.1 as i32;
+        let x2 = brick.position.0 + size.0 as i32;
+        let y2 = brick.position.1 + size.1 as i32;
+        // Cells whose centers (c + 0.5) * cell lie inside [x1, x2)
+        let half = self.cell / 2;
+        let c1 = (x1 - half + self.cell - 1).div_euclid(self.cell);
+        let c2 = (x2 - half - 1).div_euclid(self.cell);
+        let r1 = (y1 - half + self.cell - 1).div_euclid(self.cell);
+        let r2 = (y2 - half - 1).div_euclid(self.cell);
+        if c1 > c2 || r1 > r2 {
+            let cell = (brick.position.0.div_euclid(self.cell), brick.position.1.div_euclid(self.cell));
+            self.cells.insert(cell, color);
+            return;
+        }
+        for r in r1..=r2 {
+            for c in c1..=c2 {
+                self.cells.insert((c, r), color);
+            }
+        }
+    }
+
+    // Emits the cells as quads, merging same-colored runs along each row
+    fn drain_into(&mut self, staging: &mut Staging) {
+        let mut cells: Vec<((i32, i32), [u8; 4])> = self.cells.drain().collect();
+        cells.sort_unstable_by_key(|&((c, r), _)| (r, c));
+
+        let mut i = 0;
+        while i < cells.len() {
+            let ((c, r), color) = cells[i];
+            let mut end = c + 1;
+            i += 1;
+            while i < cells.len() && cells[i].0 == (end, r) && cells[i].1 == color {
+                end += 1;
+                i += 1;
+            }
+            let shape = Shape {
+                x1: (c * self.cell) as f32,
+                y1: (r * self.cell) as f32,
+                x2: (end * self.cell) as f32,
+                y2: ((r + 1) * self.cell) as f32,
+            };
+            staging.push(&rec(&shape), color);
+            staging.extend_aabb(shape.unpack());
+        }
+    }
+}
+
+// A region for outlining: rectangular fills of one color at one surface
+type RegionKey = ([u8; 4], i32);
+
+// Every loaded chunk's rectangular fills by region, so the outline of a
+// region cut by chunk borders skips the stretches where it carries on in a
+// neighboring chunk
+#[derive(Default)]
+struct RegionIndex {
+    // Chunks indexed so far
+    chunks: usize,
+    buckets: HashMap<(RegionKey, i32, i32), Vec<(usize, Rect)>>,
+}
+
+impl RegionIndex {
+    fn insert(&mut self, key: RegionKey, chunk: usize, rect: Rect) {
+        for bx in rect.0.div_euclid(REGION_BUCKET)..=rect.2.div_euclid(REGION_BUCKET) {
+            for by in rect.1.div_euclid(REGION_BUCKET)..=rect.3.div_euclid(REGION_BUCKET) {
+                self.buckets.entry((key, bx, by)).or_default().push((chunk, rect));
+            }
+        }
+    }
+
+    // The stretches of `edge`, on the boundary of the region's part in
+    // `chunk`, that no other chunk's part of the region lies across
+    fn exposed(&self, key: RegionKey, chunk: usize, edge: &Edge) -> Vec<Edge> {
+        let horizontal = edge.from.1 == edge.to.1;
+        // Along the edge, and where it lies across
+        let (from, to, at) = match horizontal {
+            true => (edge.from.0, edge.to.0, edge.from.1),
+            false => (edge.from.1, edge.to.1, edge.from.0),
+        };
+        let inward = edge.inward.0 + edge.inward.1;
+        let buckets = |a: i32, b: i32| a.div_euclid(REGION_BUCKET)..=b.div_euclid(REGION_BUCKET);
+
+        let mut covered: Vec<(i32, i32)> = Vec::new();
+        for along in buckets(from, to) {
+            let bucket = match horizontal {
+                true => (key, along, at.div_euclid(REGION_BUCKET)),
+                false => (key, at.div_euclid(REGION_BUCKET), along),
+            };
+            for &(other, rect) in self.buckets.get(&bucket).into_iter().flatten() {
+                if other == chunk {
+                    continue;
+                }
+                let (low, high, across_low, across_high) = match horizontal {
+                    true => (rect.0, rect.2, rect.1, rect.3),
+                    false => (rect.1, rect.3, rect.0, rect.2),
+                };
+                // Lying on the side facing away from the region
+                let outside = match inward > 0 {
+                    true => across_low < at && at <= across_high,
+                    false => across_low <= at && at < across_high,
+                };
+                if outside && low < to && from < high {
+                    covered.push((low.max(from), high.min(to)));
+                }
+            }
+        }
+        covered.sort_unstable();
+
+        let mut exposed = Vec::new();
+        let mut start = from;
+        for (low, high) in covered.into_iter().chain(std::iter::once((to, to))) {
+            if low > start {
+                let (a, b) = match horizontal {
+                    true => ((start, at), (low, at)),
+                    false => ((at, start), (at, low)),
+                };
+                exposed.push(Edge { from: a, to: b, inward: edge.inward });
+            }
+            start = start.max(high);
+        }
+        exposed
+    }
+}
+
+// View scales the detail batches draw at, then those of each LOD_LEVELS
+// entry. Each level takes over where the finer one stops.
+fn lod_scales() -> (ScaleRange, Vec<ScaleRange>) {
+    let detail = ScaleRange { min: LOD_LEVELS[0].1, max: f32::INFINITY };
+    let levels = LOD_LEVELS
+        .iter()
+        .enumerate()
+        .map(|(i, &(_, max))| {
+            let min = LOD_LEVELS.get(i + 1).map_or(0.0, |&(_, below)| below);
+            ScaleRange { min, max }
+        })
+        .collect();
+    (detail, levels)
+}
+
 /// Incremental geometry builder. Consumes chunks in the save's processing
 /// order (top layer first), maintaining the occlusion grid across chunks, and
 /// uploads batches keyed for bottom-first draw order. Survives across
 /// [`SaveLoading::step`] calls so geometry can build as chunks stream in.
+///
+/// Fill modes also build coarse level-of-detail batches per chunk, which the
+/// renderer swaps in for the detail batches when zoomed far enough out that
+/// the coarse cells shrink to about a pixel.
 pub struct GeometryState {
     mode: GeometryMode,
     grid: CoverGrid,
@@ -375,11 +837,23 @@ pub struct GeometryState {
     // mid-stream heightmap needs a final rebuild once loading completes
     height_extent: (i32, i32),
     next_chunk: usize,
+    // Bricks wholly outside are left out
+    region: Option<Rect>,
+    // Bricks whose top surface lies outside are left out
+    heights: Option<(i32, i32)>,
+    // Set for oblique views, which draw brick boxes depth-tested
+    projector: Option<Projector>,
+    // Seen from below: lowest bricks win and layers draw top first
+    underside: bool,
     pub culled: usize,
-    staging_vertices: Vec<u8>,
-    staging_indices: Vec<u32>,
-    staging_aabb: (f32, f32, f32, f32),
+    staging: Staging,
+    // One per LOD_LEVELS entry; empty when the mode has no fills
+    lod_staging: Vec<Staging>,
     staging_layer: i16,
+    // Plan mode's wall footprints, outlined together once built
+    walls: Vec<Rect>,
+    // Set for region outlines
+    regions: Option<RegionIndex>,
 }
 
 impl GeometryState {
@@ -388,6 +862,20 @@ impl GeometryState {
     }
 
     pub fn new(save: &SaveData, mode: GeometryMode) -> GeometryState {
+        // Outlines shrink to nothing long before the coarse levels kick in,
+        // so only fills get them
+        let lod = match mode {
+            GeometryMode::Map { fills, .. } => fills,
+            GeometryMode::Heightmap => true,
+            GeometryMode::Plan { .. } => false,
+        };
+        let (detail_scales, lod_staging) = if lod {
+            let (detail, levels) = lod_scales();
+            (detail, levels.into_iter().map(Staging::new).collect())
+        } else {
+            (ScaleRange::ALL, Vec::new())
+        };
+
         GeometryState {
             mode,
             grid: CoverGrid::new(save.grid_bounds),
@@ -396,46 +884,204 @@ impl GeometryState {
                 _ => (0, 1),
             },
             next_chunk: 0,
+            region: None,
+            heights: None,
+            projector: None,
+            underside: false,
             culled: 0,
-            staging_vertices: Vec::new(),
-            staging_indices: Vec::new(),
-            staging_aabb: (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            staging: Staging::new(detail_scales),
+            lod_staging,
             staging_layer: 0,
+            walls: Vec::new(),
+            regions: match mode {
+                GeometryMode::Map { outlines: Some(OutlineStyle { regions: true, .. }), .. } => Some(RegionIndex::default()),
+                _ => None,
+            },
         }
     }
 
+    /// Limits geometry built from here on to bricks whose footprints reach
+    /// into `region` (absolute save units, x1, y1, x2, y2); `None` builds
+    /// everything.
+    pub fn set_region(&mut self, region: Option<Rect>) {
+        self.region = region;
+    }
+
+    /// Limits geometry built from here on to bricks whose top surface lies
+    /// in `heights` (low inclusive, high exclusive), such as one storey of
+    /// a building; `None` builds every height.
+    pub fn set_heights(&mut self, heights: Option<(i32, i32)>) {
+        self.heights = heights;
+    }
+
+    /// Draws bricks as boxes seen from an angle rather than top-down. Call
+    /// before building anything, after any region, which fog spans. Oblique
+    /// views skip occlusion culling (bricks covered from above can show from
+    /// the side), levels of detail and outlines.
+    pub fn set_projection(&mut self, save: &SaveData, oblique: Option<Oblique>) {
+        self.projector = oblique.map(|oblique| {
+            let (x1, y1, x2, y2) = save.grid_bounds;
+            let (cx, cy) = save.centroid;
+            let reach = [(x1, y1), (x2, y1), (x1, y2), (x2, y2)]
+                .iter()
+                .map(|&(x, y)| ((x - cx) as f32).hypot((y - cy) as f32))
+                .fold(0.0, f32::max);
+            let mut projector = Projector::new(oblique, save.centroid, reach);
+            if oblique.fog.is_some() {
+                projector.fog_over(self.region.unwrap_or(save.grid_bounds), save.height_extent());
+            }
+            projector
+        });
+        if self.projector.is_some() {
+            self.staging = Staging::new(ScaleRange::ALL);
+            self.lod_staging.clear();
+        }
+    }
+
+    /// Maps the build from beneath, as a reflected ceiling plan: still
+    /// north-up and unmirrored, but wherever bricks overlap the lowest one
+    /// shows, so cave ceilings, bridges and underground bases come out.
+    /// Call before building anything.
+    pub fn set_underside(&mut self, underside: bool) {
+        self.underside = underside;
+    }
+
     /// Builds geometry for any chunks added to the save since the last call.
     pub fn build_pending(&mut self, save: &SaveData, renderer: &mut Renderer) -> Result<(), String> {
+        self.index_regions(save);
+        if self.underside {
+            // Occluders come first in processing, so from below that's the
+            // lowest layer, which loads last. Coverage left by an earlier
+            // call's higher layers would hide what shows from below, so
+            // each call culls afresh; lower layers still draw over them.
+            self.grid.clear();
+            for index in (self.next_chunk..save.chunks.len()).rev() {
+                self.build_chunk(save, index, renderer);
+            }
+            self.next_chunk = save.chunks.len();
+        }
         while self.next_chunk < save.chunks.len() {
-            self.build_chunk(save, renderer);
+            self.build_chunk(save, self.next_chunk, renderer);
             self.next_chunk += 1;
         }
+        self.push_walls(renderer);
         Ok(())
     }
 
-    fn build_chunk(&mut self, save: &SaveData, renderer: &mut Renderer) {
-        let chunk = &save.chunks[self.next_chunk];
+    // Adds the chunks loaded since the last call to the region index. Region
+    // boundaries against chunks indexed later are outlined regardless, until
+    // the next full build.
+    fn index_regions(&mut self, save: &SaveData) {
+        let Some(mut regions) = self.regions.take() else {
+            return;
+        };
+        for (index, chunk) in save.chunks.iter().enumerate().skip(regions.chunks) {
+            for brick in self.chunk_bricks(chunk) {
+                if self.left_out(brick) || !is_full_rect(&save.brick_assets[brick.asset_name_index as usize]) {
+                    continue;
+                }
+                regions.insert((self.fill_color(brick), self.surface(brick)), index, footprint(brick));
+            }
+        }
+        regions.chunks = save.chunks.len();
+        self.regions = Some(regions);
+    }
 
-        // Batches never span layers — the layer is the draw-order key
-        if chunk.layer != self.staging_layer {
-            self.flush(renderer);
-            self.staging_layer = chunk.layer;
+    // Outlines the walls found since the last call as merged regions, over
+    // everything else. Walls from separate calls outline separately.
+    fn push_walls(&mut self, renderer: &mut Renderer) {
+        if self.walls.is_empty() {
+            return;
+        }
+        self.flush(renderer);
+        self.staging_layer = i16::MAX;
+        let merged = merge_rects(&self.walls);
+        for &(x1, y1, x2, y2) in &merged.rects {
+            let shape = Shape { x1: x1 as f32, y1: y1 as f32, x2: x2 as f32, y2: y2 as f32 };
+            self.staging.push(&rec(&shape), plan::WALL_FILL);
+            self.staging.extend_aabb(shape.unpack());
         }
+        for edge in &merged.boundary {
+            let from = (edge.from.0 as f32, edge.from.1 as f32);
+            let to = (edge.to.0 as f32, edge.to.1 as f32);
+            let inward = (edge.inward.0 as f32, edge.inward.1 as f32);
+            let thin = edge_ol(from, to, inward, 0.0);
+            let unit = edge_ol(from, to, inward, 1.0);
+            self.staging.push_outline(&thin, &unit, plan::WALL_LINE);
+        }
+        self.flush(renderer);
+        self.walls.clear();
+    }
 
+    fn fill_color(&self, brick: &Brick) -> [u8; 4] {
+        match self.mode {
+            GeometryMode::Map { .. } => brick.color,
+            GeometryMode::Plan { .. } => plan::floor_fill(brick.color),
+            GeometryMode::Heightmap => {
+                let (min_height, max_height) = self.height_extent;
+                let relative_height = (brick.position.2 - min_height) as f32 / (max_height - min_height).max(1) as f32;
+                let level = (relative_height * 255.0) as u8;
+                [level, level, level, 255]
+            }
+        }
+    }
+
+    // Height that orders drawing: the top surface, the highest drawn last,
+    // or from below the bottom surface, the lowest drawn last
+    fn surface(&self, brick: &Brick) -> i32 {
+        match self.underside {
+            true => -util::bottom_surface(brick),
+            false => util::top_surface(brick),
+        }
+    }
+
+    // Outside the region or heights being built
+    fn left_out(&self, brick: &Brick) -> bool {
+        self.region.is_some_and(|region| !overlaps(footprint(brick), region))
+            || self.heights.is_some_and(|(low, high)| !(low..high).contains(&util::top_surface(brick)))
+    }
+
+    // A chunk's bricks in processing order. Copies stacked under an identical
+    // brick only count where the top copy may not cover them: in projected
+    // views, when only some heights are built, and in plans, where the
+    // bottom copy of a stacked column is the one standing on the floor.
+    fn chunk_bricks<'a>(&self, chunk: &'a MapChunk) -> Vec<&'a Brick> {
+        let mut bricks: Vec<&Brick> = chunk.bricks.iter().collect();
+        let stacked = self.projector.is_some()
+            || self.heights.is_some()
+            || matches!(self.mode, GeometryMode::Plan { .. });
+        if stacked {
+            bricks.extend(&chunk.stacked);
+        }
+        if self.underside || stacked {
+            bricks.sort_by_key(|brick| self.surface(brick));
+        }
+        bricks
+    }
+
+    // Which of a chunk's bricks, in processing order, are left out or culled
+    fn hide(&mut self, save: &SaveData, bricks: &[&Brick]) -> Vec<bool> {
         // Outline-only mode draws no fills, so nothing occludes anything
-        let cull = match self.mode {
+        let cull = self.projector.is_none() && match self.mode {
             GeometryMode::Map { fills, .. } => fills,
-            GeometryMode::Heightmap => true,
+            GeometryMode::Heightmap | GeometryMode::Plan { .. } => true,
         };
 
         // Top-down: a brick is hidden if every coverage cell its footprint
         // touches was fully covered by the rectangular fills of bricks drawn
         // over it. Conservative on both sides — shaped bricks never cover,
         // partial cells never count as covered.
-        let mut hidden = vec![false; chunk.bricks.len()];
+        let mut hidden: Vec<bool> = bricks.iter().map(|brick| self.left_out(brick)).collect();
         if cull {
-            for (k, brick) in chunk.bricks.iter().enumerate().rev() {
-                if self.grid.fully_covered(brick) {
+            for (k, brick) in bricks.iter().enumerate().rev() {
+                // Bricks left out occlude nothing; the ones they would have
+                // hidden merely draw unnecessarily
+                if hidden[k] {
+                    continue;
+                }
+                // A plan's walls show through whatever covers them
+                let wall = matches!(self.mode, GeometryMode::Plan { floor } if plan::is_wall(brick, floor));
+                if !wall && self.grid.fully_covered(brick) {
                     hidden[k] = true;
                     self.culled += 1;
                     continue;
@@ -446,41 +1092,201 @@ impl GeometryState {
                 }
             }
         }
+        hidden
+    }
+
+    fn build_chunk(&mut self, save: &SaveData, index: usize, renderer: &mut Renderer) {
+        let chunk = &save.chunks[index];
+        if self.region.is_some_and(|region| !overlaps(chunk.bounds, region)) {
+            return;
+        }
+
+        // Batches never span layers — the layer is the draw-order key, and
+        // from below the top layer draws first
+        let layer = if self.underside { -chunk.layer } else { chunk.layer };
+        if layer != self.staging_layer {
+            self.flush(renderer);
+            self.staging_layer = layer;
+        }
+
+        let bricks = self.chunk_bricks(chunk);
+
+        let hidden = self.hide(save, &bricks);
+
+        let mut lod_grids: Vec<LodGrid> = LOD_LEVELS
+            .iter()
+            .take(self.lod_staging.len())
+            .map(|&(cell, _)| LodGrid::new(cell))
+            .collect();
+
+        // Runs of equal surface, within which draw order is arbitrary
+        // anyway, so bricks in a run can merge without reordering anything
+        let mut start = 0;
+        while start < bricks.len() {
+            let level = self.surface(bricks[start]);
+            let mut end = start + 1;
+            while end < bricks.len() && self.surface(bricks[end]) == level {
+                end += 1;
+            }
+            self.build_run(save, index, &bricks[start..end], &hidden[start..end], &mut lod_grids, renderer);
+            start = end;
+        }
+
+        // Coarse geometry lands after the chunk's detail geometry in upload
+        // order, matching it within the layer
+        for (grid, staging) in lod_grids.iter_mut().zip(&mut self.lod_staging) {
+            grid.drain_into(staging);
+            if staging.indices.len() >= BATCH_INDEX_TARGET {
+                staging.flush(self.staging_layer, renderer);
+            }
+        }
+    }
 
-        for (brick, &hide) in chunk.bricks.iter().zip(&hidden) {
+    fn build_run(
+        &mut self,
+        save: &SaveData,
+        chunk: usize,
+        bricks: &[&Brick],
+        hidden: &[bool],
+        lod_grids: &mut [LodGrid],
+        renderer: &mut Renderer,
+    ) {
+        if let Some(projector) = &self.projector {
+            for (&brick, &hide) in bricks.iter().zip(hidden) {
+                if hide || !projector.shows(brick) {
+                    continue;
+                }
+                for (positions, color) in projector.faces(brick, self.fill_color(brick)) {
+                    self.staging.push_projected(&positions, color);
+                }
+                self.staging.extend_aabb(projector.extent(brick));
+                if self.staging.indices.len() >= BATCH_INDEX_TARGET {
+                    self.staging.flush(self.staging_layer, renderer);
+                }
+            }
+            return;
+        }
+
+        // Rectangular bricks are grouped by color when their fills merge or
+        // their outlines trace region boundaries
+        let group_rects = match self.mode {
+            GeometryMode::Map { merge, outlines, .. } => merge || outlines.is_some_and(|o| o.regions),
+            GeometryMode::Heightmap | GeometryMode::Plan { .. } => false,
+        };
+
+        let mut groups: HashMap<[u8; 4], Vec<&Brick>> = HashMap::new();
+        for (&brick, &hide) in bricks.iter().zip(hidden) {
             if hide {
                 continue;
             }
             let name = &save.brick_assets[brick.asset_name_index as usize];
 
+            let color = self.fill_color(brick);
+            for grid in lod_grids.iter_mut() {
+                grid.paint(brick, color);
+            }
+
+            if group_rects && is_full_rect(name) {
+                groups.entry(color).or_default().push(brick);
+                continue;
+            }
+
             match self.mode {
-                GeometryMode::Map { outlines, fills } => {
+                GeometryMode::Map { fills, outlines, studs, .. } => {
                     if fills {
                         let verts = calculate_brick_vertices(name, brick);
-                        push_shape(&mut self.staging_vertices, &mut self.staging_indices, &verts, brick.color);
+                        self.staging.push(&verts, color);
+                    }
+                    if fills && studs {
+                        for (positions, color) in stud_faces(name, brick, color) {
+                            self.staging.push(&positions, color);
+                        }
                     }
-                    if outlines {
-                        let ol_verts = calculate_brick_outline_vertices(name, brick);
-                        push_shape(&mut self.staging_vertices, &mut self.staging_indices, &ol_verts, Color::black().to_bytes());
+                    if let Some(style) = outlines {
+                        let thin = calculate_brick_outline_vertices(name, brick, 0.0);
+                        let unit = calculate_brick_outline_vertices(name, brick, 1.0);
+                        self.staging.push_outline(&thin, &unit, style.color.resolve(color));
                     }
                 }
                 GeometryMode::Heightmap => {
-                    let (min_height, max_height) = self.height_extent;
                     let verts = calculate_brick_vertices(name, brick);
-                    let relative_height = (brick.position.2 - min_height) as f32 / (max_height - min_height).max(1) as f32;
-                    let level = (relative_height * 255.0) as u8;
-                    push_shape(&mut self.staging_vertices, &mut self.staging_indices, &verts, [level, level, level, 255]);
+                    self.staging.push(&verts, color);
+                }
+                GeometryMode::Plan { floor } => {
+                    if plan::is_wall(brick, floor) {
+                        self.walls.push(footprint(brick));
+                        continue;
+                    }
+                    let verts = calculate_brick_vertices(name, brick);
+                    self.staging.push(&verts, color);
                 }
             }
+            self.staging.extend_aabb(Shape::from(brick).unpack());
 
-            let size = util::sizer(brick);
-            self.staging_aabb.0 = self.staging_aabb.0.min((brick.position.0 - size.0 as i32) as f32 - AABB_PAD);
-            self.staging_aabb.1 = self.staging_aabb.1.min((brick.position.1 - size.1 as i32) as f32 - AABB_PAD);
-            self.staging_aabb.2 = self.staging_aabb.2.max((brick.position.0 + size.0 as i32) as f32 + AABB_PAD);
-            self.staging_aabb.3 = self.staging_aabb.3.max((brick.position.1 + size.1 as i32) as f32 + AABB_PAD);
+            if self.staging.indices.len() >= BATCH_INDEX_TARGET {
+                self.staging.flush(self.staging_layer, renderer);
+            }
+        }
+
+        // Sorted so the geometry is the same from one build to the next
+        let mut groups: Vec<([u8; 4], Vec<&Brick>)> = groups.into_iter().collect();
+        groups.sort_unstable_by_key(|(color, _)| *color);
+        for (color, group) in groups {
+            self.push_group(save, chunk, color, &group);
+            if self.staging.indices.len() >= BATCH_INDEX_TARGET {
+                self.staging.flush(self.staging_layer, renderer);
+            }
+        }
+    }
+
+    // Draws same-colored rectangular bricks of one run of `chunk`, merged if
+    // asked, followed by their studs and outlines so every fill lies beneath
+    // them
+    fn push_group(&mut self, save: &SaveData, chunk: usize, color: [u8; 4], group: &[&Brick]) {
+        let GeometryMode::Map { fills, merge, outlines, studs } = self.mode else {
+            return;
+        };
+
+        let rects: Vec<Rect> = group.iter().map(|brick| footprint(brick)).collect();
+        let merged = merge_rects(&rects);
+
+        let fill_rects = if merge { &merged.rects } else { &rects };
+        for &(x1, y1, x2, y2) in fill_rects {
+            let shape = Shape { x1: x1 as f32, y1: y1 as f32, x2: x2 as f32, y2: y2 as f32 };
+            if fills {
+                self.staging.push(&rec(&shape), color);
+            }
+            self.staging.extend_aabb(shape.unpack());
+        }
+        if fills && studs {
+            for brick in group {
+                let name = &save.brick_assets[brick.asset_name_index as usize];
+                for (positions, color) in stud_faces(name, brick, color) {
+                    self.staging.push(&positions, color);
+                }
+            }
+        }
 
-            if self.staging_indices.len() >= BATCH_INDEX_TARGET {
-                self.flush(renderer);
+        let Some(style) = outlines else {
+            return;
+        };
+        let outline_color = style.color.resolve(color);
+        // Only region outlines index their regions
+        if let Some(regions) = &self.regions {
+            let key = (color, self.surface(group[0]));
+            let boundary: Vec<Edge> = merged.boundary.iter().flat_map(|edge| regions.exposed(key, chunk, edge)).collect();
+            for edge in &boundary {
+                let from = (edge.from.0 as f32, edge.from.1 as f32);
+                let to = (edge.to.0 as f32, edge.to.1 as f32);
+                let inward = (edge.inward.0 as f32, edge.inward.1 as f32);
+                let thin = edge_ol(from, to, inward, 0.0);
+                let unit = edge_ol(from, to, inward, 1.0);
+                self.staging.push_outline(&thin, &unit, outline_color);
+            }
+        } else {
+            for brick in group {
+                let shape = Shape::from(*brick);
+                self.staging.push_outline(&rec_ol(&shape, 0.0), &rec_ol(&shape, 1.0), outline_color);
             }
         }
     }
@@ -488,17 +1294,135 @@ impl GeometryState {
     /// Uploads any staged geometry as a batch. Call after `build_pending` so
     /// partially filled batches reach the screen too.
     pub fn flush(&mut self, renderer: &mut Renderer) {
-        if self.staging_indices.is_empty() {
-            return;
+        self.staging.flush(self.staging_layer, renderer);
+        for staging in &mut self.lod_staging {
+            staging.flush(self.staging_layer, renderer);
         }
-        renderer.upload_batch(
-            self.staging_layer as i32,
-            self.staging_aabb,
-            &self.staging_vertices,
-            &self.staging_indices,
-        );
-        self.staging_vertices.clear();
-        self.staging_indices.clear();
-        self.staging_aabb = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lod_cells_take_the_highest_brick_and_merge_along_rows() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let mut grid = LodGrid::new(20);
+        // A 2x1 cell floor, half covered by a higher brick, then a
+        // stud-sized brick too small to cover any cell center
+        grid.paint(&Brick::sized((20, 10, 2), (20, 10, 2)), red);
+        grid.paint(&Brick::sized((30, 10, 6), (10, 10, 2)), blue);
+        grid.paint(&Brick::sized((50, 10, 2), (10, 10, 2)), blue);
+        grid.paint(&Brick::sized((105, 105, 2), (2, 2, 2)), red);
+        let mut cells: Vec<((i32, i32), [u8; 4])> = grid.cells.iter().map(|(&cell, &color)| (cell, color)).collect();
+        cells.sort_unstable_by_key(|&(cell, _)| cell);
+        assert_eq!(cells, vec![((0, 0), red), ((1, 0), blue), ((2, 0), blue), ((5, 5), red)]);
+
+        // The two blue cells in a row become one quad, so three quads
+        let mut staging = Staging::new(ScaleRange::ALL);
+        grid.drain_into(&mut staging);
+        assert!(grid.cells.is_empty());
+        assert_eq!(staging.indices.len(), 3 * 6);
+        assert_eq!(staging.aabb, (-AABB_PAD, -AABB_PAD, 120.0 + AABB_PAD, 120.0 + AABB_PAD));
+    }
+
+    #[test]
+    fn stacked_copies_are_left_out_of_top_down_maps() {
+        let mut save = SaveData::new(String::new(), 4, (0, 0), (0, 0, 100, 100));
+        // A pillar of three identical bricks plus an exact duplicate of the
+        // top one, split across two chunks that load top layer first
+        save.push_chunk(1, vec![Brick::sized((10, 10, 10), (10, 10, 2)), Brick::sized((10, 10, 10), (10, 10, 2))]);
+        save.push_chunk(0, vec![Brick::sized((10, 10, 2), (10, 10, 2)), Brick::sized((10, 10, 6), (10, 10, 2))]);
+
+        let heights = |bricks: &[Brick]| bricks.iter().map(|brick| brick.position.2).collect::<Vec<_>>();
+        assert_eq!(heights(&save.chunks[0].bricks), vec![10]);
+        assert!(save.chunks[0].stacked.is_empty());
+        assert!(save.chunks[1].bricks.is_empty());
+        assert_eq!(heights(&save.chunks[1].stacked), vec![2, 6]);
+        assert_eq!(save.bricks().count(), 1);
+        assert_eq!(save.all_bricks().count(), 3);
+        assert_eq!(save.discarded, 3);
+    }
+
+    #[test]
+    fn storeys_of_stacked_slabs_each_draw_their_own_copy() {
+        let mut save = SaveData::new(String::new(), 3, (0, 0), (0, 0, 100, 100));
+        // Three identical slabs, one per floor, the top one in its own chunk
+        save.push_chunk(1, vec![Brick::sized((10, 10, 98), (10, 10, 2))]);
+        save.push_chunk(0, vec![Brick::sized((10, 10, 2), (10, 10, 2)), Brick::sized((10, 10, 50), (10, 10, 2))]);
+        let storeys = crate::floors::detect_storeys(save.all_bricks(), 48);
+        let floors: Vec<i32> = storeys.iter().map(|storey| storey.floor).collect();
+        assert_eq!(floors, vec![4, 52, 100]);
+
+        // Each storey draws the slab on its floor, stacked or not
+        for storey in &storeys {
+            let mut state = GeometryState::new(&save, GeometryMode::Heightmap);
+            state.set_heights(Some(storey.heights()));
+            let drawn: Vec<i32> = save.chunks.iter()
+                .flat_map(|chunk| state.chunk_bricks(chunk))
+                .filter(|brick| !state.left_out(brick))
+                .map(util::top_surface)
+                .collect();
+            assert_eq!(drawn, vec![storey.floor]);
+        }
+    }
+
+    #[test]
+    fn plans_find_walls_among_stacked_copies() {
+        let mut save = SaveData::new(String::new(), 2, (0, 0), (0, 0, 100, 100));
+        save.brick_assets.push("PB_DefaultBrick".to_string());
+        // A column of two identical tall bricks on a floor at 4; only the
+        // lower one stands on the floor
+        save.push_chunk(0, vec![Brick::sized((10, 10, 16), (5, 5, 12)), Brick::sized((10, 10, 40), (5, 5, 12))]);
+        let mut state = GeometryState::new(&save, GeometryMode::Plan { floor: 4 });
+        let bricks = state.chunk_bricks(&save.chunks[0]);
+        let hidden = state.hide(&save, &bricks);
+        let walls: Vec<i32> = bricks.iter().zip(hidden)
+            .filter(|&(brick, hide)| !hide && plan::is_wall(brick, 4))
+            .map(|(brick, _)| brick.position.2)
+            .collect();
+        assert_eq!(walls, vec![16]);
+    }
+
+    #[test]
+    fn region_outlines_carry_on_across_chunks() {
+        let red = [255, 0, 0, 255];
+        let mut regions = RegionIndex::default();
+        // A floor split by a chunk border at x = 100, the part beyond it
+        // only half as deep, and a red floor one level up beyond that
+        regions.insert((red, 4), 0, (0, 0, 100, 100));
+        regions.insert((red, 4), 1, (100, 0, 200, 50));
+        regions.insert((red, 8), 1, (100, 50, 200, 100));
+
+        // Where the floors meet, chunk 0's side is outlined only alongside
+        // the higher floor
+        let border = Edge { from: (100, 0), to: (100, 100), inward: (-1, 0) };
+        assert_eq!(regions.exposed((red, 4), 0, &border), vec![Edge { from: (100, 50), to: (100, 100), inward: (-1, 0) }]);
+        // Chunk 1's side of the border is inside chunk 0's part
+        let border = Edge { from: (100, 0), to: (100, 50), inward: (1, 0) };
+        assert!(regions.exposed((red, 4), 1, &border).is_empty());
+        // An outer edge stays whole
+        let south = Edge { from: (0, 100), to: (100, 100), inward: (0, -1) };
+        assert_eq!(regions.exposed((red, 4), 0, &south), vec![south]);
+    }
+
+    #[test]
+    fn exactly_one_level_of_detail_draws_at_any_scale() {
+        let (detail, levels) = lod_scales();
+        assert_eq!(levels.len(), LOD_LEVELS.len());
+        let ranges: Vec<ScaleRange> = std::iter::once(detail).chain(levels).collect();
+        for scale in [0.0, 0.001, 0.0125, 0.02, 0.05, 0.1, 1.0, 100.0] {
+            assert_eq!(ranges.iter().filter(|range| range.contains(scale)).count(), 1, "scale {}", scale);
+        }
+        // Detail down to 0.05, then 20 unit cells down to 0.0125, then 80
+        let drawn = |scale: f32| ranges.iter().position(|range| range.contains(scale));
+        assert_eq!(drawn(0.1), Some(0));
+        assert_eq!(drawn(0.05), Some(0));
+        assert_eq!(drawn(0.02), Some(1));
+        assert_eq!(drawn(0.0125), Some(1));
+        assert_eq!(drawn(0.01), Some(2));
+        assert_eq!(drawn(0.0), Some(2));
     }
 }
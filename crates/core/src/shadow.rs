@@ -0,0 +1,236 @@
+use crate::brick::Brick;
+use crate::heightfield::{HeightField, EMPTY};
+use crate::merge::Rect;
+use crate::overlay::ImageView;
+use crate::raster::Raster;
+use crate::SaveData;
+
+// Save units per stud
+const STUD: f32 = 10.0;
+
+// How much light a fully shadowed surface loses
+const SHADOW_DARKNESS: f32 = 0.45;
+
+/// Sunlight casting shadows across the plan view.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sun {
+    /// Compass bearing the light comes from, in radians clockwise from
+    /// north (world -y)
+    pub azimuth: f32,
+    /// Radians above the horizon, in (0, π/2)
+    pub elevation: f32,
+    /// Width of blurred shadow edges, in save units; 0 for hard shadows
+    pub softness: f32,
+}
+
+impl Sun {
+    /// From angles in degrees and a softness in studs.
+    pub fn new(azimuth: f32, elevation: f32, softness: f32) -> Result<Sun, String> {
+        if !(elevation > 0.0 && elevation < 90.0) {
+            return Err("sun elevation must be above 0 and below 90 degrees".to_string());
+        }
+        if softness.is_nan() || softness < 0.0 {
+            return Err("shadow softness can't be negative".to_string());
+        }
+        Ok(Sun { azimuth: azimuth.to_radians(), elevation: elevation.to_radians(), softness: softness * STUD })
+    }
+
+    // Unit step across the ground toward the sun
+    fn toward(&self) -> (f32, f32) {
+        (self.azimuth.sin(), -self.azimuth.cos())
+    }
+
+    /// How far across the ground something `height` save units tall casts
+    /// its shadow.
+    pub fn reach(&self, height: f32) -> f32 {
+        height / self.elevation.tan()
+    }
+}
+
+/// How shadowed the ground is over a grid of cells, from brick footprints
+/// and heights: each cell is lit unless something toward the sun rises
+/// above the line from it to the sun.
+pub struct ShadowMap {
+    field: HeightField,
+    // 0.0 lit to 1.0 fully shadowed
+    shade: Vec<f32>,
+}
+
+impl ShadowMap {
+    /// Shadows over `area` (absolute save units, x1, y1, x2, y2) in cells
+    /// `cell` save units square. Only `bricks` cast and catch them.
+    pub fn new<'a>(sun: &Sun, area: (f32, f32, f32, f32), cell: f32, bricks: impl Iterator<Item = &'a Brick>) -> ShadowMap {
+        ShadowMap::over(sun, HeightField::new(area, cell, bricks))
+    }
+
+    fn over(sun: &Sun, field: HeightField) -> ShadowMap {
+        let HeightField { cols, rows, cell, .. } = field;
+        let ceiling = shadow_ceiling(&field.heights, cols, rows, cell, sun);
+        let tan = sun.elevation.tan();
+        let mut shade: Vec<f32> = field
+            .heights
+            .iter()
+            .zip(&ceiling)
+            .map(|(&height, &above)| match sun.softness > 0.0 {
+                // Fades in over the first stretch of the shadow's length
+                true => ((above - height) / (sun.softness * tan)).clamp(0.0, 1.0),
+                false => if above > height { 1.0 } else { 0.0 },
+            })
+            .collect();
+        if sun.softness > 0.0 {
+            let radius = (sun.softness / 2.0 / cell).round() as usize;
+            box_blur(&mut shade, cols, rows, radius);
+        }
+        for (shade, &height) in shade.iter_mut().zip(&field.heights) {
+            if height <= EMPTY {
+                *shade = 0.0;
+            }
+        }
+        ShadowMap { field, shade }
+    }
+
+    /// How shadowed the ground is at an absolute position, 0.0..=1.0.
+    pub fn at(&self, position: (f32, f32)) -> f32 {
+        self.field.cell_at(position).map_or(0.0, |i| self.shade[i])
+    }
+
+    /// Darkens the shadowed parts of `raster`, which holds the image `view`
+    /// describes.
+    pub fn apply(&self, raster: &mut Raster, view: &ImageView) {
+        self.field.darken(raster, view, &self.shade, SHADOW_DARKNESS);
+    }
+}
+
+/// Darkens `raster` (the image `view` describes) with the shadows `sun`
+/// casts across the save's bricks, or those reaching into `region` when
+/// given (absolute save units).
+pub fn cast_shadows(save: &SaveData, sun: &Sun, region: Option<Rect>, raster: &mut Raster, view: &ImageView) {
+    let (low, high) = save.height_extent();
+    let reach = sun.reach((high - low).max(0) as f32);
+    let (dx, dy) = sun.toward();
+
+    // Anything toward the sun within a shadow's length can reach the image
+    let grow = (
+        -(dx * reach).min(0.0),
+        -(dy * reach).min(0.0),
+        (dx * reach).max(0.0),
+        (dy * reach).max(0.0),
+    );
+    if let Some(field) = HeightField::under_view(save, view, grow, region) {
+        ShadowMap::over(sun, field).apply(raster, view);
+    }
+}
+
+// For every cell, the height the shadows of everything toward the sun
+// reach over it. Walks away from the sun one cell at a time along the
+// direction's major axis, so each cell builds on its sunward neighbor:
+// whichever is higher of that neighbor and its shadow, dropping with the
+// distance stepped. The minor axis blends the two nearest neighbors.
+fn shadow_ceiling(heights: &[f32], cols: usize, rows: usize, cell: f32, sun: &Sun) -> Vec<f32> {
+    let (dx, dy) = sun.toward();
+    let x_major = dx.abs() >= dy.abs();
+    let (major_len, minor_len, major_d, minor_d) = match x_major {
+        true => (cols, rows, dx, dy),
+        false => (rows, cols, dy, dx),
+    };
+    let index = |major: usize, minor: usize| match x_major {
+        true => minor * cols + major,
+        false => major * cols + minor,
+    };
+    // Minor cells per major cell, and the rise lost per step
+    let slope = minor_d / major_d.abs();
+    let drop = cell * (1.0 + slope * slope).sqrt() * sun.elevation.tan();
+
+    let mut ceiling = vec![EMPTY; cols * rows];
+    let toward_end = major_d > 0.0;
+    for k in 0..major_len {
+        let major = if toward_end { major_len - 1 - k } else { k };
+        let from = match toward_end {
+            true => major + 1,
+            false => major.wrapping_sub(1),
+        };
+        if from >= major_len {
+            continue;
+        }
+        for minor in 0..minor_len {
+            let at = minor as f32 + slope;
+            let lo = at.floor();
+            let t = at - lo;
+            let sample = |m: f32| {
+                if m < 0.0 || m >= minor_len as f32 {
+                    return EMPTY;
+                }
+                let i = index(from, m as usize);
+                heights[i].max(ceiling[i])
+            };
+            // A cell without bricks only counts when it's the nearer one
+            let above = match (sample(lo), sample(lo + 1.0)) {
+                (a, b) if a > EMPTY && b > EMPTY => a + (b - a) * t,
+                (a, _) if a > EMPTY && t < 0.5 => a,
+                (_, b) if b > EMPTY && t >= 0.5 => b,
+                _ => EMPTY,
+            };
+            ceiling[index(major, minor)] = above - drop;
+        }
+    }
+    ceiling
+}
+
+// Averages each value with those within `radius` cells, rows then columns
+fn box_blur(values: &mut [f32], cols: usize, rows: usize, radius: usize) {
+    if radius == 0 {
+        return;
+    }
+    let blur_line = |line: &mut Vec<f32>| {
+        let mut sums = vec![0.0; line.len() + 1];
+        for (i, v) in line.iter().enumerate() {
+            sums[i + 1] = sums[i] + v;
+        }
+        for (i, v) in line.iter_mut().enumerate() {
+            let lo = i.saturating_sub(radius);
+            let hi = (i + radius + 1).min(sums.len() - 1);
+            *v = (sums[hi] - sums[lo]) / (hi - lo) as f32;
+        }
+    };
+    let mut line = Vec::new();
+    for r in 0..rows {
+        line.clear();
+        line.extend_from_slice(&values[r * cols..(r + 1) * cols]);
+        blur_line(&mut line);
+        values[r * cols..(r + 1) * cols].copy_from_slice(&line);
+    }
+    for c in 0..cols {
+        line.clear();
+        line.extend((0..rows).map(|r| values[r * cols + c]));
+        blur_line(&mut line);
+        for (r, v) in line.iter().enumerate() {
+            values[r * cols + c] = *v;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn towers_shade_the_floor_away_from_the_sun() {
+        // A floor 200 units square with a tower 50 taller in the middle, lit
+        // from the east at 45 degrees
+        let bricks = [Brick::sized((100, 100, 2), (100, 100, 2)), Brick::sized((100, 100, 29), (10, 10, 25))];
+        let sun = Sun::new(90.0, 45.0, 0.0).unwrap();
+        let map = ShadowMap::new(&sun, (0.0, 0.0, 200.0, 200.0), 5.0, bricks.iter());
+
+        // West of the tower lies in its shadow, as far as it's tall
+        assert_eq!(map.at((80.0, 100.0)), 1.0);
+        assert_eq!(map.at((45.0, 100.0)), 1.0);
+        // East of it, past its length and off to the side are lit
+        assert_eq!(map.at((120.0, 100.0)), 0.0);
+        assert_eq!(map.at((30.0, 100.0)), 0.0);
+        assert_eq!(map.at((80.0, 150.0)), 0.0);
+        // The tower's own top is in the sun
+        assert_eq!(map.at((100.0, 100.0)), 0.0);
+
+        assert!(Sun::new(0.0, 90.0, 0.0).is_err());
+    }
+}
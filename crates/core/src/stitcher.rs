@@ -1,3 +1,5 @@
+use crate::raster::Raster;
+
 /// Stitches raw RGBA screenshot tiles into one PNG. Tiles are blitted
 /// straight into the full-size pixel buffer as they arrive, so peak memory is
 /// the final image plus one tile.
@@ -7,6 +9,10 @@ pub struct TileStitcher {
     tile_height: u32,
     rows: u32,
     cols: u32,
+    // Size of the stitched image, which may cut the last row and column of
+    // tiles short
+    width: u32,
+    height: u32,
 }
 
 impl Default for TileStitcher {
@@ -17,6 +23,8 @@ impl Default for TileStitcher {
             tile_height: 0,
             rows: 0,
             cols: 0,
+            width: 0,
+            height: 0,
         }
     }
 }
@@ -27,8 +35,18 @@ impl TileStitcher {
         self.tile_height = tile_height;
         self.rows = rows;
         self.cols = cols;
+        self.set_size(tile_width * cols, tile_height * rows);
+    }
+
+    /// Trims the image to `width` by `height` pixels, for a tile grid that
+    /// overshoots the size asked for. Tiles are laid out from the top-left,
+    /// so the overshoot is cut off the right and bottom edges. Clears what
+    /// was stitched so far.
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.width = width.min(self.tile_width * self.cols);
+        self.height = height.min(self.tile_height * self.rows);
         self.pixels.clear();
-        self.pixels.resize((tile_width * cols * tile_height * rows * 4) as usize, 0);
+        self.pixels.resize((self.width * self.height * 4) as usize, 0);
     }
 
     /// Copies one tile of tightly-packed RGBA pixels into place.
@@ -40,33 +58,37 @@ impl TileStitcher {
             return Err("unexpected tile size".to_string());
         }
 
-        let image_width = (self.cols * self.tile_width) as usize;
+        // Only the part of the tile inside the image is kept
+        let (left, top) = (col * self.tile_width, row * self.tile_height);
+        let visible_width = self.tile_width.min(self.width.saturating_sub(left)) as usize;
+        let visible_height = self.tile_height.min(self.height.saturating_sub(top)) as usize;
+
+        let image_width = self.width as usize;
         let tile_row_bytes = (self.tile_width * 4) as usize;
-        for y in 0..self.tile_height as usize {
+        for y in 0..visible_height {
             let src = y * tile_row_bytes;
-            let dst = ((row * self.tile_height) as usize + y) * image_width * 4
-                + (col * self.tile_width) as usize * 4;
-            self.pixels[dst..dst + tile_row_bytes].copy_from_slice(&tile[src..src + tile_row_bytes]);
+            let dst = ((top as usize + y) * image_width + left as usize) * 4;
+            self.pixels[dst..dst + visible_width * 4].copy_from_slice(&tile[src..src + visible_width * 4]);
         }
 
         Ok(())
     }
 
+    /// The stitched image, for drawing overlays onto before encoding.
+    pub fn raster(&mut self) -> Result<Raster<'_>, String> {
+        Raster::new(&mut self.pixels, self.width, self.height)
+    }
+
     /// Encodes the stitched image as a PNG and frees the pixel buffer.
     pub fn encode_png(&mut self) -> Result<Vec<u8>, String> {
-        let png = crate::encode_png(
-            &self.pixels,
-            self.cols * self.tile_width,
-            self.rows * self.tile_height,
-        )?;
+        let png = crate::encode_png(&self.pixels, self.width, self.height)?;
         self.pixels = Vec::new();
         Ok(png)
     }
 
     #[cfg(test)]
     fn pixel_at(&self, x: u32, y: u32) -> [u8; 4] {
-        let image_width = self.cols * self.tile_width;
-        let i = ((y * image_width + x) * 4) as usize;
+        let i = ((y * self.width + x) * 4) as usize;
         self.pixels[i..i + 4].try_into().unwrap()
     }
 }
@@ -127,6 +149,30 @@ mod tests {
         assert_eq!(stitcher.pixel_at(1, 1), [3, 0, 0, 255]);
     }
 
+    #[test]
+    fn trims_tiles_that_overshoot_the_size() {
+        // 7x5 from 3x3 tiles needs a 3x2 grid, 9x6 pixels in all
+        let mut stitcher = TileStitcher::default();
+        stitcher.set_layout(3, 3, 2, 3);
+        stitcher.set_size(7, 5);
+
+        for row in 0..2 {
+            for col in 0..3 {
+                let color = [(row * 3 + col) as u8 + 1, 0, 0, 255];
+                stitcher.push_pixels(&solid_tile(3, 3, color), row, col).unwrap();
+            }
+        }
+
+        let raster = stitcher.raster().unwrap();
+        assert_eq!((raster.width(), raster.height()), (7, 5));
+        assert_eq!(stitcher.pixels.len(), 7 * 5 * 4);
+        // The last column and row keep only the pixels inside the image
+        assert_eq!(stitcher.pixel_at(6, 0), [3, 0, 0, 255]);
+        assert_eq!(stitcher.pixel_at(0, 4), [4, 0, 0, 255]);
+        assert_eq!(stitcher.pixel_at(6, 4), [6, 0, 0, 255]);
+        assert_eq!(stitcher.pixel_at(5, 3), [5, 0, 0, 255]);
+    }
+
     #[test]
     fn rejects_out_of_bounds_and_missized_tiles() {
         let mut stitcher = TileStitcher::default();
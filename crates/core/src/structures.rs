@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::brick::Brick;
+use crate::merge::{touching_groups, Rect};
+use crate::save::SaveData;
+use crate::util;
+
+/// A separate build: bricks joined by touching one another, side by side
+/// or stacked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Structure {
+    /// Extent of its brick footprints, in absolute save units
+    pub bounds: Rect,
+    /// Lowest bottom and highest top surface
+    pub heights: (i32, i32),
+    pub bricks: usize,
+    /// Whoever placed the most of its bricks, unless they're all public
+    pub owner: Option<String>,
+}
+
+impl Structure {
+    /// From its lowest brick to its highest, in save units.
+    pub fn height(&self) -> i32 {
+        self.heights.1 - self.heights.0
+    }
+}
+
+/// Finds the structures in `save`, largest first.
+pub fn find_structures(save: &SaveData) -> Vec<Structure> {
+    group_structures(save.all_bricks(), &save.owners)
+}
+
+/// Groups bricks into structures: two bricks join when their footprints
+/// touch or overlap and so do their height ranges. Each brick's owner
+/// indexes `owners` from 1, 0 being public. Largest first.
+pub fn group_structures<'a>(bricks: impl Iterator<Item = &'a Brick>, owners: &[String]) -> Vec<Structure> {
+    let mut boxes: Vec<([i32; 3], [i32; 3])> = Vec::new();
+    let mut brick_owners: Vec<Option<usize>> = Vec::new();
+    for brick in bricks {
+        let size = util::sizer(brick);
+        let (x, y, _) = brick.position;
+        boxes.push((
+            [x - size.0 as i32, y - size.1 as i32, util::bottom_surface(brick)],
+            [x + size.0 as i32, y + size.1 as i32, util::top_surface(brick)],
+        ));
+        brick_owners.push((brick.owner as usize).checked_sub(1).filter(|&i| i < owners.len()));
+    }
+
+    let mut structures: Vec<Structure> = touching_groups(&boxes)
+        .into_iter()
+        .map(|members| {
+            let (mut lo, mut hi) = ([i32::MAX; 3], [i32::MIN; 3]);
+            let mut counts: HashMap<usize, usize> = HashMap::new();
+            for &i in &members {
+                for k in 0..3 {
+                    lo[k] = lo[k].min(boxes[i].0[k]);
+                    hi[k] = hi[k].max(boxes[i].1[k]);
+                }
+                if let Some(owner) = brick_owners[i] {
+                    *counts.entry(owner).or_default() += 1;
+                }
+            }
+            // Ties go to the owner listed first
+            let owner = counts
+                .into_iter()
+                .max_by_key(|&(owner, count)| (count, std::cmp::Reverse(owner)))
+                .map(|(owner, _)| owners[owner].clone());
+            Structure {
+                bounds: (lo[0], lo[1], hi[0], hi[1]),
+                heights: (lo[2], hi[2]),
+                bricks: members.len(),
+                owner,
+            }
+        })
+        .collect();
+    // Deterministic whatever the input order: largest, then westmost, first
+    structures.sort_unstable_by_key(|s| (std::cmp::Reverse(s.bricks), s.bounds, s.heights));
+    structures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touching_bricks_form_one_structure() {
+        // A tower of three stacked bricks beside a wall it touches, a brick
+        // floating above the wall, and a hut across the way
+        let owned = |owner: u32, brick: Brick| Brick { owner, ..brick };
+        let bricks = [
+            owned(1, Brick::sized((0, 0, 6), (10, 10, 6))),
+            owned(2, Brick::sized((0, 0, 18), (10, 10, 6))),
+            owned(2, Brick::sized((0, 0, 30), (10, 10, 6))),
+            owned(1, Brick::sized((15, 0, 12), (5, 20, 12))),
+            Brick::sized((15, 0, 50), (5, 5, 6)),
+            owned(3, Brick::sized((500, 500, 6), (20, 20, 6))),
+        ];
+        let owners = ["Ann", "Bo", "Cy"].map(String::from);
+        let structures = group_structures(bricks.iter(), &owners);
+
+        assert_eq!(structures.len(), 3);
+        // Ann and Bo placed two each; Ann comes first
+        assert_eq!(
+            structures[0],
+            Structure { bounds: (-10, -20, 20, 20), heights: (0, 36), bricks: 4, owner: Some("Ann".to_string()) }
+        );
+        assert_eq!(structures[0].height(), 36);
+        assert_eq!(structures[1].bricks, 1);
+        assert_eq!(structures[1].owner, None);
+        assert_eq!(structures[2].owner.as_deref(), Some("Cy"));
+    }
+
+    #[test]
+    fn towers_of_identical_bricks_keep_every_brick() {
+        let mut save = SaveData::new(String::new(), 3, (0, 0), (0, 0, 100, 100));
+        // A tower of three identical bricks, of which top-down maps only
+        // keep the top one
+        save.push_chunk(0, (0..3).map(|level| Brick::sized((10, 10, 2 + 4 * level), (10, 10, 2))).collect());
+        let structures = find_structures(&save);
+        assert_eq!(structures.len(), 1);
+        assert_eq!(structures[0].bricks, 3);
+        assert_eq!(structures[0].heights, (0, 12));
+    }
+}
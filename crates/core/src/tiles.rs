@@ -0,0 +1,123 @@
+use crate::overlay::ImageView;
+use crate::render::Background;
+use crate::{Renderer, SaveData};
+
+/// Pixel width and height of every tile.
+pub const TILE_SIZE: u32 = 256;
+
+// Deepest zoom offered, whatever the build's size. At 2^20 tiles across even
+// the largest worlds are past one pixel per save unit.
+const MAX_ZOOM: u32 = 20;
+
+/// Slippy-map tiling of a build for map viewers like Leaflet. At zoom 0 one
+/// tile covers the build's longer side, and each zoom doubles the scale.
+/// Tile x grows with world x and tile y with world y, so the unrotated map
+/// reads the same way as the rendered PNG.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TileGrid {
+    /// Top-left corner of tile (0, 0, 0), in save units relative to the
+    /// centroid
+    pub origin: (f32, f32),
+    /// Save units across one zoom 0 tile
+    pub extent: f32,
+}
+
+impl TileGrid {
+    /// The grid over `bounds` (x1, y1, x2, y2 in save units relative to the
+    /// centroid, like [`SaveData::bounds`]), centered on them.
+    pub fn new(bounds: (i32, i32, i32, i32)) -> TileGrid {
+        let (x1, y1, x2, y2) = bounds;
+        let extent = (x2 - x1).max(y2 - y1).max(1) as f32;
+        let mid = ((x1 + x2) as f32 / 2.0, (y1 + y2) as f32 / 2.0);
+        TileGrid {
+            origin: (mid.0 - extent / 2.0, mid.1 - extent / 2.0),
+            extent,
+        }
+    }
+
+    /// Pixels per save unit at `zoom`.
+    pub fn scale(&self, zoom: u32) -> f32 {
+        (TILE_SIZE << zoom) as f32 / self.extent
+    }
+
+    /// The first zoom drawn at `scale` or finer.
+    pub fn zoom_for(&self, scale: f32) -> u32 {
+        (0..MAX_ZOOM).find(|&zoom| self.scale(zoom) >= scale).unwrap_or(MAX_ZOOM)
+    }
+
+    /// Whether (zoom, x, y) names a tile of the grid.
+    pub fn contains(&self, zoom: u32, x: u32, y: u32) -> bool {
+        zoom <= MAX_ZOOM && x < 1 << zoom && y < 1 << zoom
+    }
+
+    /// The tile's area as x1, y1, x2, y2 in centroid-relative save units.
+    pub fn tile_bounds(&self, zoom: u32, x: u32, y: u32) -> (f32, f32, f32, f32) {
+        let size = self.extent / (1u32 << zoom) as f32;
+        let x1 = self.origin.0 + x as f32 * size;
+        let y1 = self.origin.1 + y as f32 * size;
+        (x1, y1, x1 + size, y1 + size)
+    }
+
+    /// The range of tile columns and rows at `zoom` touching `bounds`
+    /// (centroid-relative save units), as x1, y1, x2, y2 inclusive.
+    pub fn tiles_over(&self, zoom: u32, bounds: (i32, i32, i32, i32)) -> (u32, u32, u32, u32) {
+        let size = self.extent / (1u32 << zoom) as f32;
+        let last = (1u32 << zoom) - 1;
+        let column = |x: i32| (((x as f32 - self.origin.0) / size).floor().max(0.0) as u32).min(last);
+        let row = |y: i32| (((y as f32 - self.origin.1) / size).floor().max(0.0) as u32).min(last);
+        (column(bounds.0), row(bounds.1), column(bounds.2), row(bounds.3))
+    }
+
+    /// The tile as an image, for shading or drawing over once rendered.
+    pub fn tile_view(&self, save: &SaveData, zoom: u32, x: u32, y: u32) -> ImageView {
+        let (x1, y1, x2, y2) = self.tile_bounds(zoom, x, y);
+        ImageView {
+            width: TILE_SIZE,
+            height: TILE_SIZE,
+            matrix: save.view_matrix(
+                TILE_SIZE as f32,
+                TILE_SIZE as f32,
+                -(x1 + x2) / 2.0,
+                -(y1 + y2) / 2.0,
+                self.scale(zoom),
+                0.0,
+            ),
+        }
+    }
+
+    /// Renders one tile from the geometry already on `renderer`, as RGBA
+    /// pixels.
+    pub fn render_tile(
+        &self,
+        save: &SaveData,
+        renderer: &Renderer,
+        zoom: u32,
+        x: u32,
+        y: u32,
+        background: &Background,
+    ) -> Result<Vec<u8>, String> {
+        let view = self.tile_view(save, zoom, x, y);
+        renderer.render_to_pixels(TILE_SIZE, TILE_SIZE, &view.matrix, background)?.finish_blocking()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_is_square_over_the_longer_side() {
+        let grid = TileGrid::new((-100, -50, 300, 50));
+        assert_eq!(grid.extent, 400.0);
+        assert_eq!(grid.origin, (-100.0, -200.0));
+        assert_eq!(grid.scale(0), 0.64);
+        assert_eq!(grid.scale(2), 2.56);
+        assert_eq!(grid.zoom_for(1.0), 1);
+        assert_eq!(grid.tile_bounds(1, 1, 0), (100.0, -200.0, 300.0, 0.0));
+
+        // The build is a band across the middle rows
+        assert_eq!(grid.tiles_over(2, (-100, -50, 300, 50)), (0, 1, 3, 2));
+        assert!(grid.contains(2, 3, 3));
+        assert!(!grid.contains(2, 4, 0));
+    }
+}
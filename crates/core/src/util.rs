@@ -49,12 +49,13 @@ pub fn transform_size(name: &str, procedural_size: (u32, u32, u32), rotation: Ro
 }
 
 /// Converts a parsed legacy-format brick into a render-ready one, dropping
-/// invisible bricks. `color` is the already-resolved display color.
+/// invisible bricks and any of an unknown type. `color` is the
+/// already-resolved display color.
 pub fn slim_brick(brick: &brickadia::save::Brick, brick_assets: &[String], color: [u8; 4]) -> Option<Brick> {
     if !brick.visibility {
         return None;
     }
-    let name = &brick_assets[brick.asset_name_index as usize];
+    let name = brick_assets.get(brick.asset_name_index as usize)?;
     let procedural_size = match brick.size {
         Size::Empty => (0, 0, 0),
         Size::Procedural(x, y, z) => (x, y, z),
@@ -66,6 +67,8 @@ pub fn slim_brick(brick: &brickadia::save::Brick, brick_assets: &[String], color
         color,
         rotation: brick.rotation.clone(),
         direction: brick.direction.clone(),
+        owner: brick.owner_index,
+        material: brick.material_index.min(u16::MAX as u32) as u16,
     })
 }
 
@@ -135,6 +138,10 @@ pub fn top_surface(brick: &Brick) -> i32 {
     brick.position.2 + sizer(brick).2 as i32
 }
 
+pub fn bottom_surface(brick: &Brick) -> i32 {
+    brick.position.2 - sizer(brick).2 as i32
+}
+
 pub fn sizer(brick: &Brick) -> (u32, u32, u32) {
     brick.size_u32()
 }
@@ -1,11 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
 
-use brickadia::save::{Direction, Rotation};
-use brdb::{Brdb, BrFsReader, BrReader, BrickType, Brz, IntoReader};
+use brickadia::save::{self as legacy, Direction, Rotation, UnrealType};
+use brdb::{Brdb, BrdbValue, BrFsReader, BrReader, BrickType, Brz, IntoReader};
 use brdb::schema::BrdbSchemaGlobalData;
 
 use crate::brick::Brick;
+use crate::color::{byte_as_linear, byte_as_srgb};
+use crate::labels::{component_text, Label};
+use crate::legacy::LegacySave;
+use crate::markers::{brick_markers, Marker};
 use crate::util;
 
 // Saves written by CL13911 and later store brick colors already in sRGB.
@@ -28,15 +32,30 @@ const MAIN_GRID: usize = 1;
 pub trait ChunkSource {
     fn brick_count(&self) -> i32;
     fn description(&self) -> &str;
+    /// Names of whoever placed bricks, which bricks' owners index from 1
+    fn owners(&self) -> &[String];
     /// World-space xy rectangle covering every chunk plus overhang slack
     fn grid_bounds(&self) -> (i32, i32, i32, i32);
     /// Fraction of bricks parsed so far, 0.0..=1.0
     fn progress(&self) -> f32;
     /// Parses the next chunk into render-ready bricks. Returns the chunk's
-    /// z layer and its visible bricks, or `None` when exhausted.
-    fn next_chunk(&mut self, brick_assets: &mut Vec<String>) -> Result<Option<(i16, Vec<Brick>)>, String>;
+    /// z layer and its visible bricks, or `None` when exhausted. New brick
+    /// types and materials are added to `brick_assets` and `materials`.
+    /// Markers found along the way, hidden bricks included, go to
+    /// `markers`, and text from visible bricks' components to `labels`.
+    fn next_chunk(
+        &mut self,
+        brick_assets: &mut Vec<String>,
+        materials: &mut Vec<String>,
+        markers: &mut Vec<Marker>,
+        labels: &mut Vec<Label>,
+    ) -> Result<Option<(i16, Vec<Brick>)>, String>;
 }
 
+// A brick's components as .brs saves hold them: properties by name, under
+// each component's name
+type BrickComponents = HashMap<String, HashMap<String, UnrealType>>;
+
 struct ChunkStream<T: BrFsReader> {
     reader: BrReader<T>,
     // (chunk index, brick count), sorted z descending then y, x
@@ -45,9 +64,13 @@ struct ChunkStream<T: BrFsReader> {
     bricks_seen: u64,
     bricks_total: u64,
     global_data: Arc<BrdbSchemaGlobalData>,
+    // Chunks holding components; the rest have no component data to read
+    component_chunks: HashSet<brdb::ChunkIndex>,
     linear_colors: bool,
     asset_indices: HashMap<String, u32>,
+    material_indices: HashMap<String, u16>,
     description: String,
+    owners: Vec<String>,
     grid_bounds: (i32, i32, i32, i32),
 }
 
@@ -63,6 +86,24 @@ pub fn open_brdb(body: &[u8]) -> Result<Box<dyn ChunkSource>, String> {
     Ok(Box::new(ChunkStream::open(db.into_reader())?))
 }
 
+/// Reads every brick of a brz world's main grid, hidden ones included, into
+/// a legacy save for writing back out. Brick types, sizes, placement,
+/// colors, visibility, collision, owners, materials and components carry
+/// over; whatever the world held that didn't, such as entities, wires or
+/// exact colors, is listed in `lost`.
+pub fn read_legacy_brz(body: &[u8]) -> Result<LegacySave, String> {
+    let brz = Brz::read_slice(body)
+        .map_err(|e| format!("brdb error reading brz archive: {}", e))?;
+    ChunkStream::open(brz.into_reader())?.into_legacy()
+}
+
+/// Like [`read_legacy_brz`], for a brdb world.
+pub fn read_legacy_brdb(body: &[u8]) -> Result<LegacySave, String> {
+    let db = Brdb::from_bytes(body)
+        .map_err(|e| format!("brdb error opening database: {}", e))?;
+    ChunkStream::open(db.into_reader())?.into_legacy()
+}
+
 impl<T: BrFsReader> ChunkStream<T> {
     fn open(reader: BrReader<T>) -> Result<ChunkStream<T>, String> {
         let global_data = reader.global_data()
@@ -73,6 +114,8 @@ impl<T: BrFsReader> ChunkStream<T> {
             .and_then(|b| parse_changelist(&b.game_version))
             .is_some_and(|cl| cl < SRGB_COLOR_CHANGELIST);
         let description = bundle.map(|b| b.description).unwrap_or_default();
+        // A world without an owner table is all public
+        let owners = reader.owners_soa().map(|owners| owners.user_names).unwrap_or_default();
 
         // Dynamic brick grids (vehicles etc.) are positioned by entity
         // transforms the renderer doesn't model, so only the main grid is
@@ -97,6 +140,12 @@ impl<T: BrFsReader> ChunkStream<T> {
         grid_bounds.2 += CHUNK_OVERHANG;
         grid_bounds.3 += CHUNK_OVERHANG;
 
+        let component_chunks = reader.component_chunk_index(MAIN_GRID)
+            .map_err(|e| format!("brdb error reading component chunk index: {}", e))?
+            .into_iter()
+            .map(|meta| meta.index)
+            .collect();
+
         // Top layer first for the occlusion grid; y/x order within a layer
         // keeps batches spatially coherent and the draw order deterministic
         chunks.sort_unstable_by_key(|(i, _)| (std::cmp::Reverse(i.z), i.y, i.x));
@@ -108,13 +157,161 @@ impl<T: BrFsReader> ChunkStream<T> {
             bricks_seen: 0,
             bricks_total,
             global_data,
+            component_chunks,
             linear_colors,
             asset_indices: HashMap::new(),
+            material_indices: HashMap::new(),
             description,
+            owners,
             grid_bounds,
         })
     }
 
+    // Decodes a chunk's components, keyed by their brick's place in the
+    // chunk's brick order. Properties whose type .brs lacks are named in
+    // `coerced` as "Component.property".
+    fn chunk_components(
+        &self,
+        index: brdb::ChunkIndex,
+        coerced: &mut BTreeSet<String>,
+    ) -> Result<HashMap<usize, BrickComponents>, String> {
+        let mut components: HashMap<usize, BrickComponents> = HashMap::new();
+        if !self.component_chunks.contains(&index) {
+            return Ok(components);
+        }
+        let soa = self.reader.component_chunk_soa(MAIN_GRID, index)
+            .map_err(|e| format!("brdb error reading components of chunk {}: {}", index, e))?;
+        for component in soa.iter_components(index, self.global_data.clone()) {
+            let (brick, component) = component
+                .map_err(|e| format!("brdb error reading component in chunk {}: {}", index, e))?;
+            let properties = component.properties.into_iter()
+                .map(|(name, value)| {
+                    if matches!(value, BrdbValue::I32(_)) {
+                        coerced.insert(format!("{}.{}", component.type_name, name));
+                    }
+                    (name, convert_value(value))
+                })
+                .collect();
+            components.entry(brick).or_default().insert(component.type_name, properties);
+        }
+        Ok(components)
+    }
+
+    fn into_legacy(self) -> Result<LegacySave, String> {
+        let mut save = legacy::SaveData::default();
+        save.header1.description = self.description.clone();
+        save.header2.brick_assets.clear();
+        save.header2.materials.clear();
+
+        // A world without an owner table is all public
+        if let Ok(owners) = self.reader.owners_soa() {
+            for (id, name) in owners.user_ids.iter().zip(&owners.user_names) {
+                let id = owner_uuid(id).parse().map_err(|_| format!("owner {} has an invalid id", name))?;
+                save.header2.brick_owners.push(legacy::BrickOwner { id, name: name.clone(), bricks: 0 });
+            }
+        }
+
+        let mut asset_indices: HashMap<String, u32> = HashMap::new();
+        let mut material_indices: HashMap<String, u32> = HashMap::new();
+        let mut coerced = BTreeSet::new();
+        // Bricks whose sRGB color has no exact linear byte value
+        let mut rounded = 0;
+        for &(index, num_bricks) in &self.chunks {
+            let soa = self.reader.brick_chunk_soa(MAIN_GRID, index)
+                .map_err(|e| format!("brdb error reading chunk {}: {}", index, e))?;
+            let mut components = self.chunk_components(index, &mut coerced)?;
+            save.bricks.reserve(num_bricks as usize);
+            for (i, brick) in soa.iter_bricks(index, self.global_data.clone()).enumerate() {
+                let brick = brick
+                    .map_err(|e| format!("brdb error reading brick in chunk {}: {}", index, e))?;
+
+                let assets = &mut save.header2.brick_assets;
+                let asset_name_index = *asset_indices.entry(brick.asset.asset().to_string()).or_insert_with_key(|name| {
+                    assets.push(name.clone());
+                    assets.len() as u32 - 1
+                });
+                let materials = &mut save.header2.materials;
+                let material_index = *material_indices.entry(brick.material.to_string()).or_insert_with_key(|name| {
+                    materials.push(name.clone());
+                    materials.len() as u32 - 1
+                });
+
+                let size = match &brick.asset {
+                    BrickType::Procedural { size, .. } => legacy::Size::Procedural(size.x as u32, size.y as u32, size.z as u32),
+                    BrickType::Basic(_) => legacy::Size::Empty,
+                };
+
+                // .brs stores linear color
+                let [r, g, b] = [brick.color.r, brick.color.g, brick.color.b];
+                let color = match self.linear_colors {
+                    true => legacy::Color { r, g, b, a: 255 },
+                    false => {
+                        let color = legacy::Color { r: byte_as_linear(r), g: byte_as_linear(g), b: byte_as_linear(b), a: 255 };
+                        if [color.r, color.g, color.b].map(byte_as_srgb) != [r, g, b] {
+                            rounded += 1;
+                        }
+                        color
+                    }
+                };
+
+                // .brs owner 0 is public; the rest index the owner list
+                // from 1
+                let owner = brick.owner_index.filter(|&owner| owner < save.header2.brick_owners.len());
+                if let Some(owner) = owner {
+                    save.header2.brick_owners[owner].bricks += 1;
+                }
+
+                let brick_index = save.bricks.len() as u32;
+                let brick_components = components.remove(&i).unwrap_or_default();
+                for (name, properties) in &brick_components {
+                    let component = save.components.entry(name.clone()).or_insert_with(|| legacy::Component {
+                        version: 1,
+                        ..Default::default()
+                    });
+                    component.brick_indices.push(brick_index);
+                    for (property, value) in properties {
+                        component.properties.entry(property.clone()).or_insert_with(|| type_name(value).to_string());
+                    }
+                }
+
+                save.bricks.push(legacy::Brick {
+                    asset_name_index,
+                    size,
+                    position: (brick.position.x, brick.position.y, brick.position.z),
+                    direction: convert_direction(brick.direction),
+                    rotation: convert_rotation(brick.rotation),
+                    collision: legacy::BrickCollision {
+                        player: brick.collision.player,
+                        weapon: brick.collision.weapon,
+                        interaction: brick.collision.interaction,
+                        tool: brick.collision.tool,
+                    },
+                    visibility: brick.visible,
+                    material_index,
+                    material_intensity: brick.material_intensity as u32,
+                    color: legacy::BrickColor::Unique(color),
+                    owner_index: owner.map_or(0, |owner| owner as u32 + 1),
+                    components: brick_components,
+                    ..Default::default()
+                });
+            }
+        }
+        save.header1.brick_count = save.bricks.len() as u32;
+
+        let mut lost = Vec::new();
+        // Dynamic brick grids (vehicles etc.) are entities, off the main grid
+        if self.reader.entity_chunk_index().is_ok_and(|chunks| !chunks.is_empty()) {
+            lost.push("entities, such as dynamic brick grids (only the main grid is read)".to_string());
+        }
+        if self.reader.wire_chunk_index(MAIN_GRID).is_ok_and(|chunks| !chunks.is_empty()) {
+            lost.push("wires between components".to_string());
+        }
+        lost.extend(coerced.into_iter().map(|property| format!("the integer type of {}, now a float", property)));
+        if rounded > 0 {
+            lost.push(format!("exact colors of {} bricks, rounded to the nearest linear value", rounded));
+        }
+        Ok(LegacySave { save, lost })
+    }
 }
 
 impl<T: BrFsReader> ChunkSource for ChunkStream<T> {
@@ -126,6 +323,10 @@ impl<T: BrFsReader> ChunkSource for ChunkStream<T> {
         &self.description
     }
 
+    fn owners(&self) -> &[String] {
+        &self.owners
+    }
+
     fn grid_bounds(&self) -> (i32, i32, i32, i32) {
         self.grid_bounds
     }
@@ -137,7 +338,13 @@ impl<T: BrFsReader> ChunkSource for ChunkStream<T> {
         self.bricks_seen as f32 / self.bricks_total as f32
     }
 
-    fn next_chunk(&mut self, brick_assets: &mut Vec<String>) -> Result<Option<(i16, Vec<Brick>)>, String> {
+    fn next_chunk(
+        &mut self,
+        brick_assets: &mut Vec<String>,
+        materials: &mut Vec<String>,
+        markers: &mut Vec<Marker>,
+        labels: &mut Vec<Label>,
+    ) -> Result<Option<(i16, Vec<Brick>)>, String> {
         let Some(&(index, num_bricks)) = self.chunks.get(self.next) else {
             return Ok(None);
         };
@@ -147,21 +354,38 @@ impl<T: BrFsReader> ChunkSource for ChunkStream<T> {
         let soa = self.reader.brick_chunk_soa(MAIN_GRID, index)
             .map_err(|e| format!("brdb error reading chunk {}: {}", index, e))?;
 
+        // Rendering doesn't mind what .brs would have coerced
+        let components = self.chunk_components(index, &mut BTreeSet::new())?;
+        let no_components = BrickComponents::new();
+
         let mut bricks: Vec<Brick> = Vec::with_capacity(num_bricks as usize);
-        for brick in soa.iter_bricks(index, self.global_data.clone()) {
+        for (i, brick) in soa.iter_bricks(index, self.global_data.clone()).enumerate() {
             let brick = brick
                 .map_err(|e| format!("brdb error reading brick in chunk {}: {}", index, e))?;
+            let position = (brick.position.x, brick.position.y, brick.position.z);
+
+            let brick_components = components.get(&i);
+            markers.extend(brick_markers(brick.asset.asset(), brick_components.unwrap_or(&no_components), position));
 
             if !brick.visible {
                 continue;
             }
 
+            if let Some(text) = brick_components.and_then(component_text) {
+                labels.push(Label { position, text });
+            }
+
             let name = brick.asset.asset().to_string();
             let next_index = brick_assets.len() as u32;
             let asset_name_index = *self.asset_indices.entry(name).or_insert_with_key(|name| {
                 brick_assets.push(name.clone());
                 next_index
             });
+            let next_index = materials.len().min(u16::MAX as usize) as u16;
+            let material = *self.material_indices.entry(brick.material.to_string()).or_insert_with_key(|name| {
+                materials.push(name.clone());
+                next_index
+            });
 
             let procedural_size = match brick.asset {
                 BrickType::Procedural { size, .. } => (size.x as u32, size.y as u32, size.z as u32),
@@ -181,7 +405,7 @@ impl<T: BrFsReader> ChunkSource for ChunkStream<T> {
             let rotation = convert_rotation(brick.rotation);
             let direction = convert_direction(brick.direction);
             bricks.push(Brick {
-                position: (brick.position.x, brick.position.y, brick.position.z),
+                position,
                 size: util::transform_size(
                     &brick_assets[asset_name_index as usize],
                     procedural_size,
@@ -192,6 +416,8 @@ impl<T: BrFsReader> ChunkSource for ChunkStream<T> {
                 color: color.to_bytes(),
                 rotation,
                 direction,
+                owner: brick.owner_index.filter(|&owner| owner < self.owners.len()).map_or(0, |owner| owner as u32 + 1),
+                material,
             });
         }
 
@@ -199,6 +425,12 @@ impl<T: BrFsReader> ChunkSource for ChunkStream<T> {
     }
 }
 
+// A world's owner ids are Unreal GUIDs, whose four words written out in order
+// spell the uuid .brs saves hold
+fn owner_uuid(id: &brdb::Guid) -> String {
+    format!("{:08x}{:08x}{:08x}{:08x}", id.a, id.b, id.c, id.d)
+}
+
 // Bundle.json game versions look like "CL13911". Missing, unparsable, and the
 // "CL0" placeholder tooling writes all mean "unknown", which is treated as
 // current rather than assuming an ancient save.
@@ -209,6 +441,34 @@ fn parse_changelist(game_version: &str) -> Option<u32> {
     }
 }
 
+// A component property as .brs saves type it. Asset references become the
+// class names .brs stores, and integers floats.
+fn convert_value(value: BrdbValue) -> UnrealType {
+    match value {
+        BrdbValue::Bool(value) => UnrealType::Boolean(value),
+        BrdbValue::U8(value) => UnrealType::Byte(value),
+        BrdbValue::I32(value) => UnrealType::Float(value as f32),
+        BrdbValue::F32(value) => UnrealType::Float(value),
+        BrdbValue::String(value) => UnrealType::String(value),
+        BrdbValue::Asset(value) => UnrealType::Class(value.unwrap_or_else(|| "None".to_string())),
+        BrdbValue::Color(color) => UnrealType::Color(legacy::Color { r: color.r, g: color.g, b: color.b, a: 255 }),
+        BrdbValue::Rotator(pitch, yaw, roll) => UnrealType::Rotator(pitch, yaw, roll),
+    }
+}
+
+// Type of a component property in a .brs component's property list
+fn type_name(value: &UnrealType) -> &'static str {
+    match value {
+        UnrealType::Class(_) => "Class",
+        UnrealType::String(_) => "String",
+        UnrealType::Boolean(_) => "Boolean",
+        UnrealType::Float(_) => "Float",
+        UnrealType::Color(_) => "Color",
+        UnrealType::Byte(_) => "Byte",
+        UnrealType::Rotator(..) => "Rotator",
+    }
+}
+
 fn convert_direction(direction: brdb::Direction) -> Direction {
     match direction {
         brdb::Direction::XPositive => Direction::XPositive,
@@ -228,3 +488,22 @@ fn convert_rotation(rotation: brdb::Rotation) -> Rotation {
         brdb::Rotation::Deg270 => Rotation::Deg270,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_components_read_like_brs_ones() {
+        let mut components: BrickComponents = HashMap::new();
+        let properties = [
+            ("Message".to_string(), BrdbValue::String("<b>Bank</b>".to_string())),
+            ("bPlayInteractSound".to_string(), BrdbValue::Bool(true)),
+        ];
+        components.insert(
+            "Component_Internal_Interact".to_string(),
+            properties.into_iter().map(|(name, value)| (name, convert_value(value))).collect(),
+        );
+        assert_eq!(component_text(&components).as_deref(), Some("Bank"));
+    }
+}
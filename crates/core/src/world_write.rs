@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use brickadia::save::{self as legacy, BrickColor, Direction, Preview, Rotation, UnrealType};
+use brdb::{BrdbValue, BrickType, World};
+
+use crate::color::{byte_as_linear, byte_as_srgb};
+use crate::save::SaveFormat;
+
+// Material for bricks whose material index is out of range
+const DEFAULT_MATERIAL: &str = "BMC_Plastic";
+
+/// Writes a legacy save as a world in `format`, .brz or .brdb, at `path`.
+/// Brick types, sizes, placement, colors, visibility, collision, owners,
+/// materials and components carry over. Returns what the world couldn't
+/// hold.
+pub fn write_world(save: &legacy::SaveData, format: SaveFormat, path: &Path) -> Result<Vec<String>, String> {
+    let mut world = World::new();
+    world.meta.bundle.description = save.header1.description.clone();
+    world.owners = save.header2.brick_owners.iter()
+        .map(|owner| {
+            // The uuid's words in order make up the GUID worlds hold
+            let id = owner.id.as_u128();
+            brdb::Owner {
+                id: brdb::Guid { a: (id >> 96) as u32, b: (id >> 64) as u32, c: (id >> 32) as u32, d: id as u32 },
+                name: owner.name.clone(),
+                display_name: owner.name.clone(),
+            }
+        })
+        .collect();
+
+    let assets = &save.header2.brick_assets;
+    // Bricks whose linear color has no exact sRGB byte value, and bricks
+    // that weren't opaque
+    let (mut rounded, mut translucent) = (0, 0);
+    world.bricks.reserve(save.bricks.len());
+    for brick in &save.bricks {
+        let Some(name) = assets.get(brick.asset_name_index as usize) else {
+            return Err(format!("brick asset index {} out of range", brick.asset_name_index));
+        };
+        let asset = match brick.size {
+            legacy::Size::Procedural(x, y, z) => BrickType::Procedural {
+                asset: name.as_str().into(),
+                size: (x as u16, y as u16, z as u16).into(),
+            },
+            legacy::Size::Empty => BrickType::Basic(name.as_str().into()),
+        };
+        let color = match &brick.color {
+            BrickColor::Index(index) => save.header2.colors.get(*index as usize)
+                .ok_or_else(|| format!("brick color index {} out of range", index))?,
+            BrickColor::Unique(color) => color,
+        };
+        // Worlds store display sRGB, .brs linear values
+        let srgb = [color.r, color.g, color.b].map(byte_as_srgb);
+        if srgb.map(byte_as_linear) != [color.r, color.g, color.b] {
+            rounded += 1;
+        }
+        if color.a != u8::MAX {
+            translucent += 1;
+        }
+        let material = save.header2.materials.get(brick.material_index as usize)
+            .map_or(DEFAULT_MATERIAL, String::as_str);
+
+        world.bricks.push(brdb::Brick {
+            asset,
+            // Owner 0 is public; the rest index the owner list from 1
+            owner_index: (brick.owner_index as usize).checked_sub(1).filter(|&i| i < world.owners.len()),
+            position: brick.position.into(),
+            rotation: convert_rotation(&brick.rotation),
+            direction: convert_direction(&brick.direction),
+            collision: brdb::Collision {
+                player: brick.collision.player,
+                weapon: brick.collision.weapon,
+                interaction: brick.collision.interaction,
+                tool: brick.collision.tool,
+            },
+            visible: brick.visibility,
+            color: brdb::Color { r: srgb[0], g: srgb[1], b: srgb[2] },
+            material: material.to_string(),
+            material_intensity: brick.material_intensity.min(u8::MAX as u32) as u8,
+            components: convert_components(&brick.components),
+        });
+    }
+
+    match format {
+        SaveFormat::Brz => world.write_brz(path),
+        SaveFormat::Brdb => world.write_brdb(path),
+        SaveFormat::Brs => return Err("write .brs saves with brickadia's writer".to_string()),
+    }
+    .map_err(|e| format!("brdb error writing {}: {}", path.display(), e))?;
+
+    let mut lost = Vec::new();
+    if !matches!(save.preview, Preview::None) {
+        lost.push("the preview image".to_string());
+    }
+    if rounded > 0 {
+        lost.push(format!("exact colors of {} bricks, rounded to the nearest sRGB value", rounded));
+    }
+    if translucent > 0 {
+        lost.push(format!("transparency of {} bricks", translucent));
+    }
+    Ok(lost)
+}
+
+// Sorted by name, so the same save always writes the same world
+fn convert_components(components: &HashMap<String, HashMap<String, UnrealType>>) -> Vec<brdb::Component> {
+    let mut names: Vec<&String> = components.keys().collect();
+    names.sort();
+    names.into_iter()
+        .map(|name| {
+            let mut properties: Vec<(String, BrdbValue)> = components[name].iter()
+                .map(|(property, value)| (property.clone(), convert_value(value)))
+                .collect();
+            properties.sort_by(|a, b| a.0.cmp(&b.0));
+            brdb::Component { type_name: name.clone(), properties }
+        })
+        .collect()
+}
+
+fn convert_value(value: &UnrealType) -> BrdbValue {
+    match value {
+        UnrealType::Class(class) if class == "None" => BrdbValue::Asset(None),
+        UnrealType::Class(class) => BrdbValue::Asset(Some(class.clone())),
+        UnrealType::String(text) => BrdbValue::String(text.clone()),
+        UnrealType::Boolean(value) => BrdbValue::Bool(*value),
+        UnrealType::Float(value) => BrdbValue::F32(*value),
+        UnrealType::Color(color) => BrdbValue::Color(brdb::Color { r: color.r, g: color.g, b: color.b }),
+        UnrealType::Byte(value) => BrdbValue::U8(*value),
+        UnrealType::Rotator(pitch, yaw, roll) => BrdbValue::Rotator(*pitch, *yaw, *roll),
+    }
+}
+
+fn convert_direction(direction: &Direction) -> brdb::Direction {
+    match direction {
+        Direction::XPositive => brdb::Direction::XPositive,
+        Direction::XNegative => brdb::Direction::XNegative,
+        Direction::YPositive => brdb::Direction::YPositive,
+        Direction::YNegative => brdb::Direction::YNegative,
+        Direction::ZPositive => brdb::Direction::ZPositive,
+        Direction::ZNegative => brdb::Direction::ZNegative,
+    }
+}
+
+fn convert_rotation(rotation: &Rotation) -> brdb::Rotation {
+    match rotation {
+        Rotation::Deg0 => brdb::Rotation::Deg0,
+        Rotation::Deg90 => brdb::Rotation::Deg90,
+        Rotation::Deg180 => brdb::Rotation::Deg180,
+        Rotation::Deg270 => brdb::Rotation::Deg270,
+    }
+}
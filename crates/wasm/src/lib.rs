@@ -1,5 +1,10 @@
-use brick_cartographer_core::render::PixelReadback;
-use brick_cartographer_core::save::{GeometryMode, GeometryState, SaveData, SaveLoading};
+use brick_cartographer_core::fit::{self, Fit};
+use brick_cartographer_core::markers::{MarkerKind, MarkerLayers};
+use brick_cartographer_core::overlay::{self, GridSpacing, ImageView, Overlays};
+use brick_cartographer_core::projection::Oblique;
+use brick_cartographer_core::raster::{Font, Raster};
+use brick_cartographer_core::render::{Background, OutlineWidth, PixelReadback};
+use brick_cartographer_core::save::{GeometryMode, GeometryState, OutlineColor, OutlineStyle, SaveData, SaveLoading};
 use brick_cartographer_core::{Renderer, TileStitcher};
 use js_sys::Array;
 use wasm_bindgen::prelude::*;
@@ -36,6 +41,17 @@ pub struct BRSProcessor {
     save: Option<SaveData>,
     geometry: GeometryState,
     renderer: Renderer,
+    background: Background,
+    // Drawn over exported images, not the live view
+    overlays: Overlays,
+    // Set through setFont before exporting overlays with text
+    font: Option<Font>,
+    // Some when the map is seen at an angle rather than from above
+    oblique: Option<Oblique>,
+    // The projected build's extent, kept while `oblique` is set
+    oblique_bounds: Option<(i32, i32, i32, i32)>,
+    // Mapping from below rather than above
+    underside: bool,
 }
 
 /// Opens a save and prepares the renderer. Bricks stream in through
@@ -51,13 +67,19 @@ pub async fn load_file(body: Vec<u8>) -> Result<BRSProcessor, JsValue> {
         .await
         .map_err(JsValue::from)?;
 
-    let geometry = GeometryState::new(loading.save(), GeometryMode::Map { outlines: false, fills: true });
+    let geometry = GeometryState::new(loading.save(), GeometryMode::Map { fills: true, merge: false, outlines: None, studs: false });
 
     Ok(BRSProcessor {
         loading: Some(loading),
         save: None,
         geometry,
         renderer,
+        background: Background::default(),
+        overlays: Overlays::default(),
+        font: None,
+        oblique: None,
+        oblique_bounds: None,
+        underside: false,
     })
 }
 
@@ -102,10 +124,14 @@ impl BRSProcessor {
 
         if done {
             let save = self.loading.take().unwrap().finish().map_err(JsValue::from)?;
-            // A heightmap scales to the save's height extent, which only now
-            // covers every chunk
-            if self.geometry.mode() == GeometryMode::Heightmap {
-                self.geometry = build_all(&save, GeometryMode::Heightmap, &mut self.renderer)?;
+            // A heightmap scales to the save's height extent, and a projection
+            // its depth to the build's reach, which only now cover every
+            // chunk. From below, culling only now sees every layer in order.
+            if self.geometry.mode() == GeometryMode::Heightmap || self.oblique.is_some() || self.underside {
+                self.geometry = build_all(&save, self.geometry.mode(), self.oblique, self.underside, &mut self.renderer)?;
+            }
+            if let Some(oblique) = self.oblique {
+                self.oblique_bounds = oblique.bounds(save.centroid, save.all_bricks());
             }
             log(&format!("Bricks Discarded: {}", save.discarded));
             log(&format!("Bricks Culled: {}", self.geometry.culled));
@@ -116,25 +142,195 @@ impl BRSProcessor {
     }
 
     /// Switches what the map shows; rebuilds geometry for everything loaded
-    /// so far. Streaming continues in the new mode.
+    /// so far. Streaming continues in the new mode. The optional trailing
+    /// arguments style outlines: `outline_width` in screen pixels (unset
+    /// keeps the default width that scales with zoom), `outline_color` as
+    /// "#rrggbb" or "darken", and `region_outlines` to outline only
+    /// boundaries between differently colored or elevated regions. `merge`
+    /// merges abutting same-colored bricks to save GPU memory. `studs` draws
+    /// studs and bevels on studded bricks, for close-up renders.
     #[wasm_bindgen(js_name = setViewMode)]
-    pub fn set_view_mode(&mut self, outlines: bool, fills: bool, heightmap: bool) -> Result<(), JsValue> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_view_mode(
+        &mut self,
+        outlines: bool,
+        fills: bool,
+        heightmap: bool,
+        merge: Option<bool>,
+        outline_width: Option<f32>,
+        outline_color: Option<String>,
+        region_outlines: Option<bool>,
+        studs: Option<bool>,
+    ) -> Result<(), JsValue> {
         let mode = if heightmap {
             GeometryMode::Heightmap
         } else {
-            GeometryMode::Map { outlines, fills }
+            let color = match outline_color {
+                Some(color) => color.parse::<OutlineColor>().map_err(JsValue::from)?,
+                None => OutlineStyle::default().color,
+            };
+            let style = OutlineStyle { color, regions: region_outlines.unwrap_or(false) };
+            GeometryMode::Map {
+                fills,
+                merge: merge.unwrap_or(false),
+                outlines: outlines.then_some(style),
+                studs: studs.unwrap_or(false),
+            }
         };
+        self.renderer.set_outline_width(match outline_width {
+            Some(px) if px > 0.0 => OutlineWidth::Pixels(px),
+            _ => OutlineWidth::default(),
+        });
         let save = match &self.loading {
             Some(loading) => loading.save(),
             None => self.save.as_ref().unwrap(),
         };
-        self.geometry = build_all(save, mode, &mut self.renderer)?;
+        self.geometry = build_all(save, mode, self.oblique, self.underside, &mut self.renderer)?;
         if self.loading.is_none() {
             log(&format!("Bricks Culled: {}", self.geometry.culled));
         }
         Ok(())
     }
 
+    /// Maps the build from below when `underside` is set, as a reflected
+    /// ceiling plan: north stays up and nothing mirrors, but wherever bricks
+    /// overlap the lowest shows. Rebuilds geometry for everything loaded.
+    #[wasm_bindgen(js_name = setUnderside)]
+    pub fn set_underside(&mut self, underside: bool) -> Result<(), JsValue> {
+        self.underside = underside;
+        let save = match &self.loading {
+            Some(loading) => loading.save(),
+            None => self.save.as_ref().unwrap(),
+        };
+        self.geometry = build_all(save, self.geometry.mode(), self.oblique, underside, &mut self.renderer)?;
+        Ok(())
+    }
+
+    /// Views the map at an angle so height shows: turned by `azimuth` and
+    /// seen from `elevation` above the horizon, both in degrees (45 and 30
+    /// when unset, the classic isometric look). With neither set the map is
+    /// top-down again. Overlays other than the scale bar are left off exported
+    /// images while projected, since they're laid out for the top-down map.
+    #[wasm_bindgen(js_name = setProjection)]
+    pub fn set_projection(&mut self, azimuth: Option<f32>, elevation: Option<f32>) -> Result<(), JsValue> {
+        let oblique = match (azimuth, elevation) {
+            (None, None) => None,
+            (azimuth, elevation) => {
+                Some(Oblique::new(azimuth.unwrap_or(45.0), elevation.unwrap_or(30.0)).map_err(JsValue::from)?)
+            }
+        };
+        self.project(oblique)
+    }
+
+    /// Shows a vertical cross-section along the line from (x1, y1) to
+    /// (x2, y2) in absolute save units, say two clicked points: the bricks
+    /// within `thickness` of it, distance along the line across and height
+    /// up. `setProjection()` returns to the map.
+    #[wasm_bindgen(js_name = setSection)]
+    pub fn set_section(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, thickness: i32) -> Result<(), JsValue> {
+        let section = Oblique::section((x1, y1), (x2, y2), thickness).map_err(JsValue::from)?;
+        self.project(Some(section))
+    }
+
+    fn project(&mut self, oblique: Option<Oblique>) -> Result<(), JsValue> {
+        self.oblique = oblique;
+        let save = match &self.loading {
+            Some(loading) => loading.save(),
+            None => self.save.as_ref().unwrap(),
+        };
+        self.oblique_bounds = oblique.and_then(|oblique| oblique.bounds(save.centroid, save.all_bricks()));
+        self.geometry = build_all(save, self.geometry.mode(), oblique, self.underside, &mut self.renderer)?;
+        Ok(())
+    }
+
+    // The extent views fit and screenshots cover: the projected build's
+    // while projected, otherwise the map's
+    fn view_bounds(&self) -> (i32, i32, i32, i32) {
+        match (self.oblique, self.oblique_bounds) {
+            (Some(_), Some(bounds)) => bounds,
+            _ => self.save_ref().bounds,
+        }
+    }
+
+    // Overlays placed by world position only line up with the top-down map;
+    // the scale bar still holds across the projected map. Any with text need
+    // a font set first.
+    fn overlays(&self) -> Result<Overlays, JsValue> {
+        let overlays = match self.oblique {
+            Some(_) => Overlays { scale_bar: self.overlays.scale_bar, ..Overlays::default() },
+            None => self.overlays,
+        };
+        if overlays.needs_font() && self.font.is_none() {
+            return Err(JsValue::from("overlays with text need a font; load one with setFont"));
+        }
+        Ok(overlays)
+    }
+
+    /// Sets the typeface for overlay text from the bytes of a TrueType or
+    /// OpenType file. The website build leaves the CLI's bundled font out to
+    /// stay small, so exports with text load it first.
+    #[wasm_bindgen(js_name = setFont)]
+    pub fn set_font(&mut self, data: Vec<u8>) -> Result<(), JsValue> {
+        self.font = Some(Font::from_bytes(data).map_err(JsValue::from)?);
+        Ok(())
+    }
+
+    /// Sets what shows behind the bricks, on screen and in screenshots:
+    /// "transparent" (the default, letting the page show through),
+    /// "#rrggbb[aa]", or "checker" / "grid" with an optional ":<studs>" size.
+    #[wasm_bindgen(js_name = setBackground)]
+    pub fn set_background(&mut self, background: &str) -> Result<(), JsValue> {
+        self.background = background.parse().map_err(JsValue::from)?;
+        Ok(())
+    }
+
+    /// Sets the overlays drawn over `renderToPng` screenshots and images
+    /// stitched by an `ImageCombiner`: a world grid every `grid` studs (or
+    /// "chunk"; unset for none), coordinate ticks, a scale bar, a north
+    /// arrow, and text labels from sign and interact components.
+    #[wasm_bindgen(js_name = setOverlays)]
+    pub fn set_overlays(
+        &mut self,
+        grid: Option<String>,
+        ticks: bool,
+        scale_bar: bool,
+        compass: bool,
+        labels: Option<bool>,
+    ) -> Result<(), JsValue> {
+        let grid = match grid {
+            Some(grid) => Some(grid.parse::<GridSpacing>().map_err(JsValue::from)?),
+            None => None,
+        };
+        self.overlays = Overlays {
+            grid,
+            ticks,
+            scale_bar,
+            compass,
+            labels: labels.unwrap_or(false),
+            markers: self.overlays.markers,
+        };
+        Ok(())
+    }
+
+    /// Toggles point-of-interest marker icons per category on exported
+    /// images.
+    #[wasm_bindgen(js_name = setMarkers)]
+    pub fn set_markers(&mut self, spawns: bool, lights: bool, audio: bool, interact: bool) {
+        self.overlays.markers = MarkerLayers { spawns, lights, audio, interact };
+    }
+
+    /// Marker counts per category as [spawns, lights, audio, interact], for
+    /// deciding which toggles to offer.
+    #[wasm_bindgen(js_name = markerCounts)]
+    pub fn marker_counts(&self) -> Array {
+        let counts = Array::new();
+        for kind in MarkerKind::ALL {
+            let count = self.save_ref().markers.iter().filter(|m| m.kind == kind).count();
+            counts.push(&JsValue::from(count as u32));
+        }
+        counts
+    }
+
     // Save info getters for frontend
     pub fn description(&self) -> String {
         self.save_ref().description.clone()
@@ -150,7 +346,7 @@ impl BRSProcessor {
         centroid
     }
     pub fn bounds(&self) -> Array {
-        let b = self.save_ref().bounds;
+        let b = self.view_bounds();
         let bounds = Array::new();
         bounds.push(&JsValue::from(b.0));
         bounds.push(&JsValue::from(b.1));
@@ -159,13 +355,21 @@ impl BRSProcessor {
         bounds
     }
 
+    // Scale at which the whole build, rotated, fits the given viewport
+    // inside `margin` pixels; undefined when the viewport is too small.
+    // Centering is up to the caller: the bounds' midpoint, negated, as pan.
+    #[wasm_bindgen(js_name = fitScale)]
+    pub fn fit_scale(&self, size_x: u32, size_y: u32, rotation: f32, margin: u32) -> Option<f32> {
+        fit::fit_scale(self.view_bounds(), rotation, Some(size_x), Some(size_y), margin, Fit::Contain)
+    }
+
     pub fn render(&mut self, size_x: i32, size_y: i32, pan_x: f32, pan_y: f32, scale: f32, rotation: f32) -> Result<(), JsValue> {
         if size_x <= 0 || size_y <= 0 {
             return Ok(());
         }
         let matrix = self.save_ref().view_matrix(size_x as f32, size_y as f32, pan_x, pan_y, scale, rotation);
         self.renderer
-            .render_to_surface(size_x as u32, size_y as u32, &matrix)
+            .render_to_surface(size_x as u32, size_y as u32, &matrix, &self.background)
             .map_err(JsValue::from)
     }
 
@@ -173,7 +377,7 @@ impl BRSProcessor {
     // tightly-packed RGBA pixels. Used for screenshot tiles.
     #[wasm_bindgen(js_name = renderToPixels)]
     pub fn render_to_pixels(&self, size_x: i32, size_y: i32, pan_x: f32, pan_y: f32, scale: f32, rotation: f32) -> Result<js_sys::Promise, JsValue> {
-        let readback = self.render_offscreen(size_x, size_y, pan_x, pan_y, scale, rotation)?;
+        let (readback, _) = self.render_offscreen(size_x, size_y, pan_x, pan_y, scale, rotation)?;
         Ok(future_to_promise(async move {
             let pixels = await_readback(readback).await?;
             Ok(js_sys::Uint8Array::from(pixels.as_slice()).into())
@@ -183,10 +387,18 @@ impl BRSProcessor {
     // Like renderToPixels, but resolves to an encoded PNG
     #[wasm_bindgen(js_name = renderToPng)]
     pub fn render_to_png(&self, size_x: i32, size_y: i32, pan_x: f32, pan_y: f32, scale: f32, rotation: f32) -> Result<js_sys::Promise, JsValue> {
-        let readback = self.render_offscreen(size_x, size_y, pan_x, pan_y, scale, rotation)?;
+        let (readback, view) = self.render_offscreen(size_x, size_y, pan_x, pan_y, scale, rotation)?;
         let (width, height) = (size_x as u32, size_y as u32);
+        let overlays = self.overlays()?;
+        let font = self.font.clone();
+        let labels = if overlays.labels { self.save_ref().labels.clone() } else { Vec::new() };
+        let markers = if overlays.markers.any() { self.save_ref().markers.clone() } else { Vec::new() };
         Ok(future_to_promise(async move {
-            let pixels = await_readback(readback).await?;
+            let mut pixels = await_readback(readback).await?;
+            if overlays.any() {
+                let mut raster = Raster::new(&mut pixels, width, height).map_err(JsValue::from)?;
+                overlay::draw_overlays(&mut raster, &view, &overlays, font.as_ref(), &labels, &markers);
+            }
             let png = brick_cartographer_core::encode_png(&pixels, width, height)
                 .map_err(JsValue::from)?;
             Ok(js_sys::Uint8Array::from(png.as_slice()).into())
@@ -201,14 +413,15 @@ impl BRSProcessor {
         pan_y: f32,
         scale: f32,
         rotation: f32,
-    ) -> Result<PixelReadback, JsValue> {
+    ) -> Result<(PixelReadback, ImageView), JsValue> {
         if size_x <= 0 || size_y <= 0 {
             return Err(JsValue::from("invalid render size"));
         }
         let matrix = self.save_ref().view_matrix(size_x as f32, size_y as f32, pan_x, pan_y, scale, rotation);
-        self.renderer
-            .render_to_pixels(size_x as u32, size_y as u32, &matrix)
-            .map_err(JsValue::from)
+        let readback = self.renderer
+            .render_to_pixels(size_x as u32, size_y as u32, &matrix, &self.background)
+            .map_err(JsValue::from)?;
+        Ok((readback, ImageView { width: size_x as u32, height: size_y as u32, matrix }))
     }
 }
 
@@ -236,9 +449,17 @@ async fn sleep_ms(ms: i32) {
     let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
 }
 
-fn build_all(save: &SaveData, mode: GeometryMode, renderer: &mut Renderer) -> Result<GeometryState, JsValue> {
+fn build_all(
+    save: &SaveData,
+    mode: GeometryMode,
+    oblique: Option<Oblique>,
+    underside: bool,
+    renderer: &mut Renderer,
+) -> Result<GeometryState, JsValue> {
     renderer.clear_batches();
     let mut state = GeometryState::new(save, mode);
+    state.set_projection(save, oblique);
+    state.set_underside(underside);
     state.build_pending(save, renderer).map_err(JsValue::from)?;
     state.flush(renderer);
     Ok(state)
@@ -262,11 +483,39 @@ impl ImageCombiner {
         self.stitcher.set_layout(tile_width, tile_height, rows, cols);
     }
 
+    /// Trims the combined image to `width` by `height`, after `setLayout`,
+    /// when the tiles overshoot it. Tiles are placed from the top-left.
+    #[wasm_bindgen(js_name = setSize)]
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.stitcher.set_size(width, height);
+    }
+
     #[wasm_bindgen(js_name = pushPixels)]
     pub fn push_pixels(&mut self, tile: &[u8], row: u32, col: u32) -> Result<(), JsValue> {
         self.stitcher.push_pixels(tile, row, col).map_err(JsValue::from)
     }
 
+    /// Draws `save`'s overlays over the stitched image. Takes the view of
+    /// the whole image as if it were rendered in one piece: the pan to its
+    /// center, the scale and the rotation.
+    #[wasm_bindgen(js_name = drawOverlays)]
+    pub fn draw_overlays(&mut self, save: &BRSProcessor, pan_x: f32, pan_y: f32, scale: f32, rotation: f32) -> Result<(), JsValue> {
+        let overlays = save.overlays()?;
+        if !overlays.any() {
+            return Ok(());
+        }
+        let mut raster = self.stitcher.raster().map_err(JsValue::from)?;
+        let (width, height) = (raster.width(), raster.height());
+        let view = ImageView {
+            width,
+            height,
+            matrix: save.save_ref().view_matrix(width as f32, height as f32, pan_x, pan_y, scale, rotation),
+        };
+        let data = save.save_ref();
+        overlay::draw_overlays(&mut raster, &view, &overlays, save.font.as_ref(), &data.labels, &data.markers);
+        Ok(())
+    }
+
     #[wasm_bindgen(js_name = combineImages)]
     pub fn combine_images(&mut self) -> Result<Vec<u8>, JsValue> {
         self.stitcher.encode_png().map_err(JsValue::from)
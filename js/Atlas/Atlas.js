@@ -1,6 +1,7 @@
 import { saveBlob } from "./util";
-import { ROTATE_CW, ROTATE_CCW, HOME, FULLSCREEN, BORDERS, FILL, MOUNTAIN, PHOTO, MAP, LEGO, LOAD, GITHUB } from "./icons";
+import { ROTATE_CW, ROTATE_CCW, HOME, FULLSCREEN, BORDERS, FILL, MOUNTAIN, CUBE, SECTION, UNDERSIDE, PHOTO, MAP, LEGO, LAYERS, LOAD, GITHUB } from "./icons";
 import ACM_City from "../../default_saves/ACM_City.brs";
+import OVERLAY_FONT from "../../crates/core/assets/DejaVuSans-Bold.ttf?url";
 import wasm from "../wasm";
 
 const DEFAULT_ROTATION = 0;
@@ -10,6 +11,13 @@ const MAX_SCALE = 20;
 const MIN_SCALE = 0.01;
 const DEFAULT_PAN = { x: 0, y: 0 };
 const SCROLL_INTENSITY = 1.2;
+const FIT_MARGIN = 32;
+const ISOMETRIC_AZIMUTH = 45;
+const ISOMETRIC_ELEVATION = 30;
+// Width in save units of the slice a section takes in around its line
+const SECTION_THICKNESS = 40;
+// A press that moves less than this many pixels is a click, not a drag
+const CLICK_SLOP = 4;
 const WEBGPU_NOTICE_DISMISSED = "webgpu-notice-dismissed";
 
 // navigator.gpu can exist while no adapter is actually obtainable, which is
@@ -32,6 +40,18 @@ export default class Atlas {
         this.showOutlines = false;
         this.fillBricks = true;
         this.showHeightmap = false;
+        this.isometric = false;
+        this.showSection = false;
+        this.showUnderside = false;
+        // What saved images get drawn over them; the map on screen never
+        // shows these
+        this.overlays = { grid: "", ticks: false, scaleBar: false, compass: false, labels: false };
+        // Marker icons per category on saved images
+        this.markers = { spawns: false, lights: false, audio: false, interact: false };
+        // The overlay font's bytes, fetched the first time one is turned on
+        this.font = null;
+        // Clicked world points while picking a section's line, else null
+        this.sectionPoints = null;
         this.isDragging = false;
         this.rotation = DEFAULT_ROTATION;
         this.scale = DEFAULT_SCALE;
@@ -61,16 +81,41 @@ export default class Atlas {
                 <div class="map-button zoom-out-button" title="Zoom Out">-</div>
                 <div class="map-button rotate-cw-button svg-button" title="Rotate CW">${ROTATE_CW}</div>
                 <div class="map-button rotate-ccw-button svg-button" title="Rotate CCW">${ROTATE_CCW}</div>
-                <div class="map-button home-button svg-button" title="Home Position">${HOME}</div>
+                <div class="map-button home-button svg-button" title="Fit Build to Screen">${HOME}</div>
                 <div class="map-button fullscreen-button svg-button" title="Toggle Fullscreen">${FULLSCREEN}</div>
                 <div class="button-label fill-label">FILL</div>
                 <div class="map-button border-button svg-button" title="Toggle Brick Borders">${BORDERS}</div>
                 <div class="map-button fill-button svg-button button-toggled" title="Toggle Brick Fill">${FILL}</div>
                 <div class="map-button heightmap-button svg-button" title="Toggle Heightmap">${MOUNTAIN}</div>
+                <div class="map-button isometric-button svg-button" title="Toggle Isometric View">${CUBE}</div>
+                <div class="map-button section-button svg-button" title="Cross-Section Between Two Clicked Points">${SECTION}</div>
+                <div class="map-button underside-button svg-button" title="Toggle View From Below">${UNDERSIDE}</div>
                 <div class="button-label save-label">SAVE</div>
                 <div class="map-button photo-button svg-button" title="Save Current View">${PHOTO}</div>
                 <div class="map-button hd-photo-button svg-button" title="Save Entire Map">${MAP}</div>
                 <div class="map-button zoom-photo-button svg-button" title="Save Entire Map x10 Zoom">${LEGO}</div>
+                <div class="map-button overlays-button svg-button" title="Overlays on Saved Images">${LAYERS}</div>
+                <div class="overlays-panel" style="display:none">
+                    <div class="overlays-title">On saved images</div>
+                    <label>Grid
+                        <select name="grid">
+                            <option value="">None</option>
+                            <option value="16">16 studs</option>
+                            <option value="32">32 studs</option>
+                            <option value="64">64 studs</option>
+                            <option value="chunk">Chunks</option>
+                        </select>
+                    </label>
+                    <label><input type="checkbox" name="ticks" /> Coordinates</label>
+                    <label><input type="checkbox" name="scaleBar" /> Scale bar</label>
+                    <label><input type="checkbox" name="compass" /> North arrow</label>
+                    <label><input type="checkbox" name="labels" /> Sign labels</label>
+                    <div class="overlays-title">Markers</div>
+                    <label><input type="checkbox" name="spawns" data-marker /> Spawns <span class="marker-count"></span></label>
+                    <label><input type="checkbox" name="lights" data-marker /> Lights <span class="marker-count"></span></label>
+                    <label><input type="checkbox" name="audio" data-marker /> Audio <span class="marker-count"></span></label>
+                    <label><input type="checkbox" name="interact" data-marker /> Interact <span class="marker-count"></span></label>
+                </div>
                 <div class="button-label load-label">LOAD</div>
                 <div class="map-button load-button svg-button" title="Load Build">${LOAD}</div>
                 <a class="github-button" href="https://github.com/Kmschr/BrickCartographer" target="_blank" rel="noopener noreferrer">${GITHUB}</a>
@@ -94,6 +139,11 @@ export default class Atlas {
         this.borderButton = $(".border-button");
         this.fillButton = $(".fill-button");
         this.heightmapButton = $(".heightmap-button");
+        this.isometricButton = $(".isometric-button");
+        this.sectionButton = $(".section-button");
+        this.undersideButton = $(".underside-button");
+        this.overlaysButton = $(".overlays-button");
+        this.overlaysPanel = $(".overlays-panel");
         this.el = { $ };
     }
 
@@ -115,14 +165,19 @@ export default class Atlas {
         $(".zoom-out-button").addEventListener("click", () => { this.zoomOut(); this.redraw(); });
         $(".rotate-cw-button").addEventListener("click", () => this.rotateCW());
         $(".rotate-ccw-button").addEventListener("click", () => this.rotateCCW());
-        $(".home-button").addEventListener("click", () => this.resetView());
+        $(".home-button").addEventListener("click", () => this.fitView());
         $(".fullscreen-button").addEventListener("click", () => this.toggleFullscreen());
         this.borderButton.addEventListener("click", () => this.toggleBrickOutlines());
         this.fillButton.addEventListener("click", () => this.toggleBrickFill());
         this.heightmapButton.addEventListener("click", () => this.toggleHeightmap());
+        this.isometricButton.addEventListener("click", () => this.toggleIsometric());
+        this.sectionButton.addEventListener("click", () => this.toggleSection());
+        this.undersideButton.addEventListener("click", () => this.toggleUnderside());
         $(".photo-button").addEventListener("click", () => this.takeScreenshot());
         $(".hd-photo-button").addEventListener("click", () => this.takeHDScreenshot(1));
-        $(".zoom-photo-button").addEventListener("click", () => this.takeHDScreenshot(10));
+        $(".zoom-photo-button").addEventListener("click", () => this.takeHDScreenshot(10, true));
+        this.overlaysButton.addEventListener("click", () => this.toggleOverlaysPanel());
+        this.overlaysPanel.addEventListener("change", e => this.handleOverlayChange(e.target));
         $(".load-button").addEventListener("click", () => this.clickFileInput());
         this.fileInput.addEventListener("change", e => this.handleFileSelected(e));
         $(".webgpu-notice-dismiss").addEventListener("click", () => this.dismissWebGPUNotice());
@@ -161,6 +216,9 @@ export default class Atlas {
         this.borderButton.classList.toggle("button-toggled", this.showOutlines);
         this.fillButton.classList.toggle("button-toggled", this.fillBricks);
         this.heightmapButton.classList.toggle("button-toggled", this.showHeightmap);
+        this.isometricButton.classList.toggle("button-toggled", this.isometric);
+        this.sectionButton.classList.toggle("button-toggled", this.showSection || this.sectionPoints !== null);
+        this.undersideButton.classList.toggle("button-toggled", this.showUnderside);
     }
 
     // Flip loading on, yield a frame so the spinner paints, then run the
@@ -184,6 +242,7 @@ export default class Atlas {
     handleMouseDownEvent(event) {
         event = this.handleTouchEvent(event);
         this.dragPos = { x: event.clientX, y: event.clientY };
+        this.pressPos = this.dragPos;
         this.isDragging = true;
     }
 
@@ -198,6 +257,11 @@ export default class Atlas {
 
     handleMouseUpEvent() {
         this.isDragging = false;
+        if (this.sectionPoints && this.pressPos) {
+            const moved = Math.hypot(this.dragPos.x - this.pressPos.x, this.dragPos.y - this.pressPos.y);
+            if (moved < CLICK_SLOP) this.pickSectionPoint(this.dragPos);
+        }
+        this.pressPos = null;
     }
 
     handleTouchEvent(event) {
@@ -242,7 +306,14 @@ export default class Atlas {
         const save = await rust.loadFile(bytes);
         this.replaceSave(save);
         this.map = name;
+        this.showSection = false;
+        this.sectionPoints = null;
+        this.syncToggleButtons();
         save.setViewMode(this.showOutlines, this.fillBricks, this.showHeightmap);
+        if (this.isometric) save.setProjection(ISOMETRIC_AZIMUTH, ISOMETRIC_ELEVATION);
+        if (this.showUnderside) save.setUnderside(true);
+        await this.applyOverlays();
+        this.applyMarkers();
         this.resetView();
         this.canvas.style.cursor = null;
 
@@ -250,7 +321,10 @@ export default class Atlas {
             const progress = save.loadStep(40);
             this.setLoadingProgress(progress);
             this.redraw();
-            if (progress >= 1) break;
+            if (progress >= 1) {
+                this.updateMarkerCounts();
+                break;
+            }
             await new Promise(resolve => setTimeout(resolve, 0));
         }
     }
@@ -277,38 +351,108 @@ export default class Atlas {
     // output isn't bounded by the browser's max 2d-canvas size — large builds
     // (Orion's Freebuild etc.) would otherwise exceed it and produce no image
     // at all. Tiles render sequentially to bound peak memory.
-    async takeHDScreenshot(zoom) {
+    async takeHDScreenshot(zoom, studs = false) {
         if (!this.save) return;
 
-        const scale = DEFAULT_SCALE * zoom;
-        const tileWidth = this.canvas.width;
-        const tileHeight = this.canvas.height;
+        // Close up, plates and bricks show their studs for the shot
+        const brickLook = studs && this.fillBricks && !this.showHeightmap && !this.showUnderside;
+        if (brickLook) {
+            this.save.setViewMode(this.showOutlines, this.fillBricks, false,
+                undefined, undefined, undefined, undefined, true);
+        }
 
+        // The image is the build's size at the zoom plus a margin, and the
+        // build is fit into it so the tiles' overshoot can be trimmed away
         const bounds = this.save.bounds();
+        const zoomed = DEFAULT_SCALE * zoom;
+        const width = Math.ceil((bounds[2] - bounds[0]) * zoomed) + FIT_MARGIN * 2;
+        const height = Math.ceil((bounds[3] - bounds[1]) * zoomed) + FIT_MARGIN * 2;
+        const scale = this.save.fitScale(width, height, DEFAULT_ROTATION, FIT_MARGIN) ?? zoomed;
+        const panX = -(bounds[0] + bounds[2]) / 2;
+        const panY = -(bounds[1] + bounds[3]) / 2;
+
+        const tileWidth = this.canvas.width;
+        const tileHeight = this.canvas.height;
+        const numCols = Math.max(1, Math.ceil(width / tileWidth));
+        const numRows = Math.max(1, Math.ceil(height / tileHeight));
+        // Tiles walk from the image's top-left corner
         const worldTileWidth = tileWidth / scale;
         const worldTileHeight = tileHeight / scale;
-        bounds[0] += worldTileWidth / 2;
-        bounds[1] += worldTileHeight / 2;
-        const imageWidth = bounds[2] - bounds[0];
-        const imageHeight = bounds[3] - bounds[1];
-        const numCols = Math.max(1, Math.ceil(imageWidth / worldTileWidth));
-        const numRows = Math.max(1, Math.ceil(imageHeight / worldTileHeight));
+        const startX = panX + (width - tileWidth) / 2 / scale;
+        const startY = panY + (height - tileHeight) / 2 / scale;
 
         try {
             this.imageCombiner.setLayout(tileWidth, tileHeight, numRows, numCols);
+            this.imageCombiner.setSize(width, height);
             for (let col = 0; col < numCols; col++) {
                 for (let row = 0; row < numRows; row++) {
                     const pixels = await this.save.renderToPixels(
                         tileWidth, tileHeight,
-                        -bounds[0] - col * worldTileWidth, -bounds[1] - row * worldTileHeight,
+                        startX - col * worldTileWidth, startY - row * worldTileHeight,
                         scale, DEFAULT_ROTATION);
                     this.imageCombiner.pushPixels(pixels, row, col);
                 }
             }
+            // Overlays go on last, over the image as a whole
+            this.imageCombiner.drawOverlays(this.save, panX, panY, scale, DEFAULT_ROTATION);
             const buffer = this.imageCombiner.combineImages();
             saveBlob(new Blob([buffer.buffer], { type: "image/png" }), `${this.map}.png`);
         } catch (err) {
             console.error(err);
+        } finally {
+            if (brickLook) this.save.setViewMode(this.showOutlines, this.fillBricks, this.showHeightmap);
+        }
+    }
+
+    toggleOverlaysPanel() {
+        const open = this.overlaysPanel.style.display === "none";
+        this.overlaysPanel.style.display = open ? "flex" : "none";
+        this.overlaysButton.classList.toggle("button-toggled", open);
+    }
+
+    handleOverlayChange(input) {
+        if (input.dataset.marker !== undefined) {
+            this.markers[input.name] = input.checked;
+            this.applyMarkers();
+            return;
+        }
+        this.overlays[input.name] = input.type === "checkbox" ? input.checked : input.value;
+        this.applyOverlays();
+    }
+
+    applyMarkers() {
+        if (!this.save) return;
+        const { spawns, lights, audio, interact } = this.markers;
+        this.save.setMarkers(spawns, lights, audio, interact);
+    }
+
+    // Shows how many markers of each category the save has, greying out
+    // the toggles of categories it has none of
+    updateMarkerCounts() {
+        const counts = this.save.markerCounts();
+        const inputs = this.overlaysPanel.querySelectorAll("input[data-marker]");
+        inputs.forEach((input, i) => {
+            const count = counts[i];
+            input.disabled = count === 0;
+            input.parentElement.classList.toggle("marker-none", count === 0);
+            input.parentElement.querySelector(".marker-count").textContent = `(${count.toLocaleString()})`;
+        });
+    }
+
+    // Overlay text needs a font, which the website's wasm leaves out to stay
+    // small, so the bundled one is fetched once an overlay is first turned on
+    async applyOverlays() {
+        if (!this.save) return;
+        const { grid, ticks, scaleBar, compass, labels } = this.overlays;
+        try {
+            if (!this.font && (grid || ticks || scaleBar || compass || labels)) {
+                const res = await fetch(OVERLAY_FONT);
+                this.font = new Uint8Array(await res.arrayBuffer());
+            }
+            if (this.font) this.save.setFont(this.font);
+            this.save.setOverlays(grid || undefined, ticks, scaleBar, compass, labels);
+        } catch (err) {
+            console.error(err);
         }
     }
 
@@ -347,6 +491,98 @@ export default class Atlas {
         this.withLoading(() => this.processSave());
     }
 
+    // The projection turns the build itself, so the map's rotation goes back
+    // to north-up either way
+    toggleIsometric() {
+        if (!this.save) return;
+        this.isometric = !this.isometric;
+        this.showSection = false;
+        this.sectionPoints = null;
+        this.syncToggleButtons();
+        this.withLoading(() => this.applyProjection());
+    }
+
+    // Only the top-down map has an underside, so it stays as it is
+    toggleUnderside() {
+        if (!this.save || this.isometric || this.showSection) return;
+        this.showUnderside = !this.showUnderside;
+        this.syncToggleButtons();
+        this.withLoading(() => {
+            try {
+                this.save.setUnderside(this.showUnderside);
+            } catch (err) {
+                console.error(err);
+            }
+            this.redraw();
+        });
+    }
+
+    applyProjection() {
+        try {
+            if (this.isometric) {
+                this.save.setProjection(ISOMETRIC_AZIMUTH, ISOMETRIC_ELEVATION);
+            } else {
+                this.save.setProjection();
+            }
+        } catch (err) {
+            console.error(err);
+        }
+        this.rotation = DEFAULT_ROTATION;
+        this.fitView();
+    }
+
+    // First click starts picking the cut line's two ends on the map; once
+    // a section shows, a click goes back to the map
+    toggleSection() {
+        if (!this.save) return;
+        if (this.showSection) {
+            this.showSection = false;
+            this.withLoading(() => this.applyProjection());
+        } else {
+            this.sectionPoints = this.sectionPoints ? null : [];
+            // Points are picked on the top-down map
+            if (this.sectionPoints && this.isometric) {
+                this.isometric = false;
+                this.withLoading(() => this.applyProjection());
+            }
+        }
+        this.canvas.style.cursor = this.sectionPoints ? "crosshair" : null;
+        this.syncToggleButtons();
+    }
+
+    pickSectionPoint(clientPos) {
+        this.sectionPoints.push(this.toWorld(clientPos));
+        if (this.sectionPoints.length < 2) return;
+
+        const [from, to] = this.sectionPoints;
+        this.sectionPoints = null;
+        this.canvas.style.cursor = null;
+        this.withLoading(() => {
+            try {
+                this.save.setSection(from.x, from.y, to.x, to.y, SECTION_THICKNESS);
+                this.showSection = true;
+            } catch (err) {
+                console.error(err);
+            }
+            this.syncToggleButtons();
+            this.rotation = DEFAULT_ROTATION;
+            this.fitView();
+        });
+    }
+
+    // Absolute save coordinates under a point on the page, undoing the
+    // view's pan, zoom and rotation about the centroid
+    toWorld(clientPos) {
+        const rect = this.canvas.getBoundingClientRect();
+        const dx = (clientPos.x - rect.left - this.canvas.width / 2) / this.scale;
+        const dy = (clientPos.y - rect.top - this.canvas.height / 2) / this.scale;
+        const centroid = this.save.centroid();
+        return {
+            x: Math.round(dx * Math.cos(this.rotation) - dy * Math.sin(this.rotation) - this.pan.x + centroid[0]),
+            y: Math.round(dx * Math.sin(this.rotation) + dy * Math.cos(this.rotation) - this.pan.y + centroid[1])
+        };
+    }
+
     getNewPan(panStart, panEnd) {
         let panDiff = {
             x: panEnd.x - panStart.x,
@@ -369,6 +605,18 @@ export default class Atlas {
         this.redraw();
     }
 
+    // Centers the build and zooms until all of it fits the canvas at the
+    // current rotation
+    fitView() {
+        if (!this.save) return;
+        const scale = this.save.fitScale(this.canvas.width, this.canvas.height, this.rotation, FIT_MARGIN);
+        if (scale === undefined) return;
+        const bounds = this.save.bounds();
+        this.pan = { x: -(bounds[0] + bounds[2]) / 2, y: -(bounds[1] + bounds[3]) / 2 };
+        this.scale = Math.min(MAX_SCALE, Math.max(MIN_SCALE, scale));
+        this.redraw();
+    }
+
     // Frees the old save's GPU buffers and device promptly instead of
     // waiting on wasm-bindgen's GC finalizer
     replaceSave(save) {
@@ -42,6 +42,28 @@ export const MOUNTAIN = `<svg xmlns="http://www.w3.org/2000/svg" class="icon ico
 <path d="M6 20l5 -15h2l5 15" />
 </svg>`;
 
+export const CUBE = `<svg xmlns="http://www.w3.org/2000/svg" class="icon icon-tabler icon-tabler-box" width="28" height="28" viewBox="0 0 24 24" stroke-width="1.5" stroke="#A7BBCE" fill="none" stroke-linecap="round" stroke-linejoin="round">
+<path stroke="none" d="M0 0h24v24H0z"/>
+<polyline points="12 3 20 7.5 20 16.5 12 21 4 16.5 4 7.5 12 3" />
+<line x1="12" y1="12" x2="20" y2="7.5" />
+<line x1="12" y1="12" x2="12" y2="21" />
+<line x1="12" y1="12" x2="4" y2="7.5" />
+</svg>`;
+
+export const SECTION = `<svg xmlns="http://www.w3.org/2000/svg" class="icon icon-tabler icon-tabler-section" width="28" height="28" viewBox="0 0 24 24" stroke-width="1.5" stroke="#A7BBCE" fill="none" stroke-linecap="round" stroke-linejoin="round">
+<path stroke="none" d="M0 0h24v24H0z"/>
+<rect x="4" y="4" width="16" height="16" rx="2" />
+<line x1="3" y1="21" x2="21" y2="3" stroke-dasharray="2 2" />
+</svg>`;
+
+export const UNDERSIDE = `<svg xmlns="http://www.w3.org/2000/svg" class="icon icon-tabler icon-tabler-underside" width="28" height="28" viewBox="0 0 24 24" stroke-width="1.5" stroke="#A7BBCE" fill="none" stroke-linecap="round" stroke-linejoin="round">
+<path stroke="none" d="M0 0h24v24H0z"/>
+<line x1="4" y1="5" x2="20" y2="5" />
+<path d="M7 5v4a5 5 0 0 0 10 0v-4" />
+<line x1="12" y1="20" x2="12" y2="14" />
+<polyline points="9 17 12 14 15 17" />
+</svg>`;
+
 export const PHOTO = `<svg xmlns="http://www.w3.org/2000/svg" class="icon icon-tabler icon-tabler-photo" width="28" height="28" viewBox="0 0 24 24" stroke-width="1.5" stroke="#A7BBCE" fill="none" stroke-linecap="round" stroke-linejoin="round">
 <path stroke="none" d="M0 0h24v24H0z"/>
 <line x1="15" y1="8" x2="15.01" y2="8" />
@@ -72,6 +94,13 @@ export const LEGO = `<svg xmlns="http://www.w3.org/2000/svg" class="icon icon-ta
 <path d="M7 5h1v-2h8v2h1a3 3 0 0 1 3 3v9a3 3 0 0 1 -3 3v1h-10v-1a3 3 0 0 1 -3 -3v-9a3 3 0 0 1 3 -3" />
 </svg>`;
 
+export const LAYERS = `<svg xmlns="http://www.w3.org/2000/svg" class="icon icon-tabler icon-tabler-stack" width="28" height="28" viewBox="0 0 24 24" stroke-width="1.5" stroke="#A7BBCE" fill="none" stroke-linecap="round" stroke-linejoin="round">
+<path stroke="none" d="M0 0h24v24H0z"/>
+<polyline points="12 4 4 8 12 12 20 8 12 4" />
+<polyline points="4 12 12 16 20 12" />
+<polyline points="4 16 12 20 20 16" />
+</svg>`;
+
 export const LOAD = `<svg xmlns="http://www.w3.org/2000/svg" class="icon icon-tabler icon-tabler-file" width="28" height="28" viewBox="0 0 24 24" stroke-width="1.5" stroke="#FFFFFF" fill="none" stroke-linecap="round" stroke-linejoin="round">
 <path stroke="none" d="M0 0h24v24H0z"/>
 <path d="M14 3v4a1 1 0 0 0 1 1h4" />